@@ -1,13 +1,19 @@
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
-use rocket::{error, Build, Ignite, Phase, Rocket, Sentinel, Orbit};
+use rocket::{error, info, span_error, span_info, warn};
+use rocket::{Build, Ignite, Phase, Rocket, Sentinel, Orbit, Response};
 use rocket::fairing::{self, Fairing, Info, Kind};
 use rocket::request::{FromRequest, Outcome, Request};
 use rocket::figment::providers::Serialized;
+use rocket::futures::future::BoxFuture;
 use rocket::http::Status;
 
-use crate::Pool;
+use crate::{Metrics, Migrate, Migrations, Pool, Transactional};
+use crate::metrics::Stats;
 
 /// Derivable trait which ties a database [`Pool`] with a configuration name.
 ///
@@ -84,7 +90,9 @@ pub trait Database: From<Self::Pool> + DerefMut<Target = Self::Pool> + Send + Sy
     /// # Example
     ///
     /// Run database migrations in an ignite fairing. It is imperative that the
-    /// migration fairing be registered _after_ the `init()` fairing.
+    /// migration fairing be registered _after_ the `init()` fairing. For
+    /// `sqlx` and `diesel` migrations, prefer to use
+    /// [`Initializer::migrations()`] instead.
     ///
     /// ```rust
     /// # #[cfg(feature = "sqlx_sqlite")] mod _inner {
@@ -126,6 +134,47 @@ pub trait Database: From<Self::Pool> + DerefMut<Target = Self::Pool> + Send + Sy
 
         None
     }
+
+    /// Checks that the database is reachable via [`Pool::ping()`].
+    ///
+    /// # Example
+    ///
+    /// Respond to a readiness probe.
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "sqlx_sqlite")] mod _inner {
+    /// # use rocket::get;
+    /// use rocket::http::Status;
+    /// use rocket_db_pools::{sqlx, Database};
+    ///
+    /// #[derive(Database)]
+    /// #[database("sqlite_db")]
+    /// struct Db(sqlx::SqlitePool);
+    ///
+    /// #[get("/ready")]
+    /// async fn ready(db: &Db) -> Status {
+    ///     match db.ping().await {
+    ///         Ok(()) => Status::Ok,
+    ///         Err(_) => Status::ServiceUnavailable,
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    fn ping(&self) -> BoxFuture<'_, Result<(), <Self::Pool as Pool>::Error>> {
+        Pool::ping(&**self)
+    }
+
+    /// Returns the current [`Metrics`] of the database in `rocket`, combining
+    /// the pool's [`Pool::status()`] with connection retrieval statistics. As
+    /// with [`Database::fetch()`], the initializer fairing must have already
+    /// executed for the `Option` to be `Some`.
+    ///
+    /// See [`Metrics`] for an example.
+    fn metrics<P: Phase>(rocket: &Rocket<P>) -> Option<Metrics> {
+        let db = Self::fetch(rocket)?;
+        let stats = rocket.state::<Stats<Self>>()?;
+        Some(stats.snapshot(Pool::status(&**db)))
+    }
 }
 
 /// A [`Fairing`] which initializes a [`Database`] and its connection pool.
@@ -144,13 +193,29 @@ pub trait Database: From<Self::Pool> + DerefMut<Target = Self::Pool> + Send + Sy
 ///
 ///   3. Calls [`Pool::init()`].
 ///
-///   4. Stores the database instance in managed storage, retrievable via
+///   4. If [`migrate`](crate::Config::migrate) is configured, applies or
+///      reports on pending migrations provided via [`Self::migrations()`],
+///      aborting launch on failure.
+///
+///   5. Stores the database instance in managed storage, retrievable via
 ///      [`Database::fetch()`].
 ///
+///   6. Calls [`Pool::init()`] for each of the configured
+///      [`replicas`](crate::Config::replicas), with `url` set to the replica's
+///      URL, and stores the pools for use by [`ReadConnection`].
+///
+/// On response, the fairing commits or rolls back any pending transactions
+/// started by the [`Transaction`] request guard, and on shutdown, it closes the
+/// pool and any replica pools via [`Pool::close()`].
+///
 /// The name of the fairing itself is `Initializer<D>`, with `D` replaced with
 /// the type name `D` unless a name is explicitly provided via
 /// [`Self::with_name()`].
-pub struct Initializer<D: Database>(Option<&'static str>, PhantomData<fn() -> D>);
+pub struct Initializer<D: Database>(
+    Option<&'static str>,
+    Option<Box<dyn Migrations<D::Pool>>>,
+    PhantomData<fn() -> D>,
+);
 
 /// A request guard which retrieves a single connection to a [`Database`].
 ///
@@ -194,13 +259,151 @@ pub struct Initializer<D: Database>(Option<&'static str>, PhantomData<fn() -> D>
 /// ```
 pub struct Connection<D: Database>(<D::Pool as Pool>::Connection);
 
+/// A request guard which retrieves a single connection to a read replica of a
+/// [`Database`], falling back to the primary database.
+///
+/// For a database type of `Db`, a request guard of `ReadConnection<Db>`
+/// retrieves a single connection to one of the replicas configured via
+/// [`replicas`](crate::Config::replicas). Replicas are selected in round-robin
+/// order. A replica from which a connection can't be retrieved is considered
+/// unhealthy and is skipped for the next 5 seconds. If there are no replicas,
+/// or no healthy replica yields a connection, a connection to the primary
+/// database is retrieved instead.
+///
+/// Replicas typically lag behind the primary. Use [`Connection`] to read data
+/// that must reflect prior writes.
+///
+/// The request guard succeeds and fails exactly as [`Connection`] does, with
+/// the failure conditions applying to the fallback connection to the primary.
+///
+/// ## Deref
+///
+/// A type of `ReadConnection<Db>` dereferences, mutably and immutably, to the
+/// native database connection type, exactly as [`Connection<Db>`] does.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "sqlx_sqlite")] mod _inner {
+/// # use rocket::get;
+/// # type Pool = rocket_db_pools::sqlx::SqlitePool;
+/// use rocket_db_pools::{sqlx, Database, ReadConnection};
+///
+/// #[derive(Database)]
+/// #[database("db")]
+/// struct Db(Pool);
+///
+/// #[get("/count")]
+/// async fn count(mut db: ReadConnection<Db>) -> Option<String> {
+///     sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM posts")
+///         .fetch_one(&mut **db)
+///         .await
+///         .ok()
+///         .map(|count| count.to_string())
+/// }
+/// # }
+/// ```
+pub struct ReadConnection<D: Database>(<D::Pool as Pool>::Connection);
+
+/// The replica pools of `D`, stored in managed state.
+struct Replicas<D: Database> {
+    replicas: Vec<Replica<D::Pool>>,
+    next: AtomicUsize,
+}
+
+/// A replica pool and the time until which it's considered unhealthy.
+struct Replica<P> {
+    pool: P,
+    unhealthy_until: Mutex<Option<Instant>>,
+}
+
+/// A request guard which begins a transaction on a single connection to a
+/// [`Database`].
+///
+/// For a database type of `Db`, a request guard of `Transaction<Db>` retrieves
+/// a single connection to `Db` and begins a transaction. The database's pool
+/// must implement [`Transactional`], as the `sqlx` and `diesel` drivers do.
+///
+/// The transaction is finalized once the response is known:
+///
+///   * If the response status is not an error (`1xx`, `2xx`, or `3xx`), the
+///     transaction is committed. If committing fails, the error is logged and
+///     the response is replaced with an empty `500 Internal Server Error`.
+///   * If the response status is an error (`4xx` or `5xx`), including when
+///     the handler panics, the transaction is rolled back.
+///   * If the request is abandoned, for instance because the client
+///     disconnected, the transaction is dropped and never committed.
+///
+/// To override this behavior, explicitly finalize the transaction via
+/// [`Transaction::commit()`] or [`Transaction::rollback()`].
+///
+/// Finalization is performed by the [`Initializer`] fairing in its response
+/// callback. As such, a transaction that is moved elsewhere, such as into a
+/// spawned task, and outlives the response is never committed automatically.
+///
+/// The request guard succeeds and fails exactly as [`Connection`] does. In
+/// particular, a failure to begin the transaction is treated as a failure to
+/// retrieve a connection.
+///
+/// ## Deref
+///
+/// A type of `Transaction<Db>` dereferences, mutably and immutably, to the
+/// driver's transaction type, [`Transactional::Transaction`]: an
+/// [`sqlx::Transaction`] for `sqlx` and the pooled connection for `diesel`.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "sqlx_sqlite")] mod _inner {
+/// # use rocket::post;
+/// # type Pool = rocket_db_pools::sqlx::SqlitePool;
+/// use rocket::http::Status;
+/// use rocket_db_pools::{sqlx, Database, Transaction};
+///
+/// #[derive(Database)]
+/// #[database("db")]
+/// struct Db(Pool);
+///
+/// #[post("/transfer/<from>/<to>")]
+/// async fn transfer(mut tx: Transaction<Db>, from: i64, to: i64) -> Status {
+///     let debit = sqlx::query("UPDATE accounts SET balance = balance - 1 WHERE id = ?")
+///         .bind(from)
+///         .execute(&mut **tx)
+///         .await;
+///
+///     let credit = sqlx::query("UPDATE accounts SET balance = balance + 1 WHERE id = ?")
+///         .bind(to)
+///         .execute(&mut **tx)
+///         .await;
+///
+///     // Both updates are committed on `200 Ok`; neither on `500`.
+///     match (debit, credit) {
+///         (Ok(_), Ok(_)) => Status::Ok,
+///         _ => Status::InternalServerError,
+///     }
+/// }
+/// # }
+/// ```
+///
+/// [`sqlx::Transaction`]: https://docs.rs/sqlx/0.8/sqlx/struct.Transaction.html
+pub struct Transaction<D: Database> where D::Pool: Transactional {
+    tx: Option<<D::Pool as Transactional>::Transaction>,
+    pending: Pending<D>,
+}
+
+/// A deferred commit (`true`) or roll back (`false`) of a transaction.
+type Finalizer = Box<dyn FnOnce(bool) -> BoxFuture<'static, Result<(), String>> + Send>;
+
+/// Transactions for `D` in a request awaiting finalization on response.
+struct Pending<D>(Arc<Mutex<Vec<Finalizer>>>, PhantomData<fn() -> D>);
+
 impl<D: Database> Initializer<D> {
     /// Returns a database initializer fairing for `D`.
     ///
     /// This method should never need to be called manually. See the [crate
     /// docs](crate) for usage information.
     pub fn new() -> Self {
-        Self(None, std::marker::PhantomData)
+        Self(None, None, std::marker::PhantomData)
     }
 
     /// Returns a database initializer fairing for `D` with name `name`.
@@ -208,7 +411,74 @@ impl<D: Database> Initializer<D> {
     /// This method should never need to be called manually. See the [crate
     /// docs](crate) for usage information.
     pub fn with_name(name: &'static str) -> Self {
-        Self(Some(name), std::marker::PhantomData)
+        Self(Some(name), None, std::marker::PhantomData)
+    }
+
+    /// Registers `migrations` to be applied to the database on ignition when
+    /// its [`migrate`](crate::Config::migrate) configuration parameter is set.
+    /// See [`Migrate`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(all(feature = "sqlx_migrate", feature = "sqlx_sqlite"))] mod _inner {
+    /// # use rocket::launch;
+    /// use rocket_db_pools::{sqlx, Database};
+    ///
+    /// #[derive(Database)]
+    /// #[database("sqlite_db")]
+    /// struct Db(sqlx::SqlitePool);
+    ///
+    /// #[launch]
+    /// fn rocket() -> _ {
+    ///     let migrations = sqlx::migrate!("db/migrations");
+    ///     rocket::build().attach(Db::init().migrations(migrations))
+    /// }
+    /// # }
+    /// ```
+    pub fn migrations<M: Migrations<D::Pool>>(mut self, migrations: M) -> Self {
+        self.1 = Some(Box::new(migrations));
+        self
+    }
+
+    /// Applies or reports on pending migrations according to `migrate`.
+    async fn migrate(&self, pool: &D::Pool, migrate: Migrate) -> Result<(), ()> {
+        let database = D::NAME;
+        let migrations = match (migrate, &self.1) {
+            (Migrate::Off, _) => return Ok(()),
+            (_, Some(migrations)) => migrations,
+            (_, None) => {
+                error!(database, "`migrate` is enabled but no migrations were registered\n\
+                    register migrations with `Initializer::migrations()`");
+
+                return Err(());
+            }
+        };
+
+        let result = match migrate {
+            Migrate::DryRun => migrations.pending(pool).await,
+            _ => migrations.run(pool).await,
+        };
+
+        match result {
+            Ok(names) if names.is_empty() => info!(database, "no pending migrations"),
+            Ok(names) => {
+                let dry_run = migrate == Migrate::DryRun;
+                let action = if dry_run { "pending" } else { "applied" };
+                span_info!("migrations", database, dry_run => {
+                    names.iter().for_each(|name| info!(migration = name, "{action}"));
+                });
+            }
+            Err(e) => {
+                span_error!("migrations", database, migration = ?e.migration => {
+                    error!(error = %e.error, "migrations failed");
+                });
+
+                return Err(());
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -243,12 +513,194 @@ impl<D: Database> Connection<D> {
     }
 }
 
+impl<D: Database> ReadConnection<D> {
+    /// Returns the internal connection value. See the [`Connection` Deref
+    /// column](crate#supported-drivers) for the expected type of this value.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "sqlx_sqlite")] mod _inner {
+    /// # use rocket::get;
+    /// # type Pool = rocket_db_pools::sqlx::SqlitePool;
+    /// use rocket_db_pools::{Database, ReadConnection};
+    ///
+    /// #[derive(Database)]
+    /// #[database("db")]
+    /// struct Db(Pool);
+    ///
+    /// #[get("/")]
+    /// async fn db_op(db: ReadConnection<Db>) {
+    ///     let inner = db.into_inner();
+    /// }
+    /// # }
+    /// ```
+    pub fn into_inner(self) -> <D::Pool as Pool>::Connection {
+        self.0
+    }
+}
+
+impl<D: Database> Replicas<D> {
+    /// How long a replica is skipped after failing to yield a connection.
+    const RETRY_AFTER: Duration = Duration::from_secs(5);
+
+    /// Retrieves a connection from the next healthy replica, if any.
+    async fn get(&self) -> Option<<D::Pool as Pool>::Connection> {
+        let n = self.replicas.len();
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        for i in (0..n).map(|i| (start + i) % n) {
+            let replica = &self.replicas[i];
+            if !replica.is_healthy() {
+                continue;
+            }
+
+            match replica.pool.get().await {
+                Ok(conn) => return Some(conn),
+                Err(e) => {
+                    warn!(database = D::NAME, replica = i, "replica unavailable: {e}");
+                    replica.mark_unhealthy(Self::RETRY_AFTER);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl<P> Replica<P> {
+    fn mark_unhealthy(&self, duration: Duration) {
+        *self.unhealthy_until.lock().unwrap() = Some(Instant::now() + duration);
+    }
+
+    fn is_healthy(&self) -> bool {
+        let mut until = self.unhealthy_until.lock().unwrap();
+        match *until {
+            Some(instant) if instant > Instant::now() => false,
+            _ => {
+                *until = None;
+                true
+            }
+        }
+    }
+}
+
+impl<D: Database> Transaction<D> where D::Pool: Transactional {
+    /// Commits the transaction immediately, regardless of the response.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "sqlx_sqlite")] mod _inner {
+    /// # use rocket::post;
+    /// # type Pool = rocket_db_pools::sqlx::SqlitePool;
+    /// use rocket::response::Debug;
+    /// use rocket_db_pools::{sqlx, Database, Transaction};
+    ///
+    /// #[derive(Database)]
+    /// #[database("db")]
+    /// struct Db(Pool);
+    ///
+    /// #[post("/log")]
+    /// async fn log(mut tx: Transaction<Db>) -> Result<(), Debug<sqlx::Error>> {
+    ///     sqlx::query("INSERT INTO logs (content) VALUES ('hit')")
+    ///         .execute(&mut **tx)
+    ///         .await?;
+    ///
+    ///     Ok(tx.commit().await?)
+    /// }
+    /// # }
+    /// ```
+    pub async fn commit(mut self) -> Result<(), <D::Pool as Transactional>::TransactionError> {
+        let tx = self.tx.take().expect("transaction is present until consumed");
+        <D::Pool as Transactional>::commit(tx).await
+    }
+
+    /// Rolls back the transaction immediately, regardless of the response.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "sqlx_sqlite")] mod _inner {
+    /// # use rocket::post;
+    /// # type Pool = rocket_db_pools::sqlx::SqlitePool;
+    /// use rocket::response::Debug;
+    /// use rocket_db_pools::{sqlx, Database, Transaction};
+    ///
+    /// #[derive(Database)]
+    /// #[database("db")]
+    /// struct Db(Pool);
+    ///
+    /// #[post("/dry-run")]
+    /// async fn dry_run(mut tx: Transaction<Db>) -> Result<String, Debug<sqlx::Error>> {
+    ///     let result = sqlx::query("DELETE FROM logs")
+    ///         .execute(&mut **tx)
+    ///         .await?;
+    ///
+    ///     tx.rollback().await?;
+    ///     Ok(format!("would delete {} rows", result.rows_affected()))
+    /// }
+    /// # }
+    /// ```
+    pub async fn rollback(mut self) -> Result<(), <D::Pool as Transactional>::TransactionError> {
+        let tx = self.tx.take().expect("transaction is present until consumed");
+        <D::Pool as Transactional>::rollback(tx).await
+    }
+
+    /// Returns the internal transaction value, which is no longer finalized
+    /// automatically. If it's dropped without being committed, it is not
+    /// committed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "sqlx_sqlite")] mod _inner {
+    /// # use rocket::get;
+    /// # type Pool = rocket_db_pools::sqlx::SqlitePool;
+    /// use rocket_db_pools::{Database, Transaction};
+    ///
+    /// #[derive(Database)]
+    /// #[database("db")]
+    /// struct Db(Pool);
+    ///
+    /// #[get("/")]
+    /// async fn db_op(tx: Transaction<Db>) {
+    ///     let inner = tx.into_inner();
+    /// }
+    /// # }
+    /// ```
+    pub fn into_inner(mut self) -> <D::Pool as Transactional>::Transaction {
+        self.tx.take().expect("transaction is present until consumed")
+    }
+}
+
+impl<D: Database> Pending<D> {
+    fn push(&self, finalizer: Finalizer) {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).push(finalizer);
+    }
+
+    fn take(&self) -> Vec<Finalizer> {
+        std::mem::take(&mut *self.0.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl<D> Clone for Pending<D> {
+    fn clone(&self) -> Self {
+        Pending(self.0.clone(), PhantomData)
+    }
+}
+
+impl<D> Default for Pending<D> {
+    fn default() -> Self {
+        Pending(Arc::default(), PhantomData)
+    }
+}
+
 #[rocket::async_trait]
 impl<D: Database> Fairing for Initializer<D> {
     fn info(&self) -> Info {
         Info {
             name: self.0.unwrap_or(std::any::type_name::<Self>()),
-            kind: Kind::Ignite | Kind::Shutdown,
+            kind: Kind::Ignite | Kind::Response | Kind::Shutdown,
         }
     }
 
@@ -262,11 +714,76 @@ impl<D: Database> Fairing for Initializer<D> {
             .join(Serialized::default("max_connections", workers * 4))
             .join(Serialized::default("connect_timeout", 5));
 
-        match <D::Pool>::init(&figment).await {
-            Ok(pool) => Ok(rocket.manage(D::from(pool))),
+        let migrate = match figment.find_value("migrate") {
+            Ok(_) => figment.extract_inner::<Migrate>("migrate"),
+            Err(_) => Ok(Migrate::Off),
+        };
+
+        let migrate = match migrate {
+            Ok(migrate) => migrate,
+            Err(e) => {
+                error!("database configuration error: {e}");
+                return Err(rocket);
+            }
+        };
+
+        let replica_urls: Vec<String> = match figment.extract_inner("replicas") {
+            Ok(urls) => urls,
+            Err(e) if e.missing() => vec![],
+            Err(e) => {
+                error!("database configuration error: {e}");
+                return Err(rocket);
+            }
+        };
+
+        let init = match rocket.state::<crate::testing::PoolInit<D>>() {
+            Some(init) => (init.0)(&figment),
+            None => <D::Pool>::init(&figment),
+        };
+
+        let pool = match init.await {
+            Ok(pool) => pool,
             Err(e) => {
                 error!("database initialization failed: {e}");
-                Err(rocket)
+                return Err(rocket);
+            }
+        };
+
+        let mut replicas = Vec::with_capacity(replica_urls.len());
+        for (i, url) in replica_urls.into_iter().enumerate() {
+            let figment = figment.clone().merge(Serialized::global("url", url));
+            match <D::Pool>::init(&figment).await {
+                Ok(pool) => replicas.push(Replica { pool, unhealthy_until: Mutex::new(None) }),
+                Err(e) => warn!(database = D::NAME, replica = i,
+                    "replica initialization failed: {e}\nskipping replica"),
+            }
+        }
+
+        if self.migrate(&pool, migrate).await.is_err() {
+            for replica in replicas {
+                replica.pool.close().await;
+            }
+
+            pool.close().await;
+            return Err(rocket);
+        }
+
+        Ok(rocket.manage(D::from(pool))
+            .manage(Replicas::<D> { replicas, next: AtomicUsize::new(0) })
+            .manage(Stats::<D>::default()))
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let finalizers = req.local_cache(Pending::<D>::default).take();
+        let commit = res.status().code < 400;
+        for finalize in finalizers {
+            if let Err(e) = finalize(commit).await {
+                let action = if commit { "commit" } else { "roll back" };
+                error!("failed to {action} `{}` transaction: {e}", D::NAME);
+                if commit {
+                    *res = Response::new();
+                    res.set_status(Status::InternalServerError);
+                }
             }
         }
     }
@@ -275,6 +792,12 @@ impl<D: Database> Fairing for Initializer<D> {
         if let Some(db) = D::fetch(rocket) {
             db.close().await;
         }
+
+        if let Some(replicas) = rocket.state::<Replicas<D>>() {
+            for replica in &replicas.replicas {
+                replica.pool.close().await;
+            }
+        }
     }
 }
 
@@ -284,7 +807,7 @@ impl<'r, D: Database> FromRequest<'r> for Connection<D> {
 
     async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
         match D::fetch(req.rocket()) {
-            Some(db) => match db.get().await {
+            Some(db) => match Stats::<D>::measure(req.rocket(), db.get()).await {
                 Ok(conn) => Outcome::Success(Connection(conn)),
                 Err(e) => Outcome::Error((Status::ServiceUnavailable, Some(e))),
             },
@@ -299,6 +822,87 @@ impl<D: Database> Sentinel for Connection<D> {
     }
 }
 
+#[rocket::async_trait]
+impl<'r, D: Database> FromRequest<'r> for ReadConnection<D> {
+    type Error = Option<<D::Pool as Pool>::Error>;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        if let Some(replicas) = req.rocket().state::<Replicas<D>>() {
+            if let Some(conn) = replicas.get().await {
+                return Outcome::Success(ReadConnection(conn));
+            }
+        }
+
+        match D::fetch(req.rocket()) {
+            Some(db) => match Stats::<D>::measure(req.rocket(), db.get()).await {
+                Ok(conn) => Outcome::Success(ReadConnection(conn)),
+                Err(e) => Outcome::Error((Status::ServiceUnavailable, Some(e))),
+            },
+            None => Outcome::Error((Status::InternalServerError, None)),
+        }
+    }
+}
+
+impl<D: Database> Sentinel for ReadConnection<D> {
+    fn abort(rocket: &Rocket<Ignite>) -> bool {
+        D::fetch(rocket).is_none()
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, D: Database> FromRequest<'r> for Transaction<D> where D::Pool: Transactional {
+    type Error = Option<<D::Pool as Pool>::Error>;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match D::fetch(req.rocket()) {
+            Some(db) => match Stats::<D>::measure(req.rocket(), db.begin()).await {
+                Ok(tx) => Outcome::Success(Transaction {
+                    tx: Some(tx),
+                    pending: req.local_cache(Pending::<D>::default).clone(),
+                }),
+                Err(e) => Outcome::Error((Status::ServiceUnavailable, Some(e))),
+            },
+            None => Outcome::Error((Status::InternalServerError, None)),
+        }
+    }
+}
+
+impl<D: Database> Sentinel for Transaction<D> where D::Pool: Transactional {
+    fn abort(rocket: &Rocket<Ignite>) -> bool {
+        D::fetch(rocket).is_none()
+    }
+}
+
+/// Defers finalization of an unconsumed transaction to the response.
+impl<D: Database> Drop for Transaction<D> where D::Pool: Transactional {
+    fn drop(&mut self) {
+        if let Some(tx) = self.tx.take() {
+            self.pending.push(Box::new(move |commit| Box::pin(async move {
+                let result = match commit {
+                    true => <D::Pool as Transactional>::commit(tx).await,
+                    false => <D::Pool as Transactional>::rollback(tx).await,
+                };
+
+                result.map_err(|e| e.to_string())
+            })));
+        }
+    }
+}
+
+impl<D: Database> Deref for Transaction<D> where D::Pool: Transactional {
+    type Target = <D::Pool as Transactional>::Transaction;
+
+    fn deref(&self) -> &Self::Target {
+        self.tx.as_ref().expect("transaction is present until consumed")
+    }
+}
+
+impl<D: Database> DerefMut for Transaction<D> where D::Pool: Transactional {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.tx.as_mut().expect("transaction is present until consumed")
+    }
+}
+
 impl<D: Database> Deref for Connection<D> {
     type Target = <D::Pool as Pool>::Connection;
 
@@ -312,3 +916,17 @@ impl<D: Database> DerefMut for Connection<D> {
         &mut self.0
     }
 }
+
+impl<D: Database> Deref for ReadConnection<D> {
+    type Target = <D::Pool as Pool>::Connection;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<D: Database> DerefMut for ReadConnection<D> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
@@ -109,10 +109,35 @@
 //! |----------|-----------------------------|-----------------------------|--------------------------------------|
 //! | Postgres | `deadpool_postgres` (v0.14) | [`deadpool_postgres::Pool`] | [`deadpool_postgres::ClientWrapper`] |
 //! | Redis    | `deadpool_redis` (v0.16)    | [`deadpool_redis::Pool`]    | [`deadpool_redis::Connection`]       |
+//! | SQLite   | `deadpool_sqlite` (v0.8)    | [`deadpool_sqlite::Pool`]   | [`deadpool_sqlite::Object`]          |
+//!
+//! `deadpool_sqlite` connections wrap a blocking [`rusqlite`] connection,
+//! which is used on a blocking thread via [`interact()`]:
+//!
+//! ```rust
+//! # #[cfg(feature = "deadpool_sqlite")] mod _inner {
+//! # use rocket::get;
+//! use rocket_db_pools::{deadpool_sqlite, Connection, Database};
+//!
+//! #[derive(Database)]
+//! #[database("sqlite_db")]
+//! struct Db(deadpool_sqlite::Pool);
+//!
+//! #[get("/<id>")]
+//! async fn name(db: Connection<Db>, id: i64) -> Option<String> {
+//!     db.interact(move |conn| {
+//!         conn.query_row("SELECT name FROM users WHERE id = ?1", [id], |row| row.get(0))
+//!     }).await.ok()?.ok()
+//! }
+//! # }
+//! ```
 //!
 //! On shutdown, new connections are denied. Shutdown _does not_ wait for
 //! connections to be returned.
 //!
+//! [`rusqlite`]: https://docs.rs/rusqlite/0.31
+//! [`interact()`]: https://docs.rs/deadpool-sync/0.1/deadpool_sync/struct.SyncWrapper.html#method.interact
+//!
 //! ## `sqlx` (v0.7)
 //!
 //! | Database | Feature         | [`Pool`] Type        | [`Connection`] Deref                     |
@@ -188,6 +213,10 @@
 //!
 //! # This option is only supported by the `sqlx_sqlite` driver.
 //! extensions = ["memvfs", "rot13"]
+//!
+//! # Backend-specific options, applied to every connection.
+//! [default.databases.db_name.sqlite]
+//! journal_mode = "wal"
 //! ```
 //!
 //! Or via environment variables:
@@ -196,7 +225,8 @@
 //! ROCKET_DATABASES='{db_name={url="db.sqlite",idle_timeout=120}}'
 //! ```
 //!
-//! See [`Config`] for details on configuration parameters.
+//! See [`Config`] for details on configuration parameters and
+//! [`SqliteConfig`] and [`PostgresConfig`] for backend-specific options.
 //!
 //! **Note:** `deadpool` and `diesel` drivers do not support and thus ignore the
 //! `min_connections` value.
@@ -226,6 +256,60 @@
 //!   - sslmode                  : `PREFERRED`
 //!   - statement-cache-capacity : `100`
 //!
+//! # Migrations
+//!
+//! Migrations for `sqlx` and `diesel` can be applied on ignition by registering
+//! them with [`Initializer::migrations()`] and enabling them via the
+//! [`migrate`](Config::migrate) configuration parameter:
+//!
+//! ```toml
+//! [default.databases.db_name]
+//! url = "db.sqlite"
+//! migrate = true
+//! ```
+//!
+//! Setting `migrate = "dry-run"` instead reports pending migrations without
+//! applying them. Support for `sqlx` migrations requires the `sqlx_migrate`
+//! feature, and support for `diesel` migrations, the `diesel_migrate` feature.
+//! See [`Migrations`] for details.
+//!
+//! # Read Replicas
+//!
+//! Read replicas of a database can be configured via the
+//! [`replicas`](Config::replicas) configuration parameter:
+//!
+//! ```toml
+//! [default.databases.db_name]
+//! url = "postgres://primary/db"
+//! replicas = ["postgres://replica-1/db", "postgres://replica-2/db"]
+//! ```
+//!
+//! Each replica is pooled separately. The [`ReadConnection`] request guard
+//! retrieves a connection from a healthy replica, falling back to the primary,
+//! while [`Connection`] and [`Transaction`] always connect to the primary.
+//!
+//! # Testing
+//!
+//! The [`testing`] module provides isolated, per-test databases which allow
+//! tests against a database to run in parallel:
+//!
+//! ```rust
+//! # #[cfg(feature = "sqlx_sqlite")] mod _inner {
+//! # use rocket_db_pools::{sqlx, Database};
+//! # #[derive(Database)]
+//! # #[database("db")]
+//! # struct Db(sqlx::SqlitePool);
+//! # fn rocket() -> rocket::Rocket<rocket::Build> { rocket::build() }
+//! use rocket::local::asynchronous::Client;
+//! use rocket_db_pools::testing::TestDatabase;
+//!
+//! # async fn f() {
+//! let rocket = TestDatabase::<Db>::memory().apply(rocket());
+//! let client = Client::tracked(rocket).await.expect("valid rocket");
+//! # }
+//! # }
+//! ```
+//!
 //! # Extending
 //!
 //! Any database driver can implement support for this library by implementing
@@ -246,6 +330,7 @@ pub use rocket::figment;
 #[cfg(any(feature = "diesel_postgres", feature = "diesel_mysql"))] pub mod diesel;
 #[cfg(feature = "deadpool_postgres")] pub use deadpool_postgres;
 #[cfg(feature = "deadpool_redis")] pub use deadpool_redis;
+#[cfg(feature = "deadpool_sqlite")] pub use deadpool_sqlite;
 #[cfg(feature = "mongodb")] pub use mongodb;
 #[cfg(feature = "sqlx")] pub use sqlx;
 
@@ -253,10 +338,16 @@ mod database;
 mod error;
 mod pool;
 mod config;
+mod migrations;
+mod metrics;
+
+pub mod testing;
 
-pub use self::database::{Connection, Database, Initializer};
+pub use self::database::{Connection, Database, Initializer, ReadConnection, Transaction};
 pub use self::error::Error;
-pub use self::pool::Pool;
-pub use self::config::Config;
+pub use self::pool::{Pool, Transactional};
+pub use self::config::{Config, Migrate, PostgresConfig, SqliteConfig};
+pub use self::migrations::{Migrations, MigrationError};
+pub use self::metrics::{Metrics, PoolStatus};
 
 pub use rocket_db_pools_codegen::*;
@@ -0,0 +1,169 @@
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use rocket::futures::Future;
+use rocket::{Phase, Rocket};
+
+use crate::{Database, Pool};
+
+/// A snapshot of the connections in a [`Pool`], returned by [`Pool::status()`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolStatus {
+    /// Number of connections currently open, idle or in use.
+    pub size: usize,
+    /// Number of open connections that are idle, awaiting retrieval.
+    pub idle: usize,
+    /// Number of open connections that are in use.
+    pub in_use: usize,
+    /// Number of tasks waiting to retrieve a connection, if known.
+    ///
+    /// Only `deadpool` and `diesel` drivers report this value.
+    pub waiters: Option<usize>,
+}
+
+/// Connection pool metrics for a [`Database`], returned by
+/// [`Database::metrics()`].
+///
+/// In addition to the pool's [`PoolStatus`], this reports statistics on
+/// connection retrievals made by the [`Connection`](crate::Connection),
+/// [`ReadConnection`](crate::ReadConnection), and
+/// [`Transaction`](crate::Transaction) request guards from the primary
+/// database since ignition. Connections retrieved directly via [`Pool::get()`]
+/// or from read replicas are not counted.
+///
+/// # Example
+///
+/// Report metrics as JSON, for instance from a fairing or, via
+/// [`Request::rocket()`](rocket::Request::rocket()), a request guard.
+///
+/// ```rust
+/// # #[cfg(feature = "sqlx_sqlite")] mod _inner {
+/// use rocket::{Rocket, Orbit};
+/// use rocket::serde::json::{json, Value};
+/// use rocket_db_pools::{sqlx, Database};
+///
+/// #[derive(Database)]
+/// #[database("db")]
+/// struct Db(sqlx::SqlitePool);
+///
+/// fn report(rocket: &Rocket<Orbit>) -> Option<Value> {
+///     let metrics = Db::metrics(rocket)?;
+///     let status = metrics.status.unwrap_or_default();
+///     Some(json!({
+///         "size": status.size,
+///         "idle": status.idle,
+///         "in_use": status.in_use,
+///         "acquired": metrics.acquired,
+///         "timeouts": metrics.timeouts,
+///         "mean_acquire_ms": metrics.mean_acquire_time().map(|t| t.as_millis()),
+///     }))
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Metrics {
+    /// The status of the pool, if reported by the driver.
+    ///
+    /// The `mongodb` driver does not report a status.
+    pub status: Option<PoolStatus>,
+    /// Number of connections successfully retrieved.
+    pub acquired: u64,
+    /// Number of failed attempts to retrieve a connection, including timeouts.
+    pub failed: u64,
+    /// Number of failed attempts to retrieve a connection that failed because
+    /// [`connect_timeout`](crate::Config::connect_timeout) elapsed.
+    pub timeouts: u64,
+    /// Total time spent retrieving connections, successfully or not.
+    pub acquire_time: Duration,
+    /// Longest time spent retrieving a single connection.
+    pub max_acquire_time: Duration,
+}
+
+impl Metrics {
+    /// Returns the mean time spent retrieving a connection, successfully or
+    /// not, or `None` if no connection retrieval has been attempted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use rocket_db_pools::Metrics;
+    ///
+    /// let metrics = Metrics {
+    ///     acquired: 3,
+    ///     failed: 1,
+    ///     acquire_time: Duration::from_millis(40),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(metrics.mean_acquire_time(), Some(Duration::from_millis(10)));
+    /// assert_eq!(Metrics::default().mean_acquire_time(), None);
+    /// ```
+    pub fn mean_acquire_time(&self) -> Option<Duration> {
+        let attempts = (self.acquired + self.failed) as u128;
+        let mean = self.acquire_time.as_nanos().checked_div(attempts)?;
+        Some(Duration::from_nanos(mean as u64))
+    }
+}
+
+/// Connection retrieval statistics for `D`, stored in managed state.
+pub(crate) struct Stats<D> {
+    acquired: AtomicU64,
+    failed: AtomicU64,
+    timeouts: AtomicU64,
+    acquire_nanos: AtomicU64,
+    max_acquire_nanos: AtomicU64,
+    _db: PhantomData<fn() -> D>,
+}
+
+impl<D: Database> Stats<D> {
+    /// Awaits `acquire`, recording its outcome and latency in `rocket`'s
+    /// `Stats<D>`, if there are any.
+    pub(crate) async fn measure<P: Phase, T, F>(rocket: &Rocket<P>, acquire: F) -> F::Output
+        where F: Future<Output = Result<T, <D::Pool as Pool>::Error>>
+    {
+        let start = Instant::now();
+        let result = acquire.await;
+        if let Some(stats) = rocket.state::<Self>() {
+            let nanos = start.elapsed().as_nanos() as u64;
+            stats.acquire_nanos.fetch_add(nanos, Ordering::Relaxed);
+            stats.max_acquire_nanos.fetch_max(nanos, Ordering::Relaxed);
+            match &result {
+                Ok(_) => stats.acquired.fetch_add(1, Ordering::Relaxed),
+                Err(e) if <D::Pool>::is_timeout(e) => {
+                    stats.timeouts.fetch_add(1, Ordering::Relaxed);
+                    stats.failed.fetch_add(1, Ordering::Relaxed)
+                }
+                Err(_) => stats.failed.fetch_add(1, Ordering::Relaxed),
+            };
+        }
+
+        result
+    }
+
+    /// Returns a snapshot of the statistics as `Metrics`.
+    pub(crate) fn snapshot(&self, status: Option<PoolStatus>) -> Metrics {
+        Metrics {
+            status,
+            acquired: self.acquired.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            timeouts: self.timeouts.load(Ordering::Relaxed),
+            acquire_time: Duration::from_nanos(self.acquire_nanos.load(Ordering::Relaxed)),
+            max_acquire_time: Duration::from_nanos(self.max_acquire_nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+impl<D> Default for Stats<D> {
+    fn default() -> Self {
+        Stats {
+            acquired: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            timeouts: AtomicU64::new(0),
+            acquire_nanos: AtomicU64::new(0),
+            max_acquire_nanos: AtomicU64::new(0),
+            _db: PhantomData,
+        }
+    }
+}
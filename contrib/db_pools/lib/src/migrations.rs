@@ -0,0 +1,198 @@
+use std::fmt;
+
+use crate::Pool;
+
+/// A source of database migrations for a [`Pool`].
+///
+/// A value implementing `Migrations` can be registered with a database's
+/// [`Initializer`](crate::Initializer) via
+/// [`Initializer::migrations()`](crate::Initializer::migrations()). When the
+/// database's [`migrate`](crate::Config::migrate) configuration parameter is
+/// set, the migrations are applied or reported on ignition. See
+/// [`Migrate`](crate::Migrate) for details.
+///
+/// This crate provides implementations for `sqlx`'s [`Migrator`], with the
+/// `sqlx_migrate` feature enabled, and `diesel`'s [`EmbeddedMigrations`], with
+/// the `diesel_migrate` feature enabled.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(all(feature = "sqlx_migrate", feature = "sqlx_sqlite"))] mod _inner {
+/// # use rocket::launch;
+/// use rocket_db_pools::{sqlx, Database};
+///
+/// #[derive(Database)]
+/// #[database("sqlite_db")]
+/// struct Db(sqlx::SqlitePool);
+///
+/// #[launch]
+/// fn rocket() -> _ {
+///     let migrations = sqlx::migrate::Migrator::DEFAULT;
+///     rocket::build().attach(Db::init().migrations(migrations))
+/// }
+/// # }
+/// ```
+///
+/// ## Async Trait
+///
+/// Like [`Pool`], `Migrations` is an _async_ trait. Implementations must be
+/// decorated with `#[async_trait]`.
+///
+/// [`Migrator`]: https://docs.rs/sqlx/0.8/sqlx/migrate/struct.Migrator.html
+/// [`EmbeddedMigrations`]: https://docs.rs/diesel_migrations/2/diesel_migrations/struct.EmbeddedMigrations.html
+#[rocket::async_trait]
+pub trait Migrations<P: Pool>: Send + Sync + 'static {
+    /// Returns the names of the migrations that have yet to be applied to the
+    /// database in `pool`, in the order they would be applied.
+    async fn pending(&self, pool: &P) -> Result<Vec<String>, MigrationError>;
+
+    /// Applies all pending migrations to the database in `pool`, returning the
+    /// names of the applied migrations in the order they were applied.
+    async fn run(&self, pool: &P) -> Result<Vec<String>, MigrationError>;
+}
+
+/// An error that occurred while inspecting or applying migrations.
+#[derive(Debug)]
+pub struct MigrationError {
+    /// The name of the migration that failed, if known.
+    pub migration: Option<String>,
+    /// The underlying error.
+    pub error: Box<dyn std::error::Error + Send + Sync>,
+}
+
+impl MigrationError {
+    /// Creates a new `MigrationError` from `error` without a known failing
+    /// migration.
+    pub fn new<E>(error: E) -> Self
+        where E: Into<Box<dyn std::error::Error + Send + Sync>>
+    {
+        MigrationError { migration: None, error: error.into() }
+    }
+
+    /// Sets the name of the failing migration to `migration`.
+    pub fn with_migration<S: Into<String>>(mut self, migration: S) -> Self {
+        self.migration = Some(migration.into());
+        self
+    }
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.migration {
+            Some(migration) => write!(f, "migration `{}` failed: {}", migration, self.error),
+            None => write!(f, "migrations failed: {}", self.error),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.error)
+    }
+}
+
+#[cfg(feature = "sqlx_migrate")]
+mod sqlx {
+    use std::collections::HashSet;
+
+    use sqlx::migrate::{Migrate, MigrateError, Migrator};
+    use super::{Migrations, MigrationError};
+
+    fn error(e: MigrateError) -> MigrationError {
+        match e {
+            MigrateError::ExecuteMigration(e, version) => {
+                MigrationError::new(e).with_migration(version.to_string())
+            }
+            e => MigrationError::new(e),
+        }
+    }
+
+    fn name(migration: &sqlx::migrate::Migration) -> String {
+        format!("{}_{}", migration.version, migration.description)
+    }
+
+    #[rocket::async_trait]
+    impl<D: sqlx::Database> Migrations<sqlx::Pool<D>> for Migrator
+        where D::Connection: Migrate
+    {
+        async fn pending(&self, pool: &sqlx::Pool<D>) -> Result<Vec<String>, MigrationError> {
+            let mut conn = pool.acquire().await.map_err(MigrationError::new)?;
+            conn.ensure_migrations_table().await.map_err(error)?;
+            let applied: HashSet<i64> = conn.list_applied_migrations().await
+                .map_err(error)?
+                .into_iter()
+                .map(|m| m.version)
+                .collect();
+
+            Ok(self.iter()
+                .filter(|m| m.migration_type.is_up_migration())
+                .filter(|m| !applied.contains(&m.version))
+                .map(name)
+                .collect())
+        }
+
+        async fn run(&self, pool: &sqlx::Pool<D>) -> Result<Vec<String>, MigrationError> {
+            let pending = self.pending(pool).await?;
+            Migrator::run(self, pool).await.map_err(error)?;
+            Ok(pending)
+        }
+    }
+}
+
+#[cfg(feature = "diesel_migrate")]
+mod diesel {
+    use deadpool::managed::{Object, Pool};
+    use diesel_async::AsyncConnection;
+    use diesel_async::async_connection_wrapper::AsyncConnectionWrapper;
+    use diesel_async::pooled_connection::AsyncDieselConnectionManager;
+    use diesel_migrations::{EmbeddedMigrations, MigrationHarness};
+
+    use super::{Migrations, MigrationError};
+
+    type Wrapper<C> = AsyncConnectionWrapper<Object<AsyncDieselConnectionManager<C>>>;
+
+    /// Runs `f` with a blocking connection from `pool` on a blocking thread.
+    async fn with_blocking_conn<C, F, T>(pool: &Pool<AsyncDieselConnectionManager<C>>, f: F)
+        -> Result<T, MigrationError>
+        where C: AsyncConnection + Send + 'static,
+              Wrapper<C>: From<Object<AsyncDieselConnectionManager<C>>>,
+              F: FnOnce(&mut Wrapper<C>) -> Result<T, MigrationError> + Send + 'static,
+              T: Send + 'static,
+    {
+        let conn = pool.get().await.map_err(|e| MigrationError::new(e.to_string()))?;
+        rocket::tokio::task::spawn_blocking(move || f(&mut conn.into()))
+            .await
+            .map_err(MigrationError::new)?
+    }
+
+    #[rocket::async_trait]
+    impl<C> Migrations<Pool<AsyncDieselConnectionManager<C>>> for EmbeddedMigrations
+        where C: AsyncConnection + Send + 'static,
+              Pool<AsyncDieselConnectionManager<C>>: crate::Pool,
+              Wrapper<C>: From<Object<AsyncDieselConnectionManager<C>>>
+                  + MigrationHarness<C::Backend>,
+    {
+        async fn pending(
+            &self,
+            pool: &Pool<AsyncDieselConnectionManager<C>>,
+        ) -> Result<Vec<String>, MigrationError> {
+            let source = self.clone();
+            with_blocking_conn(pool, move |conn| {
+                let pending = conn.pending_migrations(source).map_err(MigrationError::new)?;
+                Ok(pending.iter().map(|m| m.name().to_string()).collect())
+            }).await
+        }
+
+        async fn run(
+            &self,
+            pool: &Pool<AsyncDieselConnectionManager<C>>,
+        ) -> Result<Vec<String>, MigrationError> {
+            let source = self.clone();
+            with_blocking_conn(pool, move |conn| {
+                let applied = conn.run_pending_migrations(source).map_err(MigrationError::new)?;
+                Ok(applied.iter().map(|v| v.to_string()).collect())
+            }).await
+        }
+    }
+}
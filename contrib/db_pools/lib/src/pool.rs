@@ -3,6 +3,8 @@ use rocket::figment::Figment;
 #[allow(unused_imports)]
 use {std::time::Duration, crate::{Error, Config}};
 
+use crate::PoolStatus;
+
 /// Generic [`Database`](crate::Database) driver connection pool trait.
 ///
 /// This trait provides a generic interface to various database pooling
@@ -114,7 +116,7 @@ use {std::time::Duration, crate::{Error, Config}};
 /// }
 /// ```
 #[rocket::async_trait]
-pub trait Pool: Sized + Send + 'static {
+pub trait Pool: Sized + Send + Sync + 'static {
     /// The connection type managed by this pool, returned by [`Self::get()`].
     type Connection;
 
@@ -150,68 +152,255 @@ pub trait Pool: Sized + Send + 'static {
     /// The returned future may either resolve when all connections are known to
     /// have closed or at any point prior. Details are implementation specific.
     async fn close(&self);
+
+    /// Returns a snapshot of the connections in the pool or `None` if the pool
+    /// does not report its status.
+    ///
+    /// The default implementation returns `None`.
+    fn status(&self) -> Option<PoolStatus> {
+        None
+    }
+
+    /// Checks that the database is reachable by retrieving a connection and,
+    /// where supported, issuing a trivial query on it.
+    ///
+    /// The default implementation only retrieves a connection via
+    /// [`Self::get()`].
+    ///
+    /// ## Errors
+    ///
+    /// This method returns an error if a connection could not be retrieved or
+    /// the database failed to respond to the query.
+    async fn ping(&self) -> Result<(), Self::Error> {
+        self.get().await.map(|_| ())
+    }
+
+    /// Returns `true` if `error`, as returned by [`Self::get()`], indicates
+    /// that a timeout elapsed before a connection could be retrieved.
+    ///
+    /// The default implementation returns `false`.
+    fn is_timeout(error: &Self::Error) -> bool {
+        let _ = error;
+        false
+    }
+}
+
+/// A [`Pool`] whose connections support transactions.
+///
+/// Implementing this trait enables the [`Transaction`](crate::Transaction)
+/// request guard for databases backed by the pool. This crate provides
+/// implementations for the `sqlx` and `diesel` drivers.
+///
+/// Like [`Pool`], this is an _async_ trait, and implementations must be
+/// decorated with `#[async_trait]`.
+///
+/// ## Dropping
+///
+/// A transaction that is dropped without being passed to [`Self::commit()`] or
+/// [`Self::rollback()`] must not be committed. Ideally, it is rolled back as
+/// the `sqlx` driver does. Otherwise, the connection must be discarded as the
+/// `diesel` driver does.
+#[rocket::async_trait]
+pub trait Transactional: Pool {
+    /// An in-progress transaction, returned by [`Self::begin()`].
+    type Transaction: Send + 'static;
+
+    /// The error type returned by [`Self::commit()`] and [`Self::rollback()`].
+    type TransactionError: std::error::Error + Send + 'static;
+
+    /// Retrieves a connection from the pool and begins a transaction on it.
+    ///
+    /// ## Errors
+    ///
+    /// This method returns an error if a connection could not be retrieved or
+    /// the transaction could not be started.
+    async fn begin(&self) -> Result<Self::Transaction, Self::Error>;
+
+    /// Commits the transaction `tx`.
+    async fn commit(tx: Self::Transaction) -> Result<(), Self::TransactionError>;
+
+    /// Rolls back the transaction `tx`.
+    async fn rollback(tx: Self::Transaction) -> Result<(), Self::TransactionError>;
 }
 
 #[cfg(feature = "deadpool")]
-mod deadpool_postgres {
-    use deadpool::{Runtime, managed::{Manager, Pool, PoolError, Object}};
-    use super::{Duration, Error, Config, Figment};
+pub(crate) mod deadpool_postgres {
+    use std::ops::DerefMut;
+
+    use deadpool::{Runtime, managed::{Hook, Manager, Pool, PoolBuilder, PoolError, Object}};
+    use super::{Duration, Error, Config, Figment, PoolStatus};
 
     #[cfg(feature = "diesel")]
     use diesel_async::pooled_connection::AsyncDieselConnectionManager;
 
+    #[rocket::async_trait]
     pub trait DeadManager: Manager + Sized + Send + 'static {
         fn new(config: &Config) -> Result<Self, Self::Error>;
+
+        async fn ping(conn: &mut Self::Type) -> Result<(), Self::Error>;
+
+        /// Returns a hook to run on each newly created connection, if any.
+        fn post_create(config: &Config) -> Result<Option<Hook<Self>>, Self::Error> {
+            let _ = config;
+            Ok(None)
+        }
     }
 
     #[cfg(feature = "deadpool_postgres")]
+    #[rocket::async_trait]
     impl DeadManager for deadpool_postgres::Manager {
         fn new(config: &Config) -> Result<Self, Self::Error> {
-            Ok(Self::new(config.url.parse()?, deadpool_postgres::tokio_postgres::NoTls))
+            use deadpool_postgres::tokio_postgres::{Config as PgConfig, NoTls};
+
+            let mut pg_config: PgConfig = config.url.parse()?;
+            if let Some(ref postgres) = config.postgres {
+                if let Some(ref name) = postgres.application_name {
+                    pg_config.application_name(name);
+                }
+
+                if let Some(timeout) = postgres.statement_timeout {
+                    let options = match pg_config.get_options() {
+                        Some(options) => format!("{options} -c statement_timeout={timeout}"),
+                        None => format!("-c statement_timeout={timeout}"),
+                    };
+
+                    pg_config.options(&options);
+                }
+            }
+
+            Ok(Self::new(pg_config, NoTls))
+        }
+
+        async fn ping(conn: &mut Self::Type) -> Result<(), Self::Error> {
+            conn.simple_query("SELECT 1").await.map(|_| ())
         }
     }
 
     #[cfg(feature = "deadpool_redis")]
+    #[rocket::async_trait]
     impl DeadManager for deadpool_redis::Manager {
         fn new(config: &Config) -> Result<Self, Self::Error> {
             Self::new(config.url.as_str())
         }
+
+        async fn ping(conn: &mut Self::Type) -> Result<(), Self::Error> {
+            let _: String = deadpool_redis::redis::cmd("PING").query_async(conn).await?;
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "deadpool_sqlite")]
+    #[rocket::async_trait]
+    impl DeadManager for deadpool_sqlite::Manager {
+        fn new(config: &Config) -> Result<Self, Self::Error> {
+            let sqlite_config = deadpool_sqlite::Config::new(&config.url);
+            Ok(Self::from_config(&sqlite_config, Runtime::Tokio1))
+        }
+
+        async fn ping(conn: &mut Self::Type) -> Result<(), Self::Error> {
+            use deadpool_sqlite::rusqlite::{ffi, Error as SqliteError};
+
+            conn.interact(|conn| conn.query_row("SELECT 1", [], |_| Ok(()))).await
+                .map_err(|e| {
+                    let code = ffi::Error::new(ffi::SQLITE_ABORT);
+                    SqliteError::SqliteFailure(code, Some(e.to_string()))
+                })?
+        }
+
+        fn post_create(config: &Config) -> Result<Option<Hook<Self>>, Self::Error> {
+            use std::sync::Arc;
+            use deadpool::managed::HookError;
+            use deadpool_sqlite::rusqlite::{self, Error as SqliteError};
+
+            // Apply SQLite configuration as `PRAGMA`s. Draining rows ensures
+            // pragmas that return their new value, like `journal_mode`, apply.
+            fn apply(
+                conn: &rusqlite::Connection,
+                pragmas: &[(String, String)],
+            ) -> rusqlite::Result<()> {
+                for (name, value) in pragmas {
+                    let mut stmt = conn.prepare(&format!("PRAGMA {name} = {value}"))?;
+                    let mut rows = stmt.query([])?;
+                    while rows.next()?.is_some() { }
+                }
+
+                Ok(())
+            }
+
+            let sqlite = config.sqlite.clone().unwrap_or_default();
+            let busy_timeout = sqlite.busy_timeout.unwrap_or(config.connect_timeout * 1000);
+            let mut pragmas = vec![("busy_timeout".to_string(), busy_timeout.to_string())];
+            if let Some(ref mode) = sqlite.journal_mode {
+                pragmas.push(("journal_mode".into(), mode.clone()));
+            }
+
+            if let Some(ref level) = sqlite.synchronous {
+                pragmas.push(("synchronous".into(), level.clone()));
+            }
+
+            if let Some(enabled) = sqlite.foreign_keys {
+                pragmas.push(("foreign_keys".into(), enabled.to_string()));
+            }
+
+            pragmas.extend(sqlite.rendered_pragmas()
+                .map_err(|e| SqliteError::ToSqlConversionFailure(e.into()))?);
+
+            let pragmas = Arc::new(pragmas);
+            Ok(Some(Hook::async_fn(move |conn: &mut Self::Type, _| {
+                let pragmas = pragmas.clone();
+                Box::pin(async move {
+                    conn.interact(move |conn| apply(conn, &pragmas)).await
+                        .map_err(|e| HookError::Message(e.to_string().into()))?
+                        .map_err(HookError::Backend)
+                })
+            })))
+        }
+    }
+
+    #[cfg(any(feature = "diesel_postgres", feature = "diesel_mysql"))]
+    async fn diesel_ping<C>(conn: &mut C) -> Result<(), diesel_async::pooled_connection::PoolError>
+        where C: diesel_async::AsyncConnection
+    {
+        use diesel_async::pooled_connection::PoolError;
+
+        conn.batch_execute("SELECT 1").await.map_err(PoolError::QueryError)
     }
 
     #[cfg(feature = "diesel_postgres")]
+    #[rocket::async_trait]
     impl DeadManager for AsyncDieselConnectionManager<diesel_async::AsyncPgConnection> {
         fn new(config: &Config) -> Result<Self, Self::Error> {
             Ok(Self::new(config.url.as_str()))
         }
+
+        async fn ping(conn: &mut Self::Type) -> Result<(), Self::Error> {
+            diesel_ping(conn).await
+        }
     }
 
     #[cfg(feature = "diesel_mysql")]
+    #[rocket::async_trait]
     impl DeadManager for AsyncDieselConnectionManager<diesel_async::AsyncMysqlConnection> {
         fn new(config: &Config) -> Result<Self, Self::Error> {
             Ok(Self::new(config.url.as_str()))
         }
+
+        async fn ping(conn: &mut Self::Type) -> Result<(), Self::Error> {
+            diesel_ping(conn).await
+        }
     }
 
     #[rocket::async_trait]
     impl<M: DeadManager, C: From<Object<M>>> crate::Pool for Pool<M, C>
-        where M::Type: Send, C: Send + 'static, M::Error: std::error::Error
+        where M::Type: Send, M::Error: std::error::Error,
+              C: DerefMut<Target = M::Type> + Send + 'static
     {
         type Error = Error<PoolError<M::Error>>;
 
         type Connection = C;
 
         async fn init(figment: &Figment) -> Result<Self, Self::Error> {
-            let config: Config = figment.extract()?;
-            let manager = M::new(&config).map_err(|e| Error::Init(e.into()))?;
-
-            Pool::builder(manager)
-                .max_size(config.max_connections)
-                .wait_timeout(Some(Duration::from_secs(config.connect_timeout)))
-                .create_timeout(Some(Duration::from_secs(config.connect_timeout)))
-                .recycle_timeout(config.idle_timeout.map(Duration::from_secs))
-                .runtime(Runtime::Tokio1)
-                .build()
-                .map_err(|_| Error::Init(PoolError::NoRuntimeSpecified))
+            builder(figment)?.build().map_err(|_| Error::Init(PoolError::NoRuntimeSpecified))
         }
 
         async fn get(&self) -> Result<Self::Connection, Self::Error> {
@@ -221,19 +410,96 @@ mod deadpool_postgres {
         async fn close(&self) {
             <Pool<M, C>>::close(self)
         }
+
+        fn status(&self) -> Option<PoolStatus> {
+            let status = <Pool<M, C>>::status(self);
+            Some(PoolStatus {
+                size: status.size,
+                idle: status.available,
+                in_use: status.size.saturating_sub(status.available),
+                waiters: Some(status.waiting),
+            })
+        }
+
+        async fn ping(&self) -> Result<(), Self::Error> {
+            let mut conn = <Pool<M, C>>::get(self).await.map_err(Error::Get)?;
+            M::ping(&mut *conn).await.map_err(|e| Error::Get(PoolError::Backend(e)))
+        }
+
+        fn is_timeout(error: &Self::Error) -> bool {
+            matches!(error, Error::Get(PoolError::Timeout(_)))
+        }
+    }
+
+    /// Returns a builder for the pool configured by `figment`.
+    pub(crate) fn builder<M: DeadManager, C: From<Object<M>>>(
+        figment: &Figment,
+    ) -> Result<PoolBuilder<M, C>, Error<PoolError<M::Error>>> {
+        let config: Config = figment.extract()?;
+        let manager = M::new(&config).map_err(|e| Error::Init(e.into()))?;
+        let post_create = M::post_create(&config).map_err(|e| Error::Init(e.into()))?;
+
+        let mut builder = Pool::builder(manager)
+            .max_size(config.max_connections)
+            .wait_timeout(Some(Duration::from_secs(config.connect_timeout)))
+            .create_timeout(Some(Duration::from_secs(config.connect_timeout)))
+            .recycle_timeout(config.idle_timeout.map(Duration::from_secs))
+            .runtime(Runtime::Tokio1);
+
+        if let Some(hook) = post_create {
+            builder = builder.post_create(hook);
+        }
+
+        Ok(builder)
+    }
+
+    #[cfg(feature = "diesel")]
+    #[rocket::async_trait]
+    impl<C> crate::Transactional for Pool<AsyncDieselConnectionManager<C>>
+        where C: diesel_async::AsyncConnection + Send + 'static,
+              AsyncDieselConnectionManager<C>: DeadManager + Manager<
+                  Type = C,
+                  Error = diesel_async::pooled_connection::PoolError,
+              >,
+    {
+        type Transaction = Object<AsyncDieselConnectionManager<C>>;
+
+        type TransactionError = diesel::result::Error;
+
+        async fn begin(&self) -> Result<Self::Transaction, Self::Error> {
+            use diesel_async::{TransactionManager, pooled_connection::PoolError as Backend};
+
+            let mut conn = self.get().await.map_err(Error::Get)?;
+            C::TransactionManager::begin_transaction(&mut *conn).await
+                .map_err(|e| Error::Get(PoolError::Backend(Backend::QueryError(e))))?;
+
+            Ok(conn)
+        }
+
+        async fn commit(mut tx: Self::Transaction) -> Result<(), Self::TransactionError> {
+            use diesel_async::TransactionManager;
+
+            C::TransactionManager::commit_transaction(&mut *tx).await
+        }
+
+        async fn rollback(mut tx: Self::Transaction) -> Result<(), Self::TransactionError> {
+            use diesel_async::TransactionManager;
+
+            C::TransactionManager::rollback_transaction(&mut *tx).await
+        }
     }
 }
 
 #[cfg(feature = "sqlx")]
-mod sqlx {
+pub(crate) mod sqlx {
     use sqlx::ConnectOptions;
-    use super::{Duration, Error, Config, Figment};
+    use super::{Duration, Error, Config, Figment, PoolStatus};
     use rocket::tracing::level_filters::LevelFilter;
 
-    type Options<D> = <<D as sqlx::Database>::Connection as sqlx::Connection>::Options;
+    pub(crate) type Options<D> = <<D as sqlx::Database>::Connection as sqlx::Connection>::Options;
 
     // Provide specialized configuration for particular databases.
-    fn specialize(__options: &mut dyn std::any::Any, __config: &Config) {
+    fn specialize(__options: &mut dyn std::any::Any, __config: &Config) -> Result<(), sqlx::Error> {
         #[cfg(feature = "sqlx_sqlite")]
         if let Some(o) = __options.downcast_mut::<sqlx::sqlite::SqliteConnectOptions>() {
             *o = std::mem::take(o)
@@ -245,7 +511,80 @@ mod sqlx {
                     *o = std::mem::take(o).extension(ext.clone());
                 }
             }
+
+            if let Some(ref sqlite) = __config.sqlite {
+                if let Some(ref mode) = sqlite.journal_mode {
+                    *o = std::mem::take(o).journal_mode(mode.parse()?);
+                }
+
+                if let Some(ref level) = sqlite.synchronous {
+                    *o = std::mem::take(o).synchronous(level.parse()?);
+                }
+
+                if let Some(timeout) = sqlite.busy_timeout {
+                    *o = std::mem::take(o).busy_timeout(Duration::from_millis(timeout));
+                }
+
+                if let Some(enabled) = sqlite.foreign_keys {
+                    *o = std::mem::take(o).foreign_keys(enabled);
+                }
+
+                let pragmas = sqlite.rendered_pragmas()
+                    .map_err(|e| sqlx::Error::Configuration(e.into()))?;
+                for (name, value) in pragmas {
+                    *o = std::mem::take(o).pragma(name, value);
+                }
+            }
         }
+
+        #[cfg(feature = "sqlx_postgres")]
+        if let Some(o) = __options.downcast_mut::<sqlx::postgres::PgConnectOptions>() {
+            if let Some(ref postgres) = __config.postgres {
+                if let Some(ref name) = postgres.application_name {
+                    *o = std::mem::take(o).application_name(name);
+                }
+
+                if let Some(timeout) = postgres.statement_timeout {
+                    *o = std::mem::take(o).options([("statement_timeout", timeout)]);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the pool and connection options configured by `figment`.
+    pub(crate) fn options<D: sqlx::Database>(
+        figment: &Figment,
+    ) -> Result<(sqlx::pool::PoolOptions<D>, Options<D>), Error<sqlx::Error>> {
+        let config = figment.extract::<Config>()?;
+        let mut opts = config.url.parse::<Options<D>>().map_err(Error::Init)?;
+        specialize(&mut opts, &config).map_err(Error::Init)?;
+
+        opts = opts.disable_statement_logging();
+        if let Ok(value) = figment.find_value(rocket::Config::LOG_LEVEL) {
+            if let Some(level) = value.as_str().and_then(|v| v.parse().ok()) {
+                let log_level = match level {
+                    LevelFilter::OFF => log::LevelFilter::Off,
+                    LevelFilter::ERROR => log::LevelFilter::Error,
+                    LevelFilter::WARN => log::LevelFilter::Warn,
+                    LevelFilter::INFO => log::LevelFilter::Info,
+                    LevelFilter::DEBUG => log::LevelFilter::Debug,
+                    LevelFilter::TRACE => log::LevelFilter::Trace,
+                };
+
+                opts = opts.log_statements(log_level)
+                    .log_slow_statements(log_level, Duration::default());
+            }
+        }
+
+        let pool = sqlx::pool::PoolOptions::new()
+            .max_connections(config.max_connections as u32)
+            .acquire_timeout(Duration::from_secs(config.connect_timeout))
+            .idle_timeout(config.idle_timeout.map(Duration::from_secs))
+            .min_connections(config.min_connections.unwrap_or_default());
+
+        Ok((pool, opts))
     }
 
     #[rocket::async_trait]
@@ -255,33 +594,8 @@ mod sqlx {
         type Connection = sqlx::pool::PoolConnection<D>;
 
         async fn init(figment: &Figment) -> Result<Self, Self::Error> {
-            let config = figment.extract::<Config>()?;
-            let mut opts = config.url.parse::<Options<D>>().map_err(Error::Init)?;
-            specialize(&mut opts, &config);
-
-            opts = opts.disable_statement_logging();
-            if let Ok(value) = figment.find_value(rocket::Config::LOG_LEVEL) {
-                if let Some(level) = value.as_str().and_then(|v| v.parse().ok()) {
-                    let log_level = match level {
-                        LevelFilter::OFF => log::LevelFilter::Off,
-                        LevelFilter::ERROR => log::LevelFilter::Error,
-                        LevelFilter::WARN => log::LevelFilter::Warn,
-                        LevelFilter::INFO => log::LevelFilter::Info,
-                        LevelFilter::DEBUG => log::LevelFilter::Debug,
-                        LevelFilter::TRACE => log::LevelFilter::Trace,
-                    };
-
-                    opts = opts.log_statements(log_level)
-                        .log_slow_statements(log_level, Duration::default());
-                }
-            }
-
-            Ok(sqlx::pool::PoolOptions::new()
-                .max_connections(config.max_connections as u32)
-                .acquire_timeout(Duration::from_secs(config.connect_timeout))
-                .idle_timeout(config.idle_timeout.map(Duration::from_secs))
-                .min_connections(config.min_connections.unwrap_or_default())
-                .connect_lazy_with(opts))
+            let (pool, connect) = options::<D>(figment)?;
+            Ok(pool.connect_lazy_with(connect))
         }
 
         async fn get(&self) -> Result<Self::Connection, Self::Error> {
@@ -291,12 +605,48 @@ mod sqlx {
         async fn close(&self) {
             <sqlx::Pool<D>>::close(self).await;
         }
+
+        fn status(&self) -> Option<PoolStatus> {
+            let size = self.size() as usize;
+            let idle = self.num_idle();
+            Some(PoolStatus { size, idle, in_use: size.saturating_sub(idle), waiters: None })
+        }
+
+        async fn ping(&self) -> Result<(), Self::Error> {
+            use sqlx::Connection;
+
+            let mut conn = self.acquire().await.map_err(Error::Get)?;
+            conn.ping().await.map_err(Error::Get)
+        }
+
+        fn is_timeout(error: &Self::Error) -> bool {
+            matches!(error, Error::Get(sqlx::Error::PoolTimedOut))
+        }
+    }
+
+    #[rocket::async_trait]
+    impl<D: sqlx::Database> crate::Transactional for sqlx::Pool<D> {
+        type Transaction = sqlx::Transaction<'static, D>;
+
+        type TransactionError = sqlx::Error;
+
+        async fn begin(&self) -> Result<Self::Transaction, Self::Error> {
+            <sqlx::Pool<D>>::begin(self).await.map_err(Error::Get)
+        }
+
+        async fn commit(tx: Self::Transaction) -> Result<(), Self::TransactionError> {
+            tx.commit().await
+        }
+
+        async fn rollback(tx: Self::Transaction) -> Result<(), Self::TransactionError> {
+            tx.rollback().await
+        }
     }
 }
 
 #[cfg(feature = "mongodb")]
 mod mongodb {
-    use mongodb::{Client, options::ClientOptions};
+    use mongodb::{Client, bson::doc, options::ClientOptions};
     use super::{Duration, Error, Config, Figment};
 
     #[rocket::async_trait]
@@ -323,5 +673,18 @@ mod mongodb {
         async fn close(&self) {
             // nothing to do for mongodb
         }
+
+        async fn ping(&self) -> Result<(), Self::Error> {
+            // `Get` is uninhabited as retrieving a client can't fail, so driver
+            // errors are reported as `Init`, marked as a failed health check.
+            self.database("admin")
+                .run_command(doc! { "ping": 1 })
+                .await
+                .map(|_| ())
+                .map_err(|e| {
+                    let message = format!("health check ping failed: {e}");
+                    Error::Init(mongodb::error::Error::custom(message))
+                })
+        }
     }
 }
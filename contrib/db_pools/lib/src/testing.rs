@@ -0,0 +1,443 @@
+//! Isolated databases for testing.
+//!
+//! A [`TestDatabase`] provisions an isolated database for a single instance of
+//! Rocket, typically one per [`Client`](rocket::local::asynchronous::Client),
+//! so that tests which use a database can run in parallel without interfering
+//! with one another. The database is one of:
+//!
+//!   * a fresh SQLite file, via [`TestDatabase::sqlite()`],
+//!   * a fresh in-memory SQLite database, via [`TestDatabase::memory()`], or
+//!   * the configured database wrapped in a transaction that is never
+//!     committed, via [`TestDatabase::transaction()`].
+//!
+//! The database:
+//!
+//!   * replaces the configured database via figment overrides,
+//!   * has migrations applied, if any are registered via
+//!     [`Initializer::migrations()`](crate::Initializer::migrations()),
+//!   * is populated by [fixtures](TestDatabase::fixture()), and
+//!   * is deleted, or its transaction rolled back, when the instance of Rocket
+//!     is dropped.
+//!
+//! Fresh SQLite databases are supported by the `sqlx_sqlite` and
+//! `deadpool_sqlite` drivers. Transaction-wrapped databases are supported by
+//! the drivers whose pools implement [`TestTransaction`]: all `sqlx` drivers
+//! and `deadpool_sqlite`.
+//!
+//! # Example
+//!
+//! ```rust
+//! # #[cfg(feature = "sqlx_sqlite")] mod _inner {
+//! use rocket::{get, routes, Build, Rocket};
+//! use rocket::local::asynchronous::Client;
+//! use rocket_db_pools::{sqlx, Connection, Database};
+//! use rocket_db_pools::testing::TestDatabase;
+//!
+//! #[derive(Database)]
+//! #[database("db")]
+//! struct Db(sqlx::SqlitePool);
+//!
+//! #[get("/")]
+//! async fn count(mut db: Connection<Db>) -> String {
+//!     let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users")
+//!         .fetch_one(&mut **db)
+//!         .await
+//!         .unwrap();
+//!
+//!     count.to_string()
+//! }
+//!
+//! fn rocket() -> Rocket<Build> {
+//!     rocket::build().attach(Db::init()).mount("/", routes![count])
+//! }
+//!
+//! #[rocket::async_test]
+//! async fn test_count() {
+//!     let rocket = TestDatabase::<Db>::sqlite()
+//!         .migrate(false)
+//!         .fixture(|db| Box::pin(async move {
+//!             sqlx::query("CREATE TABLE users (name TEXT)").execute(&**db).await?;
+//!             sqlx::query("INSERT INTO users VALUES ('bob')").execute(&**db).await?;
+//!             Ok(())
+//!         }))
+//!         .apply(rocket());
+//!
+//!     let client = Client::tracked(rocket).await.unwrap();
+//!     let response = client.get("/").dispatch().await;
+//!     assert_eq!(response.into_string().await.unwrap(), "1");
+//! }
+//! # }
+//! ```
+
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rocket::{error, span_error, Build, Rocket};
+use rocket::fairing::{self, Fairing, Info, Kind};
+use rocket::figment::{Figment, providers::Serialized};
+use rocket::futures::future::BoxFuture;
+
+use crate::{Database, Pool};
+
+/// The error type returned by fixtures.
+pub type FixtureError = Box<dyn std::error::Error + Send + Sync>;
+
+type Fixture<D> = Box<dyn for<'a> Fn(&'a D) -> BoxFuture<'a, Result<(), FixtureError>>
+    + Send + Sync>;
+
+type PoolResult<D> = Result<<D as Database>::Pool, <<D as Database>::Pool as Pool>::Error>;
+
+/// An isolated database for the [`Database`] `D`, applied to an instance of
+/// Rocket via [`TestDatabase::apply()`].
+///
+/// See the [module level docs](self) for details and an example.
+pub struct TestDatabase<D: Database> {
+    storage: Storage<D>,
+    migrate: bool,
+    fixtures: Vec<Fixture<D>>,
+}
+
+enum Storage<D: Database> {
+    File,
+    Memory,
+    Transaction(PoolInit<D>),
+}
+
+/// A [`Pool`] whose connections can be wrapped in test transactions. See
+/// [`TestDatabase::transaction()`].
+///
+/// ## Async Trait
+///
+/// Like [`Pool`], `TestTransaction` is an _async_ trait. Implementations must
+/// be decorated with `#[async_trait]`.
+#[rocket::async_trait]
+pub trait TestTransaction: Pool {
+    /// Initializes a pool as [`Pool::init()`] does, except that every
+    /// connection the pool opens begins a transaction, which is never
+    /// committed, before it is used. Connections must not be closed for being
+    /// idle or old, as their transaction, and all of its changes, would be
+    /// lost.
+    async fn init_test_transaction(figment: &Figment) -> Result<Self, Self::Error>;
+}
+
+/// Initializes the pool of `D` in place of [`Pool::init()`]. Managed by
+/// [transaction-wrapped](TestDatabase::transaction()) test databases and
+/// called by `D`'s [`Initializer`](crate::Initializer).
+pub(crate) struct PoolInit<D: Database>(
+    pub(crate) Box<dyn for<'a> Fn(&'a Figment) -> BoxFuture<'a, PoolResult<D>> + Send + Sync>,
+);
+
+/// Runs fixtures once `D` is initialized.
+struct Fixtures<D: Database> {
+    fixtures: Arc<Vec<Fixture<D>>>,
+    deferred: bool,
+}
+
+/// Deletes the database file, if any, when dropped with Rocket's state.
+struct Teardown<D>(Option<PathBuf>, PhantomData<fn() -> D>);
+
+impl<D: Database> TestDatabase<D> {
+    /// A test database backed by a fresh SQLite file in the system's temporary
+    /// directory.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "sqlx_sqlite")] mod _inner {
+    /// use rocket_db_pools::{sqlx, Database};
+    /// use rocket_db_pools::testing::TestDatabase;
+    ///
+    /// #[derive(Database)]
+    /// #[database("db")]
+    /// struct Db(sqlx::SqlitePool);
+    ///
+    /// # fn _test() {
+    /// let rocket = TestDatabase::<Db>::sqlite().apply(rocket::build());
+    /// # }
+    /// # }
+    /// ```
+    pub fn sqlite() -> Self {
+        TestDatabase { storage: Storage::File, migrate: true, fixtures: vec![] }
+    }
+
+    /// A test database backed by a fresh, shared, in-memory SQLite database.
+    ///
+    /// The database persists as long as at least one connection to it is
+    /// open. As such, the pool's [`min_connections`](crate::Config) is set to
+    /// `1`. Drivers which don't support `min_connections` keep idle
+    /// connections open by default.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "sqlx_sqlite")] mod _inner {
+    /// use rocket_db_pools::{sqlx, Database};
+    /// use rocket_db_pools::testing::TestDatabase;
+    ///
+    /// #[derive(Database)]
+    /// #[database("db")]
+    /// struct Db(sqlx::SqlitePool);
+    ///
+    /// # fn _test() {
+    /// let rocket = TestDatabase::<Db>::memory().apply(rocket::build());
+    /// # }
+    /// # }
+    /// ```
+    pub fn memory() -> Self {
+        TestDatabase { storage: Storage::Memory, migrate: true, fixtures: vec![] }
+    }
+
+    /// A test database backed by the configured database, with all of its
+    /// changes made in transactions that are never committed.
+    ///
+    /// Every connection the pool opens begins a transaction before it is
+    /// used, including connections that replace ones which failed or were
+    /// dropped mid-query. Connections are not closed for being idle or old.
+    /// The pool is limited to a single connection: all requests, and
+    /// fixtures, share its transaction and thus see each other's changes, but
+    /// no other connection does. A transaction is rolled back when its
+    /// connection is closed, which happens when the instance of Rocket is
+    /// dropped or, with all changes made so far, if the connection fails.
+    ///
+    /// As the transactions are never committed:
+    ///
+    ///   * Migrations are never applied, even if
+    ///     [enabled](TestDatabase::migrate()): they would run in, and could
+    ///     commit, the transaction. Apply them to the configured database
+    ///     beforehand.
+    ///   * Transactions begun by the application, including via the
+    ///     [`Transaction`](crate::Transaction) request guard, are not
+    ///     supported.
+    ///   * With SQLite, which allows a single writer at a time, concurrent
+    ///     transaction-wrapped instances of Rocket cannot all write to the
+    ///     same database. Prefer [`TestDatabase::sqlite()`] or
+    ///     [`TestDatabase::memory()`] with SQLite.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "sqlx_postgres")] mod _inner {
+    /// use rocket_db_pools::{sqlx, Database};
+    /// use rocket_db_pools::testing::TestDatabase;
+    ///
+    /// #[derive(Database)]
+    /// #[database("db")]
+    /// struct Db(sqlx::PgPool);
+    ///
+    /// # fn _test() {
+    /// let rocket = TestDatabase::<Db>::transaction().apply(rocket::build());
+    /// # }
+    /// # }
+    /// ```
+    pub fn transaction() -> Self
+        where D::Pool: TestTransaction
+    {
+        let init = PoolInit(Box::new(|figment| D::Pool::init_test_transaction(figment)));
+        TestDatabase { storage: Storage::Transaction(init), migrate: false, fixtures: vec![] }
+    }
+
+    /// Sets whether migrations registered via
+    /// [`Initializer::migrations()`](crate::Initializer::migrations()) are
+    /// applied to the test database. Defaults to `true`. Migrations are never
+    /// applied to [transaction-wrapped](TestDatabase::transaction())
+    /// databases.
+    ///
+    /// Since launch is aborted when migrations are enabled but none are
+    /// registered, this must be set to `false` for databases without
+    /// registered migrations.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "sqlx_sqlite")] mod _inner {
+    /// use rocket_db_pools::{sqlx, Database};
+    /// use rocket_db_pools::testing::TestDatabase;
+    ///
+    /// #[derive(Database)]
+    /// #[database("db")]
+    /// struct Db(sqlx::SqlitePool);
+    ///
+    /// # fn _test() {
+    /// let test_db = TestDatabase::<Db>::sqlite().migrate(false);
+    /// # }
+    /// # }
+    /// ```
+    pub fn migrate(mut self, enabled: bool) -> Self {
+        self.migrate = enabled;
+        self
+    }
+
+    /// Adds a fixture which populates the test database. Fixtures are run in
+    /// the order they are added, after migrations are applied. If a fixture
+    /// fails, launch is aborted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "sqlx_sqlite")] mod _inner {
+    /// use rocket_db_pools::{sqlx, Database};
+    /// use rocket_db_pools::testing::TestDatabase;
+    ///
+    /// #[derive(Database)]
+    /// #[database("db")]
+    /// struct Db(sqlx::SqlitePool);
+    ///
+    /// # fn _test() {
+    /// let test_db = TestDatabase::<Db>::memory()
+    ///     .fixture(|db| Box::pin(async move {
+    ///         sqlx::query("INSERT INTO users VALUES ('alice')").execute(&**db).await?;
+    ///         Ok(())
+    ///     }));
+    /// # }
+    /// # }
+    /// ```
+    pub fn fixture<F>(mut self, fixture: F) -> Self
+        where F: for<'a> Fn(&'a D) -> BoxFuture<'a, Result<(), FixtureError>>,
+              F: Send + Sync + 'static
+    {
+        self.fixtures.push(Box::new(fixture));
+        self
+    }
+
+    /// Configures `rocket` to use the test database in place of the database
+    /// configured for `D`.
+    ///
+    /// The database's `url` is overridden, except for
+    /// [transaction-wrapped](TestDatabase::transaction()) databases whose pool
+    /// is instead limited to one connection. Its
+    /// [`replicas`](crate::Config::replicas) are removed, and
+    /// [`migrate`](crate::Config::migrate) is set as configured. All other
+    /// configuration parameters are retained.
+    pub fn apply(self, rocket: Rocket<Build>) -> Rocket<Build> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let name = format!("rocket-db-pools-{}-{}-{id}", D::NAME, std::process::id());
+        let key = |param: &str| format!("databases.{}.{param}", D::NAME);
+
+        let migrate = self.migrate && !matches!(self.storage, Storage::Transaction(_));
+        let mut figment = rocket.figment().clone()
+            .merge(Serialized::global(&key("replicas"), Vec::<String>::new()))
+            .merge(Serialized::global(&key("migrate"), migrate));
+
+        let (mut path, mut init) = (None, None);
+        match self.storage {
+            Storage::File => {
+                let file = std::env::temp_dir().join(format!("{name}.sqlite"));
+                let url = file.display().to_string();
+                figment = figment.merge(Serialized::global(&key("url"), url));
+                path = Some(file);
+            }
+            Storage::Memory => {
+                let url = format!("file:{name}?mode=memory&cache=shared");
+                figment = figment.merge(Serialized::global(&key("url"), url))
+                    .merge(Serialized::global(&key("min_connections"), 1));
+            }
+            Storage::Transaction(pool_init) => {
+                figment = figment.merge(Serialized::global(&key("max_connections"), 1))
+                    .merge(Serialized::global(&key("min_connections"), 1));
+                init = Some(pool_init);
+            }
+        }
+
+        let rocket = rocket.reconfigure(figment)
+            .manage(Teardown::<D>(path, PhantomData))
+            .attach(Fixtures { fixtures: Arc::new(self.fixtures), deferred: false });
+
+        match init {
+            Some(init) => rocket.manage(init),
+            None => rocket,
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<D: Database> Fairing for Fixtures<D> {
+    fn info(&self) -> Info {
+        Info { name: "Test Database Fixtures", kind: Kind::Ignite }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        // The database may be initialized by a fairing attached during
+        // ignition. Run again after such fairings to allow for this.
+        let Some(db) = rocket.state::<D>() else {
+            if !self.deferred {
+                let fixtures = self.fixtures.clone();
+                return Ok(rocket.attach(Fixtures::<D> { fixtures, deferred: true }));
+            }
+
+            let conn = std::any::type_name::<D>();
+            error!("`{conn}::init()` is not attached\n\
+                the fairing must be attached to use a test database.");
+
+            return Err(rocket);
+        };
+
+        for (i, fixture) in self.fixtures.iter().enumerate() {
+            if let Err(e) = fixture(db).await {
+                span_error!("fixture", database = D::NAME, fixture = i => {
+                    error!("test database fixture failed: {e}");
+                });
+
+                return Err(rocket);
+            }
+        }
+
+        Ok(rocket)
+    }
+}
+
+#[cfg(feature = "sqlx")]
+#[rocket::async_trait]
+impl<D: sqlx::Database> TestTransaction for sqlx::Pool<D>
+    where for<'c> &'c mut D::Connection: sqlx::Executor<'c, Database = D>
+{
+    async fn init_test_transaction(figment: &Figment) -> Result<Self, Self::Error> {
+        let (pool, connect) = crate::pool::sqlx::options::<D>(figment)?;
+        let pool = pool.max_lifetime(None)
+            .idle_timeout(None)
+            .after_connect(|conn, _| Box::pin(async move {
+                sqlx::Executor::execute(conn, "BEGIN").await.map(|_| ())
+            }));
+
+        Ok(pool.connect_lazy_with(connect))
+    }
+}
+
+#[cfg(feature = "deadpool_sqlite")]
+#[rocket::async_trait]
+impl TestTransaction for deadpool_sqlite::Pool {
+    async fn init_test_transaction(figment: &Figment) -> Result<Self, Self::Error> {
+        use deadpool::managed::{Hook, HookError, Manager, PoolError};
+
+        type Conn = <deadpool_sqlite::Manager as Manager>::Type;
+
+        // Added after the configuration's hook, which may set `PRAGMA`s that
+        // can't be changed in a transaction.
+        let begin = Hook::async_fn(|conn: &mut Conn, _| Box::pin(async move {
+            conn.interact(|conn| conn.execute_batch("BEGIN")).await
+                .map_err(|e| HookError::Message(e.to_string().into()))?
+                .map_err(HookError::Backend)
+        }));
+
+        crate::pool::deadpool_postgres::builder(figment)?
+            .recycle_timeout(None)
+            .post_create(begin)
+            .build()
+            .map_err(|_| crate::Error::Init(PoolError::NoRuntimeSpecified))
+    }
+}
+
+impl<D> Drop for Teardown<D> {
+    fn drop(&mut self) {
+        if let Some(path) = self.0.take() {
+            for suffix in ["", "-wal", "-shm", "-journal"] {
+                let mut file = path.clone().into_os_string();
+                file.push(suffix);
+                let _ = std::fs::remove_file(file);
+            }
+        }
+    }
+}
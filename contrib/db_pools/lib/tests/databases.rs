@@ -3,7 +3,7 @@ macro_rules! check_types_match {
         #[cfg(feature = $feature)]
         mod $name {
             use rocket::*;
-            use rocket_db_pools::{Connection, Database};
+            use rocket_db_pools::{Connection, Database, ReadConnection};
 
             #[derive(Database)]
             #[database("foo")]
@@ -13,6 +13,11 @@ macro_rules! check_types_match {
             fn _db(conn: Connection<Db>) {
                 let _: &$Conn = &*conn;
             }
+
+            #[get("/read")]
+            fn _read(conn: ReadConnection<Db>) {
+                let _: &$Conn = &*conn;
+            }
         }
     )
 }
@@ -24,6 +29,13 @@ check_types_match!(
     deadpool_postgres::ClientWrapper,
 );
 
+check_types_match!(
+    "deadpool_sqlite",
+    deadpool_sqlite,
+    deadpool_sqlite::Pool,
+    deadpool_sqlite::Object,
+);
+
 check_types_match!(
     "deadpool_redis",
     deadpool_redis,
@@ -58,3 +70,57 @@ check_types_match!(
     mongodb::Client,
     mongodb::Client,
 );
+
+macro_rules! check_transaction_types_match {
+    ($feature:expr, $name:ident, $Pool:ty, $Tx:ty $(,)?) => (
+        #[cfg(feature = $feature)]
+        mod $name {
+            use rocket::*;
+            use rocket_db_pools::{Transaction, Database};
+
+            #[derive(Database)]
+            #[database("foo")]
+            struct Db($Pool);
+
+            #[get("/")]
+            fn _db(tx: Transaction<Db>) {
+                let _: &$Tx = &*tx;
+            }
+        }
+    )
+}
+
+check_transaction_types_match!(
+    "sqlx_postgres",
+    sqlx_postgres_tx,
+    sqlx::PgPool,
+    sqlx::Transaction<'static, sqlx::Postgres>,
+);
+
+check_transaction_types_match!(
+    "sqlx_mysql",
+    sqlx_mysql_tx,
+    sqlx::MySqlPool,
+    sqlx::Transaction<'static, sqlx::MySql>,
+);
+
+check_transaction_types_match!(
+    "sqlx_sqlite",
+    sqlx_sqlite_tx,
+    sqlx::SqlitePool,
+    sqlx::Transaction<'static, sqlx::Sqlite>,
+);
+
+check_transaction_types_match!(
+    "diesel_postgres",
+    diesel_postgres_tx,
+    rocket_db_pools::diesel::PgPool,
+    rocket_db_pools::diesel::AsyncPgConnection,
+);
+
+check_transaction_types_match!(
+    "diesel_mysql",
+    diesel_mysql_tx,
+    rocket_db_pools::diesel::MysqlPool,
+    rocket_db_pools::diesel::AsyncMysqlConnection,
+);
@@ -0,0 +1,48 @@
+#![cfg(feature = "deadpool_sqlite")]
+
+use rocket::{get, routes};
+use rocket::figment::{Figment, util::map};
+use rocket::local::asynchronous::Client;
+use rocket_db_pools::{deadpool_sqlite, Connection, Database};
+
+#[derive(Database)]
+#[database("db")]
+struct Db(deadpool_sqlite::Pool);
+
+#[get("/pragma/<name>")]
+async fn pragma(db: Connection<Db>, name: String) -> String {
+    db.interact(move |conn| {
+        let query = format!("SELECT CAST((SELECT * FROM pragma_{name}) AS TEXT)");
+        conn.query_row(&query, [], |row| row.get::<_, String>(0))
+    }).await.unwrap().unwrap()
+}
+
+#[rocket::async_test]
+async fn deadpool_sqlite_applies_options() {
+    let path = std::env::temp_dir().join("rocket-db-pools-deadpool-sqlite.sqlite");
+    let _ = std::fs::remove_file(&path);
+
+    let figment = Figment::from(rocket::Config::debug_default())
+        .merge(("databases.db", map!["url" => path.display().to_string()]))
+        .merge(("databases.db.sqlite", map!["journal_mode" => "wal"]))
+        .merge(("databases.db.sqlite.foreign_keys", true))
+        .merge(("databases.db.sqlite.pragmas.cache_size", -4000));
+
+    let rocket = rocket::custom(figment)
+        .attach(Db::init())
+        .mount("/", routes![pragma]);
+
+    let client = Client::tracked(rocket).await.unwrap();
+    let get = |name: &'static str| {
+        let client = &client;
+        async move { client.get(format!("/pragma/{name}")).dispatch().await.into_string().await }
+    };
+
+    assert_eq!(get("journal_mode").await.unwrap(), "wal");
+    assert_eq!(get("foreign_keys").await.unwrap(), "1");
+    assert_eq!(get("cache_size").await.unwrap(), "-4000");
+    assert_eq!(get("busy_timeout").await.unwrap(), "5000");
+
+    let db = Db::fetch(client.rocket()).unwrap();
+    assert!(db.ping().await.is_ok());
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "sqlx_sqlite")]
+
+use rocket::{get, routes, Build, Rocket};
+use rocket::figment::{Figment, util::map};
+use rocket::local::asynchronous::Client;
+use rocket_db_pools::{sqlx, Connection, Database, Pool};
+
+#[derive(Database)]
+#[database("db")]
+struct Db(sqlx::SqlitePool);
+
+#[get("/")]
+async fn index(mut db: Connection<Db>) -> String {
+    let value: i64 = sqlx::query_scalar("SELECT 1").fetch_one(&mut **db).await.unwrap();
+    value.to_string()
+}
+
+fn rocket(url: String) -> Rocket<Build> {
+    let figment = Figment::from(rocket::Config::debug_default())
+        .merge(("databases.db", map!["url" => url]))
+        .merge(("databases.db.connect_timeout", 1));
+
+    rocket::custom(figment)
+        .attach(Db::init())
+        .mount("/", routes![index])
+}
+
+#[rocket::async_test]
+async fn metrics_count_acquisitions() {
+    let path = std::env::temp_dir().join("rocket-db-pools-metrics.sqlite");
+    let _ = std::fs::remove_file(&path);
+    let client = Client::tracked(rocket(path.display().to_string())).await.unwrap();
+
+    let metrics = Db::metrics(client.rocket()).unwrap();
+    assert_eq!(metrics.acquired, 0);
+    assert_eq!(metrics.mean_acquire_time(), None);
+
+    for _ in 0..3 {
+        assert_eq!(client.get("/").dispatch().await.into_string().await.unwrap(), "1");
+    }
+
+    let metrics = Db::metrics(client.rocket()).unwrap();
+    assert_eq!(metrics.acquired, 3);
+    assert_eq!(metrics.failed, 0);
+    assert_eq!(metrics.timeouts, 0);
+    assert!(metrics.max_acquire_time <= metrics.acquire_time);
+    assert!(metrics.mean_acquire_time().is_some());
+
+    let status = metrics.status.unwrap();
+    assert_eq!(status.size, status.idle + status.in_use);
+    assert!(status.size >= 1);
+
+    let db = Db::fetch(client.rocket()).unwrap();
+    assert!(db.ping().await.is_ok());
+    assert!(Pool::ping(&db.0).await.is_ok());
+}
+
+#[rocket::async_test]
+async fn metrics_count_failures() {
+    let path = std::env::temp_dir().join("rocket-db-pools-missing/metrics.sqlite");
+    let client = Client::tracked(rocket(path.display().to_string())).await.unwrap();
+
+    let response = client.get("/").dispatch().await;
+    assert_eq!(response.status(), rocket::http::Status::ServiceUnavailable);
+
+    let metrics = Db::metrics(client.rocket()).unwrap();
+    assert_eq!(metrics.acquired, 0);
+    assert_eq!(metrics.failed, 1);
+
+    let db = Db::fetch(client.rocket()).unwrap();
+    assert!(db.ping().await.is_err());
+}
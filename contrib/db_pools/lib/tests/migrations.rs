@@ -0,0 +1,78 @@
+#![cfg(feature = "sqlx_sqlite")]
+
+use std::sync::{Arc, Mutex};
+
+use rocket::{Build, Rocket};
+use rocket::figment::{Figment, util::map};
+use rocket_db_pools::{sqlx, Database, Migrations, MigrationError};
+
+#[derive(Database)]
+#[database("db")]
+struct Db(sqlx::SqlitePool);
+
+/// Migrations with one pending migration, `1_init`, that fails to apply if
+/// `fail` is set. Records which methods were called in `calls`.
+#[derive(Default, Clone)]
+struct Scripted {
+    fail: bool,
+    calls: Arc<Mutex<Vec<&'static str>>>,
+}
+
+#[rocket::async_trait]
+impl Migrations<sqlx::SqlitePool> for Scripted {
+    async fn pending(&self, _: &sqlx::SqlitePool) -> Result<Vec<String>, MigrationError> {
+        self.calls.lock().unwrap().push("pending");
+        Ok(vec!["1_init".into()])
+    }
+
+    async fn run(&self, _: &sqlx::SqlitePool) -> Result<Vec<String>, MigrationError> {
+        self.calls.lock().unwrap().push("run");
+        match self.fail {
+            true => Err(MigrationError::new("syntax error").with_migration("1_init")),
+            false => Ok(vec!["1_init".into()]),
+        }
+    }
+}
+
+fn rocket(migrate: impl Into<rocket::figment::value::Value>) -> Rocket<Build> {
+    let figment = Figment::from(rocket::Config::debug_default())
+        .merge(("databases.db", map!["url" => "sqlite::memory:"]))
+        .merge(("databases.db.migrate", migrate.into()));
+
+    rocket::custom(figment)
+}
+
+#[rocket::async_test]
+async fn migrations_run_on_ignite() {
+    let migrations = Scripted::default();
+    let rocket = rocket(true).attach(Db::init().migrations(migrations.clone()));
+    assert!(rocket.ignite().await.is_ok());
+    assert_eq!(*migrations.calls.lock().unwrap(), ["run"]);
+}
+
+#[rocket::async_test]
+async fn failed_migration_aborts_launch() {
+    let migrations = Scripted { fail: true, ..Default::default() };
+    let rocket = rocket(true).attach(Db::init().migrations(migrations.clone()));
+    assert!(rocket.ignite().await.is_err());
+    assert_eq!(*migrations.calls.lock().unwrap(), ["run"]);
+
+    // So does enabling migrations without registering any.
+    assert!(self::rocket(true).attach(Db::init()).ignite().await.is_err());
+}
+
+#[rocket::async_test]
+async fn dry_run_only_reports_pending_migrations() {
+    let migrations = Scripted { fail: true, ..Default::default() };
+    let rocket = rocket("dry-run").attach(Db::init().migrations(migrations.clone()));
+    assert!(rocket.ignite().await.is_ok());
+    assert_eq!(*migrations.calls.lock().unwrap(), ["pending"]);
+}
+
+#[rocket::async_test]
+async fn migrations_are_off_by_default() {
+    let migrations = Scripted { fail: true, ..Default::default() };
+    let rocket = rocket(false).attach(Db::init().migrations(migrations.clone()));
+    assert!(rocket.ignite().await.is_ok());
+    assert!(migrations.calls.lock().unwrap().is_empty());
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "sqlx_sqlite")]
+
+use std::path::PathBuf;
+
+use rocket::{get, routes, Build, Rocket};
+use rocket::figment::{Figment, util::map};
+use rocket::local::asynchronous::Client;
+use rocket_db_pools::{sqlx, Connection, Database, ReadConnection};
+
+#[derive(Database)]
+#[database("db")]
+struct Db(sqlx::SqlitePool);
+
+async fn origin(conn: &mut sqlx::SqliteConnection) -> String {
+    sqlx::query_scalar("SELECT name FROM origin")
+        .fetch_one(conn)
+        .await
+        .unwrap()
+}
+
+#[get("/read")]
+async fn read(mut db: ReadConnection<Db>) -> String {
+    origin(&mut db).await
+}
+
+#[get("/write")]
+async fn write(mut db: Connection<Db>) -> String {
+    origin(&mut db).await
+}
+
+async fn database(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("rocket-db-pools-replicas-{name}.sqlite"));
+    let _ = std::fs::remove_file(&path);
+
+    let url = format!("sqlite://{}?mode=rwc", path.display());
+    let pool = sqlx::SqlitePool::connect(&url).await.unwrap();
+    sqlx::query("CREATE TABLE origin (name TEXT NOT NULL)").execute(&pool).await.unwrap();
+    sqlx::query("INSERT INTO origin (name) VALUES (?)").bind(name).execute(&pool).await.unwrap();
+    pool.close().await;
+
+    path
+}
+
+fn rocket(primary: PathBuf, replicas: Vec<String>) -> Rocket<Build> {
+    let figment = Figment::from(rocket::Config::debug_default())
+        .merge(("databases.db", map!["url" => primary.display().to_string()]))
+        .merge(("databases.db.connect_timeout", 1))
+        .merge(("databases.db.replicas", replicas));
+
+    rocket::custom(figment)
+        .attach(Db::init())
+        .mount("/", routes![read, write])
+}
+
+#[rocket::async_test]
+async fn read_connections_use_replicas() {
+    let primary = database("primary").await;
+    let replica_a = database("replica-a").await;
+    let replica_b = database("replica-b").await;
+    let replicas = vec![replica_a.display().to_string(), replica_b.display().to_string()];
+    let client = Client::tracked(rocket(primary, replicas)).await.unwrap();
+
+    let mut reads = vec![];
+    for _ in 0..4 {
+        reads.push(client.get("/read").dispatch().await.into_string().await.unwrap());
+    }
+
+    reads.sort();
+    assert_eq!(reads, ["replica-a", "replica-a", "replica-b", "replica-b"]);
+
+    let write = client.get("/write").dispatch().await.into_string().await.unwrap();
+    assert_eq!(write, "primary");
+}
+
+#[rocket::async_test]
+async fn read_connections_fall_back_to_primary() {
+    let primary = database("fallback-primary").await;
+    let missing = std::env::temp_dir().join("rocket-db-pools-missing/replica.sqlite");
+    let replicas = vec![missing.display().to_string()];
+    let client = Client::tracked(rocket(primary, replicas)).await.unwrap();
+
+    for _ in 0..2 {
+        let read = client.get("/read").dispatch().await.into_string().await.unwrap();
+        assert_eq!(read, "fallback-primary");
+    }
+}
+
+#[rocket::async_test]
+async fn replicas_failing_to_initialize_are_skipped() {
+    let primary = database("skipped-primary").await;
+    let replica = database("skipped-replica").await;
+    let invalid = format!("{}?mode=invalid", replica.display());
+    let replicas = vec![invalid, replica.display().to_string()];
+    let client = Client::tracked(rocket(primary, replicas)).await.unwrap();
+
+    for _ in 0..2 {
+        let read = client.get("/read").dispatch().await.into_string().await.unwrap();
+        assert_eq!(read, "skipped-replica");
+    }
+}
+
+#[rocket::async_test]
+async fn read_connections_without_replicas_use_primary() {
+    let primary = database("no-replicas").await;
+    let client = Client::tracked(rocket(primary, vec![])).await.unwrap();
+
+    let read = client.get("/read").dispatch().await.into_string().await.unwrap();
+    assert_eq!(read, "no-replicas");
+}
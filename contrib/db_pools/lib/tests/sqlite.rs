@@ -0,0 +1,50 @@
+#![cfg(feature = "sqlx_sqlite")]
+
+use rocket::figment::{Figment, util::map};
+use rocket_db_pools::{sqlx, Database};
+
+#[derive(Database)]
+#[database("db")]
+struct Db(sqlx::SqlitePool);
+
+#[rocket::async_test]
+async fn sqlite_options_are_applied() {
+    let path = std::env::temp_dir().join("rocket-db-pools-sqlite-options.sqlite");
+    let _ = std::fs::remove_file(&path);
+
+    let figment = Figment::from(rocket::Config::debug_default())
+        .merge(("databases.db", map!["url" => path.display().to_string()]))
+        .merge(("databases.db.sqlite", map![
+            "journal_mode" => "wal",
+            "synchronous" => "normal",
+        ]))
+        .merge(("databases.db.sqlite.foreign_keys", true))
+        .merge(("databases.db.sqlite.busy_timeout", 1234))
+        .merge(("databases.db.sqlite.pragmas.cache_size", -4000));
+
+    let rocket = rocket::custom(figment).attach(Db::init()).ignite().await.unwrap();
+    let db = Db::fetch(&rocket).unwrap();
+    let pragma = |name: &'static str| async move {
+        let query = format!("SELECT CAST((SELECT * FROM pragma_{name}) AS TEXT)");
+        sqlx::query_scalar::<_, String>(&query)
+            .fetch_one(&db.0)
+            .await
+            .unwrap()
+    };
+
+    assert_eq!(pragma("journal_mode").await, "wal");
+    assert_eq!(pragma("synchronous").await, "1");
+    assert_eq!(pragma("foreign_keys").await, "1");
+    assert_eq!(pragma("busy_timeout").await, "1234");
+    assert_eq!(pragma("cache_size").await, "-4000");
+}
+
+#[rocket::async_test]
+async fn invalid_sqlite_options_abort_launch() {
+    let path = std::env::temp_dir().join("rocket-db-pools-sqlite-invalid.sqlite");
+    let figment = Figment::from(rocket::Config::debug_default())
+        .merge(("databases.db", map!["url" => path.display().to_string()]))
+        .merge(("databases.db.sqlite.journal_mode", "sideways"));
+
+    assert!(rocket::custom(figment).attach(Db::init()).ignite().await.is_err());
+}
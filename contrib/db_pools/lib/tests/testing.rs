@@ -0,0 +1,173 @@
+#![cfg(feature = "sqlx_sqlite")]
+
+use rocket::{get, post, routes, Build, Rocket};
+use rocket::local::asynchronous::Client;
+use rocket_db_pools::{sqlx, Connection, Database};
+use rocket_db_pools::testing::TestDatabase;
+
+#[derive(Database)]
+#[database("db")]
+struct Db(sqlx::SqlitePool);
+
+#[get("/")]
+async fn names(mut db: Connection<Db>) -> String {
+    let names: Vec<String> = sqlx::query_scalar("SELECT name FROM users ORDER BY name")
+        .fetch_all(&mut **db)
+        .await
+        .unwrap();
+
+    names.join(",")
+}
+
+#[post("/<name>")]
+async fn add(mut db: Connection<Db>, name: &str) {
+    sqlx::query("INSERT INTO users (name) VALUES (?)")
+        .bind(name)
+        .execute(&mut **db)
+        .await
+        .unwrap();
+}
+
+#[post("/<name>/reconnect")]
+async fn add_and_reconnect(db: Connection<Db>, name: &str) {
+    let mut conn = db.into_inner();
+    sqlx::query("INSERT INTO users (name) VALUES (?)")
+        .bind(name)
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+
+    conn.close().await.unwrap();
+}
+
+fn rocket() -> Rocket<Build> {
+    rocket::build()
+        .attach(Db::init())
+        .mount("/", routes![names, add, add_and_reconnect])
+}
+
+fn test_db(db: TestDatabase<Db>) -> TestDatabase<Db> {
+    db.migrate(false)
+        .fixture(|db| Box::pin(async move {
+            sqlx::query("CREATE TABLE users (name TEXT NOT NULL)").execute(&**db).await?;
+            Ok(())
+        }))
+        .fixture(|db| Box::pin(async move {
+            sqlx::query("INSERT INTO users (name) VALUES ('alice')").execute(&**db).await?;
+            Ok(())
+        }))
+}
+
+async fn check_isolated(db: fn() -> TestDatabase<Db>) {
+    let client_a = Client::tracked(db().apply(rocket())).await.unwrap();
+    let client_b = Client::tracked(db().apply(rocket())).await.unwrap();
+
+    client_a.post("/bob").dispatch().await;
+    client_b.post("/carol").dispatch().await;
+
+    assert_eq!(client_a.get("/").dispatch().await.into_string().await.unwrap(), "alice,bob");
+    assert_eq!(client_b.get("/").dispatch().await.into_string().await.unwrap(), "alice,carol");
+}
+
+#[rocket::async_test]
+async fn file_databases_are_isolated() {
+    check_isolated(|| test_db(TestDatabase::sqlite())).await;
+}
+
+#[rocket::async_test]
+async fn memory_databases_are_isolated() {
+    check_isolated(|| test_db(TestDatabase::memory())).await;
+}
+
+#[rocket::async_test]
+async fn file_databases_are_deleted() {
+    let client = Client::tracked(test_db(TestDatabase::sqlite()).apply(rocket())).await.unwrap();
+    let db = Db::fetch(client.rocket()).unwrap();
+    let query = "SELECT file FROM pragma_database_list WHERE name = 'main'";
+    let path: String = sqlx::query_scalar(query)
+        .fetch_one(&db.0)
+        .await
+        .unwrap();
+
+    assert!(std::path::Path::new(&path).exists());
+    drop(client);
+    assert!(!std::path::Path::new(&path).exists());
+}
+
+#[rocket::async_test]
+async fn failing_fixtures_abort_launch() {
+    let rocket = TestDatabase::<Db>::sqlite()
+        .migrate(false)
+        .fixture(|db| Box::pin(async move {
+            sqlx::query("INSERT INTO missing VALUES (1)").execute(&**db).await?;
+            Ok(())
+        }))
+        .apply(rocket());
+
+    assert!(Client::tracked(rocket).await.is_err());
+}
+
+#[rocket::async_test]
+async fn transaction_databases_are_rolled_back() {
+    let path = std::env::temp_dir().join("rocket-db-pools-testing-transaction.sqlite");
+    let _ = std::fs::remove_file(&path);
+
+    let url = format!("sqlite://{}?mode=rwc", path.display());
+    let pool = sqlx::SqlitePool::connect(&url).await.unwrap();
+    sqlx::query("CREATE TABLE users (name TEXT NOT NULL)").execute(&pool).await.unwrap();
+    sqlx::query("INSERT INTO users (name) VALUES ('alice')").execute(&pool).await.unwrap();
+
+    let figment = rocket::Config::figment()
+        .merge(("databases.db.url", path.display().to_string()));
+
+    let rocket = TestDatabase::<Db>::transaction()
+        .fixture(|db| Box::pin(async move {
+            sqlx::query("INSERT INTO users (name) VALUES ('bob')").execute(&**db).await?;
+            Ok(())
+        }))
+        .apply(rocket().reconfigure(figment));
+
+    let client = Client::tracked(rocket).await.unwrap();
+    client.post("/carol").dispatch().await;
+    assert_eq!(client.get("/").dispatch().await.into_string().await.unwrap(), "alice,bob,carol");
+
+    // Nothing is visible outside the transaction, and all of it is rolled back.
+    let names = || sqlx::query_scalar::<_, String>("SELECT name FROM users ORDER BY name");
+    assert_eq!(names().fetch_all(&pool).await.unwrap(), ["alice"]);
+    drop(client);
+
+    sqlx::query("INSERT INTO users (name) VALUES ('dave')").execute(&pool).await.unwrap();
+    assert_eq!(names().fetch_all(&pool).await.unwrap(), ["alice", "dave"]);
+    pool.close().await;
+    let _ = std::fs::remove_file(&path);
+}
+
+#[rocket::async_test]
+async fn transaction_databases_survive_reconnects() {
+    let path = std::env::temp_dir().join("rocket-db-pools-testing-reconnect.sqlite");
+    let _ = std::fs::remove_file(&path);
+
+    let url = format!("sqlite://{}?mode=rwc", path.display());
+    let pool = sqlx::SqlitePool::connect(&url).await.unwrap();
+    sqlx::query("CREATE TABLE users (name TEXT NOT NULL)").execute(&pool).await.unwrap();
+    sqlx::query("INSERT INTO users (name) VALUES ('alice')").execute(&pool).await.unwrap();
+
+    let figment = rocket::Config::figment()
+        .merge(("databases.db.url", path.display().to_string()));
+
+    let rocket = TestDatabase::<Db>::transaction().apply(rocket().reconfigure(figment));
+    let client = Client::tracked(rocket).await.unwrap();
+
+    // Closing the connection rolls back `bob`; its replacement begins anew.
+    client.post("/bob/reconnect").dispatch().await;
+    client.post("/carol").dispatch().await;
+    assert_eq!(client.get("/").dispatch().await.into_string().await.unwrap(), "alice,carol");
+
+    let names = || sqlx::query_scalar::<_, String>("SELECT name FROM users ORDER BY name");
+    assert_eq!(names().fetch_all(&pool).await.unwrap(), ["alice"]);
+    drop(client);
+
+    assert_eq!(names().fetch_all(&pool).await.unwrap(), ["alice"]);
+    pool.close().await;
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "sqlx_sqlite")]
+
+use rocket::{get, routes, uri, Build, Rocket};
+use rocket::http::Status;
+use rocket::response::Redirect;
+use rocket::figment::{Figment, util::map};
+use rocket::local::asynchronous::Client;
+use rocket_db_pools::{sqlx, Database, Transaction};
+
+#[derive(Database)]
+#[database("db")]
+struct Db(sqlx::SqlitePool);
+
+async fn insert(tx: &mut Transaction<Db>, route: &str) {
+    sqlx::query("INSERT INTO hits (route) VALUES (?)")
+        .bind(route)
+        .execute(&mut ***tx)
+        .await
+        .unwrap();
+}
+
+#[get("/ok")]
+async fn ok(mut tx: Transaction<Db>) -> Status {
+    insert(&mut tx, "ok").await;
+    Status::Ok
+}
+
+#[get("/redirect")]
+async fn redirect(mut tx: Transaction<Db>) -> Redirect {
+    insert(&mut tx, "redirect").await;
+    Redirect::to(uri!(ok))
+}
+
+#[get("/error")]
+async fn error(mut tx: Transaction<Db>) -> Status {
+    insert(&mut tx, "error").await;
+    Status::BadRequest
+}
+
+#[get("/panic")]
+async fn panic(mut tx: Transaction<Db>) -> Status {
+    insert(&mut tx, "panic").await;
+    panic!("handler panicked")
+}
+
+#[get("/commit")]
+async fn commit(mut tx: Transaction<Db>) -> Status {
+    insert(&mut tx, "commit").await;
+    tx.commit().await.unwrap();
+    Status::InternalServerError
+}
+
+#[get("/rollback")]
+async fn rollback(mut tx: Transaction<Db>) -> Status {
+    insert(&mut tx, "rollback").await;
+    tx.rollback().await.unwrap();
+    Status::Ok
+}
+
+fn rocket(name: &str) -> Rocket<Build> {
+    let path = std::env::temp_dir().join(format!("rocket-db-pools-tx-{name}.sqlite"));
+    let _ = std::fs::remove_file(&path);
+
+    let figment = Figment::from(rocket::Config::debug_default())
+        .merge(("databases.db", map!["url" => path.display().to_string()]));
+
+    rocket::custom(figment)
+        .attach(Db::init())
+        .mount("/", routes![ok, redirect, error, panic, commit, rollback])
+}
+
+async fn hits(client: &Client) -> Vec<String> {
+    let db = Db::fetch(client.rocket()).unwrap();
+    sqlx::query_scalar("SELECT route FROM hits ORDER BY id")
+        .fetch_all(&db.0)
+        .await
+        .unwrap()
+}
+
+#[rocket::async_test]
+async fn transactions_finalize_on_response() {
+    let client = Client::tracked(rocket("finalize")).await.unwrap();
+    let db = Db::fetch(client.rocket()).unwrap();
+    sqlx::query("CREATE TABLE hits (id INTEGER PRIMARY KEY, route TEXT NOT NULL)")
+        .execute(&db.0)
+        .await
+        .unwrap();
+
+    let routes = ["/ok", "/redirect", "/error", "/panic", "/commit", "/rollback"];
+    for route in routes {
+        client.get(route).dispatch().await;
+    }
+
+    let response = client.get("/redirect").dispatch().await;
+    assert_eq!(response.status(), Status::SeeOther);
+
+    assert_eq!(hits(&client).await, ["ok", "redirect", "commit", "redirect"]);
+}
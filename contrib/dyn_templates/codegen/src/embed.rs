@@ -0,0 +1,70 @@
+use std::path::Path;
+use std::error::Error;
+
+use devise::ext::SpanDiagnosticExt;
+use proc_macro2::TokenStream;
+use syn::LitStr;
+
+/// The extensions of all supported template engines.
+const ENGINE_EXTENSIONS: &[&str] = &["tera", "hbs", "j2"];
+
+pub fn embed(input: proc_macro::TokenStream) -> devise::Result<TokenStream> {
+    let dir = syn::parse::<LitStr>(input)?;
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").expect("MANIFEST_DIR");
+    let root = Path::new(&manifest_dir).join(dir.value());
+    let templates = templates(&root)
+        .map_err(|e| dir.span().error(format!("failed to read '{}': {}", root.display(), e)))?;
+
+    let root = root.display().to_string();
+    let templates = templates.iter().map(|(name, path, full_path)| quote! {
+        ::rocket_dyn_templates::EmbeddedTemplate {
+            name: #name,
+            path: #path,
+            contents: ::core::include_str!(#full_path),
+        }
+    });
+
+    Ok(quote! {
+        ::rocket_dyn_templates::EmbeddedTemplates::new(#root, &[#(#templates),*])
+    })
+}
+
+/// Returns the (name, relative path, full path) of every template in `root`.
+///
+/// Names are derived as they are for templates read from disk at runtime: the
+/// engine and data type extensions are removed from the relative path, which
+/// always uses `/` as its separator.
+fn templates(root: &Path) -> Result<Vec<(String, String, String)>, Box<dyn Error>> {
+    let mut templates = vec![];
+    for entry in walkdir::WalkDir::new(root).follow_links(true).sort_by_file_name() {
+        let entry = entry?;
+        let has_ext = entry.path().extension()
+            .and_then(|ext| ext.to_str())
+            .map_or(false, |ext| ENGINE_EXTENSIONS.contains(&ext));
+
+        if !entry.file_type().is_file() || !has_ext {
+            continue;
+        }
+
+        let relative = entry.path().strip_prefix(root)?;
+        let components = relative.iter()
+            .map(|c| c.to_str().ok_or("template paths must be valid UTF-8"))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let path = components.join("/");
+        let name = remove_extension(remove_extension(&path));
+        let full_path = entry.path().display().to_string();
+        templates.push((name.to_string(), path, full_path));
+    }
+
+    Ok(templates)
+}
+
+/// Removes the file name's last extension, if any, from `path`.
+fn remove_extension(path: &str) -> &str {
+    let file_start = path.rfind('/').map_or(0, |i| i + 1);
+    match path[file_start..].rfind('.') {
+        Some(0) | None => path,
+        Some(i) => &path[..file_start + i],
+    }
+}
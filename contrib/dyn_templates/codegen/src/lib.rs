@@ -0,0 +1,39 @@
+#![warn(rust_2018_idioms)]
+
+//! # `rocket_dyn_templates` - Code Generation
+//!
+//! Implements the code generation portion of the `rocket_dyn_templates` crate.
+//! This is an implementation detail. This create should never be depended on
+//! directly.
+
+#[macro_use] extern crate quote;
+
+mod embed;
+
+/// Embeds the templates in a directory into the binary.
+///
+/// The directory, relative to the crate's `Cargo.toml`, is walked at compile
+/// time and every file ending in a template engine extension (`.tera`,
+/// `.hbs`, or `.j2`) is included, whether or not the engine is enabled. The
+/// macro expands to an [`EmbeddedTemplates`] value to pass to
+/// [`Template::embed()`] or [`Template::try_embed()`].
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket_dyn_templates::{Template, embed};
+///
+/// #[launch]
+/// fn rocket() -> _ {
+///     # /*
+///     rocket::build().attach(Template::embed(embed!("templates")))
+///     # */ rocket::build().attach(Template::embed(embed!("../tests/templates")))
+/// }
+/// ```
+///
+/// [`EmbeddedTemplates`]: ../rocket_dyn_templates/struct.EmbeddedTemplates.html
+/// [`Template::embed()`]: ../rocket_dyn_templates/struct.Template.html#method.embed
+/// [`Template::try_embed()`]: ../rocket_dyn_templates/struct.Template.html#method.try_embed
+#[proc_macro]
+pub fn embed(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    crate::embed::embed(input).unwrap_or_else(|diag| diag.emit_as_expr_tokens()).into()
+}
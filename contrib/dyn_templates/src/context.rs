@@ -1,16 +1,19 @@
 use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 use std::error::Error;
+use std::sync::Arc;
 
 use crate::engine::Engines;
 use crate::template::TemplateInfo;
+use crate::embedded::{EmbeddedTemplate, EmbeddedTemplates};
 
 use rocket::http::ContentType;
 use normpath::PathExt;
 
 pub(crate) type Callback =
-    Box<dyn Fn(&mut Engines) -> Result<(), Box<dyn Error>> + Send + Sync + 'static>;
+    Arc<dyn Fn(&mut Engines) -> Result<(), Box<dyn Error>> + Send + Sync + 'static>;
 
+#[derive(Clone)]
 pub(crate) struct Context {
     /// The root of the template directory.
     pub root: PathBuf,
@@ -49,32 +52,37 @@ impl Context {
                     Ok(_) | Err(_) => continue,
                 };
 
-                let (template, data_type_str) = split_path(&root, entry.path());
-                if let Some(info) = templates.get(&*template) {
-                    warn!(
-                        %template,
-                        first_path = %entry.path().display(),
-                        second_path = info.path.as_ref().map(|p| display(p.display())),
-                        data_type = %info.data_type,
-                        "Template name '{template}' can refer to multiple templates.\n\
-                         First path will be used. Second path is ignored."
-                    );
-
-                    continue;
-                }
+                insert(&mut templates, &root, entry.into_path(), ext, None);
+            }
+        }
 
-                let data_type = data_type_str.as_ref()
-                    .and_then(|ext| ContentType::from_extension(ext))
-                    .unwrap_or(ContentType::Text);
+        Self::load(root, templates, callback)
+    }
 
-                templates.insert(template, TemplateInfo {
-                    path: Some(entry.into_path()),
-                    engine_ext: ext,
-                    data_type,
-                });
+    /// Load all of the templates in `embedded` and initialize them as
+    /// [`Context::initialize()`] does for templates on disk.
+    pub fn embedded(embedded: &EmbeddedTemplates, callback: &Callback) -> Option<Context> {
+        let root = embedded.dir().to_path_buf();
+        let mut templates: HashMap<String, TemplateInfo> = HashMap::new();
+        for &ext in Engines::ENABLED_EXTENSIONS {
+            for template in embedded.templates() {
+                let path = root.join(template.path);
+                if path.extension().map_or(false, |e| e == ext) {
+                    insert(&mut templates, &root, path, ext, Some(template));
+                }
             }
         }
 
+        Self::load(root, templates, callback)
+    }
+
+    /// Initializes the engines with `templates`, runs the user's `callback`,
+    /// and records any templates registered by the callback.
+    fn load(
+        root: PathBuf,
+        mut templates: HashMap<String, TemplateInfo>,
+        callback: &Callback,
+    ) -> Option<Context> {
         let mut engines = Engines::init(&templates)?;
         if let Err(reason) = callback(&mut engines) {
             error!(%reason, "template customization callback failed");
@@ -88,7 +96,7 @@ impl Context {
                     .and_then(ContentType::from_extension)
                     .unwrap_or(ContentType::Text);
 
-                let info = TemplateInfo { path: None, engine_ext, data_type };
+                let info = TemplateInfo { path: None, engine_ext, data_type, embedded: None };
                 templates.insert(name.to_string(), info);
             }
         }
@@ -100,56 +108,96 @@ impl Context {
 #[cfg(not(debug_assertions))]
 mod manager {
     use std::ops::Deref;
-    use super::Context;
+    use std::sync::Arc;
+
+    use rocket::tokio::sync::watch;
+
+    use super::{Callback, Context};
 
     /// Wraps a Context. With `cfg(debug_assertions)` active, this structure
-    /// additionally provides a method to reload the context at runtime.
-    pub(crate) struct ContextManager(Context);
+    /// additionally reloads the context at runtime as templates change.
+    pub(crate) struct ContextManager(Arc<Context>);
 
     impl ContextManager {
-        pub fn new(ctxt: Context) -> ContextManager {
-            ContextManager(ctxt)
+        pub fn new(ctxt: Context, _: &Callback) -> ContextManager {
+            ContextManager(Arc::new(ctxt))
         }
 
         pub fn context<'a>(&'a self) -> impl Deref<Target=Context> + 'a {
-            &self.0
+            &*self.0
+        }
+
+        /// Returns the current context, which can outlive a borrow of `self`.
+        pub fn snapshot(&self) -> Arc<Context> {
+            self.0.clone()
         }
 
         pub fn is_reloading(&self) -> bool {
             false
         }
+
+        pub fn subscribe(&self) -> Option<watch::Receiver<()>> {
+            None
+        }
     }
 }
 
 #[cfg(debug_assertions)]
 mod manager {
-    use std::ops::{Deref, DerefMut};
-    use std::sync::{RwLock, Mutex};
+    use std::ops::Deref;
+    use std::path::{Path, PathBuf};
+    use std::collections::HashSet;
+    use std::sync::{Arc, RwLock};
     use std::sync::mpsc::{channel, Receiver};
+    use std::time::Duration;
 
-    use notify::{recommended_watcher, Error, Event, RecommendedWatcher, RecursiveMode, Watcher};
+    use notify::{recommended_watcher, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+    use rocket::tokio::sync::watch;
 
-    use super::{Callback, Context};
+    use crate::engine::Engines;
+    use super::{split_path, Callback, Context};
+
+    /// How long to wait for further filesystem events before reloading, so
+    /// that a burst of events, say from an editor saving a file, results in a
+    /// single reload.
+    const DEBOUNCE: Duration = Duration::from_millis(100);
 
     /// Wraps a Context. With `cfg(debug_assertions)` active, this structure
-    /// additionally provides a method to reload the context at runtime.
+    /// additionally reloads the context at runtime as templates change.
     pub(crate) struct ContextManager {
-        /// The current template context, inside an RwLock so it can be updated.
-        context: RwLock<Context>,
-        /// A filesystem watcher and the receive queue for its events.
-        watcher: Option<(RecommendedWatcher, Mutex<Receiver<Result<Event, Error>>>)>,
+        /// The current template context, inside an RwLock so it can be
+        /// replaced. Readers clone the `Arc` and release the lock immediately
+        /// so that rendering never blocks, nor is blocked by, a reload.
+        context: Arc<RwLock<Arc<Context>>>,
+        /// A filesystem watcher and the sender of reload notifications.
+        watcher: Option<(RecommendedWatcher, watch::Sender<()>)>,
     }
 
-    impl ContextManager {
-        pub fn new(ctxt: Context) -> ContextManager {
-            let (tx, rx) = channel();
-            let watcher = recommended_watcher(tx).and_then(|mut watcher| {
-                watcher.watch(&ctxt.root.canonicalize()?, RecursiveMode::Recursive)?;
-                Ok(watcher)
-            });
+    /// Reloads templates on a background thread as filesystem events arrive.
+    struct Reloader {
+        /// The canonicalized root of the template directory being watched.
+        root: PathBuf,
+        context: Arc<RwLock<Arc<Context>>>,
+        callback: Callback,
+        reloads: watch::Sender<()>,
+    }
 
+    /// The effect of the filesystem events on the loaded templates.
+    #[derive(Default)]
+    struct Changes {
+        /// Names of loaded templates whose files were modified.
+        modified: HashSet<String>,
+        /// Whether templates were added or removed, requiring a full reload.
+        structural: bool,
+    }
+
+    impl ContextManager {
+        pub fn new(ctxt: Context, callback: &Callback) -> ContextManager {
+            let context = Arc::new(RwLock::new(Arc::new(ctxt)));
+            let (reloads, _) = watch::channel(());
+            let watcher = Self::watch(&context, callback, &reloads);
             let watcher = match watcher {
-                Ok(watcher) => Some((watcher, Mutex::new(rx))),
+                Ok(watcher) => Some((watcher, reloads)),
                 Err(e) => {
                     warn!("live template reloading initialization failed: {e}\n\
                         live template reloading is unavailable");
@@ -157,41 +205,189 @@ mod manager {
                 }
             };
 
-            ContextManager { watcher, context: RwLock::new(ctxt), }
+            ContextManager { watcher, context }
+        }
+
+        /// Watches the template directory, spawning a thread that reloads
+        /// templates as they change. The thread exits when the returned
+        /// watcher, and with it the sending end of the event queue, is dropped.
+        fn watch(
+            context: &Arc<RwLock<Arc<Context>>>,
+            callback: &Callback,
+            reloads: &watch::Sender<()>,
+        ) -> notify::Result<RecommendedWatcher> {
+            let root = context.read().unwrap().root.canonicalize()?;
+            let (tx, rx) = channel();
+            let mut watcher = recommended_watcher(tx)?;
+            watcher.watch(&root, RecursiveMode::Recursive)?;
+
+            let reloader = Reloader {
+                root,
+                context: context.clone(),
+                callback: callback.clone(),
+                reloads: reloads.clone(),
+            };
+
+            std::thread::Builder::new()
+                .name("rocket-template-reload".into())
+                .spawn(move || reloader.run(rx))?;
+
+            Ok(watcher)
         }
 
         pub fn context(&self) -> impl Deref<Target=Context> + '_ {
-            self.context.read().unwrap()
+            self.snapshot()
+        }
+
+        /// Returns the current context, which can outlive a borrow of `self`.
+        pub fn snapshot(&self) -> Arc<Context> {
+            self.context.read().unwrap().clone()
         }
 
         pub fn is_reloading(&self) -> bool {
             self.watcher.is_some()
         }
 
-        fn context_mut(&self) -> impl DerefMut<Target=Context> + '_ {
-            self.context.write().unwrap()
+        /// Returns a receiver notified each time templates are reloaded, if
+        /// reloading is enabled.
+        pub fn subscribe(&self) -> Option<watch::Receiver<()>> {
+            self.watcher.as_ref().map(|(_, reloads)| reloads.subscribe())
+        }
+    }
+
+    impl Reloader {
+        fn run(self, events: Receiver<notify::Result<Event>>) {
+            while let Ok(event) = events.recv() {
+                let mut batch = vec![event];
+                while let Ok(event) = events.recv_timeout(DEBOUNCE) {
+                    batch.push(event);
+                }
+
+                let changes = self.changes(batch);
+                if changes.structural {
+                    self.reload_all();
+                } else if !changes.modified.is_empty() {
+                    self.reload_modified(&changes.modified);
+                }
+            }
         }
 
-        /// Checks whether any template files have changed on disk. If there
-        /// have been changes since the last reload, all templates are
-        /// reinitialized from disk and the user's customization callback is run
-        /// again.
-        pub fn reload_if_needed(&self, callback: &Callback) {
-            let templates_changes = self.watcher.as_ref()
-                .map(|(_, rx)| rx.lock().expect("fsevents lock").try_iter().count() > 0);
-
-            if let Some(true) = templates_changes {
-                debug!("template change detected: reloading templates");
-                let root = self.context().root.clone();
-                if let Some(new_ctxt) = Context::initialize(&root, callback) {
-                    *self.context_mut() = new_ctxt;
-                } else {
-                    warn!("error while reloading template\n\
-                        existing templates will remain active.")
+        /// Determines how `events` affect the loaded templates. Paths are
+        /// inspected as they are _now_, after the burst of events, so that,
+        /// for instance, a file that is replaced by an editor is considered
+        /// modified while a temporary file that came and went is ignored.
+        fn changes(&self, events: Vec<notify::Result<Event>>) -> Changes {
+            let ctxt = self.context.read().unwrap().clone();
+            let mut changes = Changes::default();
+            let paths = events.into_iter().filter_map(|event| match event {
+                Ok(event) if matches!(event.kind, EventKind::Access(_)) => None,
+                Ok(event) => Some(event.paths),
+                Err(e) => {
+                    warn!("template directory watcher error: {e}");
+                    changes.structural = true;
+                    None
+                }
+            }).flatten().collect::<HashSet<_>>();
+
+            let is_loaded = |relative: &Path| ctxt.templates.values()
+                .filter_map(|info| info.path.as_ref()?.strip_prefix(&ctxt.root).ok())
+                .any(|loaded| loaded.starts_with(relative));
+
+            for path in paths {
+                let Ok(relative) = path.strip_prefix(&self.root) else {
+                    changes.structural = true;
+                    continue;
                 };
+
+                let is_template = path.extension()
+                    .and_then(|ext| ext.to_str())
+                    .map_or(false, |ext| Engines::ENABLED_EXTENSIONS.contains(&ext));
+
+                if !is_template {
+                    // A directory may have been added or removed along with
+                    // the templates inside of it.
+                    if path.is_dir() || (!path.exists() && is_loaded(relative)) {
+                        changes.structural = true;
+                    }
+
+                    continue;
+                }
+
+                // A template with the same name but a different path is one
+                // that was ignored in favor of this one, or vice versa.
+                let (name, _) = split_path(&self.root, &path);
+                let same_path = ctxt.templates.get(&name)
+                    .and_then(|info| info.path.as_ref()?.strip_prefix(&ctxt.root).ok())
+                    .map(|loaded| loaded == relative);
+
+                match (same_path, path.is_file()) {
+                    (Some(true), true) => { changes.modified.insert(name); }
+                    (Some(true), false) | (None, true) => changes.structural = true,
+                    (Some(false), _) | (None, false) => (),
+                }
+            }
+
+            changes
+        }
+
+        /// Reinitializes all templates from disk and runs the user's
+        /// customization callback again.
+        fn reload_all(&self) {
+            debug!("templates added or removed: reloading all templates");
+            let root = self.context.read().unwrap().root.clone();
+            if let Some(new_ctxt) = Context::initialize(&root, &self.callback) {
+                *self.context.write().unwrap() = Arc::new(new_ctxt);
+                self.reloads.send_replace(());
+            } else {
+                warn!("error while reloading templates\n\
+                    existing templates will remain active.")
             }
         }
+
+        /// Reloads only the `modified` templates from disk.
+        fn reload_modified(&self, modified: &HashSet<String>) {
+            debug!(templates = ?modified, "template change detected: reloading templates");
+            // Only this thread replaces the context, so none is lost.
+            let mut ctxt = Context::clone(&self.context.read().unwrap());
+            if ctxt.engines.reload(&ctxt.templates, modified) {
+                *self.context.write().unwrap() = Arc::new(ctxt);
+                self.reloads.send_replace(());
+            } else {
+                warn!("error while reloading templates\n\
+                    existing templates will remain active.")
+            }
+        }
+    }
+}
+
+/// Adds the template at `path`, with the engine extension `ext`, to
+/// `templates` unless a template with the same name has already been added.
+fn insert(
+    templates: &mut HashMap<String, TemplateInfo>,
+    root: &Path,
+    path: PathBuf,
+    engine_ext: &'static str,
+    embedded: Option<&'static EmbeddedTemplate>,
+) {
+    let (template, data_type_str) = split_path(root, &path);
+    if let Some(info) = templates.get(&*template) {
+        warn!(
+            %template,
+            first_path = %path.display(),
+            second_path = info.path.as_ref().map(|p| display(p.display())),
+            data_type = %info.data_type,
+            "Template name '{template}' can refer to multiple templates.\n\
+             First path will be used. Second path is ignored."
+        );
+
+        return;
     }
+
+    let data_type = data_type_str.as_ref()
+        .and_then(|ext| ContentType::from_extension(ext))
+        .unwrap_or(ContentType::Text);
+
+    templates.insert(template, TemplateInfo { path: Some(path), engine_ext, data_type, embedded });
 }
 
 /// Removes the file path's extension or does nothing if there is none.
@@ -0,0 +1,47 @@
+use std::path::Path;
+
+/// Templates embedded into the binary by [`embed!`](crate::embed!).
+///
+/// A value of this type is passed to [`Template::embed()`] or
+/// [`Template::try_embed()`] to render the embedded templates. In release
+/// builds, the templates are always loaded from the binary. In debug builds,
+/// they are instead read from the directory they were embedded from, if it
+/// still exists, so that they can be reloaded as they change.
+///
+/// [`Template::embed()`]: crate::Template::embed()
+/// [`Template::try_embed()`]: crate::Template::try_embed()
+#[derive(Debug, Clone, Copy)]
+pub struct EmbeddedTemplates {
+    dir: &'static str,
+    templates: &'static [EmbeddedTemplate],
+}
+
+/// A single template embedded by [`embed!`](crate::embed!).
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct EmbeddedTemplate {
+    /// The name of the template.
+    pub name: &'static str,
+    /// The path to the template relative to the embedded directory, with `/`
+    /// as the separator.
+    pub path: &'static str,
+    /// The contents of the template.
+    pub contents: &'static str,
+}
+
+impl EmbeddedTemplates {
+    #[doc(hidden)]
+    pub const fn new(dir: &'static str, templates: &'static [EmbeddedTemplate]) -> Self {
+        EmbeddedTemplates { dir, templates }
+    }
+
+    /// The absolute path to the directory the templates were embedded from.
+    pub(crate) fn dir(&self) -> &'static Path {
+        Path::new(self.dir)
+    }
+
+    /// The embedded templates.
+    pub(crate) fn templates(&self) -> &'static [EmbeddedTemplate] {
+        self.templates
+    }
+}
@@ -1,18 +1,39 @@
+use std::io;
 use std::path::Path;
 
 use handlebars::Handlebars;
 use rocket::serde::Serialize;
 
 use crate::engine::Engine;
+use crate::embedded::EmbeddedTemplate;
 
 impl Engine for Handlebars<'static> {
     const EXT: &'static str = "hbs";
 
     fn init<'a>(templates: impl Iterator<Item = (&'a str, &'a Path)>) -> Option<Self> {
+        let mut hb = Handlebars::new();
+        Engine::reload(&mut hb, templates).then_some(hb)
+    }
+
+    fn init_embedded(templates: impl Iterator<Item = &'static EmbeddedTemplate>) -> Option<Self> {
         let mut hb = Handlebars::new();
+        let mut ok = true;
+        for template in templates {
+            if let Err(e) = hb.register_template_string(template.name, template.contents) {
+                error!(template = template.name, path = template.path,
+                    "failed to register Handlebars template: {e}");
+
+                ok = false;
+            }
+        }
+
+        ok.then_some(hb)
+    }
+
+    fn reload<'a>(&mut self, templates: impl Iterator<Item = (&'a str, &'a Path)>) -> bool {
         let mut ok = true;
         for (template, path) in templates {
-            if let Err(e) = hb.register_template_file(template, path) {
+            if let Err(e) = self.register_template_file(template, path) {
                 error!(template, path = %path.display(),
                     "failed to register Handlebars template: {e}");
 
@@ -20,7 +41,7 @@ impl Engine for Handlebars<'static> {
             }
         }
 
-        ok.then_some(hb)
+        ok
     }
 
     fn render<C: Serialize>(&self, template: &str, context: C) -> Option<String> {
@@ -33,4 +54,15 @@ impl Engine for Handlebars<'static> {
             .map_err(|e| error!("Handlebars render error: {}", e))
             .ok()
     }
+
+    fn render_to<C: Serialize>(&self, template: &str, ctxt: C, out: &mut dyn io::Write) -> bool {
+        if self.get_template(template).is_none() {
+            error!(template, "requested Handlebars template does not exist.");
+            return false;
+        }
+
+        Handlebars::render_to_write(self, template, &ctxt, out)
+            .map_err(|e| error!("Handlebars render error: {}", e))
+            .is_ok()
+    }
 }
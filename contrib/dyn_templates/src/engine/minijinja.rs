@@ -1,3 +1,4 @@
+use std::io;
 use std::sync::Arc;
 use std::path::Path;
 use std::collections::HashMap;
@@ -6,6 +7,7 @@ use rocket::serde::Serialize;
 use minijinja::{Environment, Error, ErrorKind, AutoEscape};
 
 use crate::engine::Engine;
+use crate::embedded::EmbeddedTemplate;
 
 impl Engine for Environment<'static> {
     const EXT: &'static str = "j2";
@@ -42,6 +44,50 @@ impl Engine for Environment<'static> {
         Some(env)
     }
 
+    fn init_embedded(templates: impl Iterator<Item = &'static EmbeddedTemplate>) -> Option<Self> {
+        let templates: Vec<_> = templates.collect();
+        let paths = templates.iter()
+            .map(|t| (t.name, t.path))
+            .collect::<HashMap<_, _>>();
+
+        // Escape as if the templates were loaded from their paths. This must
+        // be set before templates are added as they are compiled when added.
+        let mut env = Environment::new();
+        env.set_auto_escape_callback(move |name| {
+            paths.get(name)
+                .map(|path| minijinja::default_auto_escape_callback(path))
+                .unwrap_or(AutoEscape::None)
+        });
+
+        let mut ok = true;
+        for template in templates {
+            if let Err(e) = env.add_template(template.name, template.contents) {
+                error!(template = template.name, path = template.path,
+                    "failed to register Minijinja template: {e}");
+
+                ok = false;
+            }
+        }
+
+        ok.then_some(env)
+    }
+
+    fn reload<'a>(&mut self, templates: impl Iterator<Item = (&'a str, &'a Path)>) -> bool {
+        let mut ok = true;
+        for (template, path) in templates {
+            // Evict the cached template so that the loader reads it again.
+            self.remove_template(template);
+            if let Err(e) = self.get_template(template) {
+                error!(template, path = %path.display(),
+                    "failed to reload Minijinja template: {e}");
+
+                ok = false;
+            }
+        }
+
+        ok
+    }
+
     fn render<C: Serialize>(&self, template: &str, context: C) -> Option<String> {
         let Ok(templ) = self.get_template(template) else {
             error!(template, "requested template does not exist");
@@ -63,4 +109,25 @@ impl Engine for Environment<'static> {
             }
         }
     }
+
+    fn render_to<C: Serialize>(&self, template: &str, ctxt: C, out: &mut dyn io::Write) -> bool {
+        let Ok(templ) = self.get_template(template) else {
+            error!(template, "requested template does not exist");
+            return false;
+        };
+
+        if let Err(e) = templ.render_captured_to(ctxt, out) {
+            span_error!("templating", template, "failed to render Minijinja template" => {
+                let mut error = Some(&e as &dyn std::error::Error);
+                while let Some(err) = error {
+                    error!("{err}");
+                    error = err.source();
+                }
+            });
+
+            return false;
+        }
+
+        true
+    }
 }
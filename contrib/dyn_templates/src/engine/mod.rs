@@ -1,9 +1,11 @@
+use std::io;
 use std::path::Path;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use rocket::serde::Serialize;
 
 use crate::template::TemplateInfo;
+use crate::embedded::EmbeddedTemplate;
 
 #[cfg(feature = "tera")]
 mod tera;
@@ -20,11 +22,14 @@ mod minijinja;
 #[cfg(feature = "minijinja")]
 use ::minijinja::Environment;
 
-pub(crate) trait Engine: Send + Sync + Sized + 'static {
+pub(crate) trait Engine: Send + Sync + Sized + Clone + 'static {
     const EXT: &'static str;
 
     fn init<'a>(templates: impl Iterator<Item = (&'a str, &'a Path)>) -> Option<Self>;
+    fn init_embedded(templates: impl Iterator<Item = &'static EmbeddedTemplate>) -> Option<Self>;
+    fn reload<'a>(&mut self, templates: impl Iterator<Item = (&'a str, &'a Path)>) -> bool;
     fn render<C: Serialize>(&self, name: &str, context: C) -> Option<String>;
+    fn render_to<C: Serialize>(&self, name: &str, context: C, out: &mut dyn io::Write) -> bool;
 }
 
 /// A structure exposing access to templating engines.
@@ -63,6 +68,7 @@ pub(crate) trait Engine: Send + Sync + Sized + 'static {
 ///
 /// [`tera::Value`]: crate::tera::Value
 /// [`tera::Result`]: crate::tera::Result
+#[derive(Clone)]
 pub struct Engines {
     /// A `Tera` templating engine.
     ///
@@ -100,8 +106,12 @@ impl Engines {
 
     pub(crate) fn init(templates: &HashMap<String, TemplateInfo>) -> Option<Engines> {
         fn inner<E: Engine>(templates: &HashMap<String, TemplateInfo>) -> Option<E> {
-            let named_templates = templates.iter()
-                .filter(|&(_, i)| i.engine_ext == E::EXT)
+            let engine_templates = || templates.iter().filter(|&(_, i)| i.engine_ext == E::EXT);
+            if engine_templates().any(|(_, i)| i.embedded.is_some()) {
+                return E::init_embedded(engine_templates().filter_map(|(_, i)| i.embedded));
+            }
+
+            let named_templates = engine_templates()
                 .filter_map(|(k, i)| Some((k.as_str(), i.path.as_ref()?)))
                 .map(|(k, p)| (k, p.as_path()));
 
@@ -127,6 +137,59 @@ impl Engines {
         })
     }
 
+    /// Reloads the templates named in `changed` from disk. If any template
+    /// fails to load, no engine is modified and `false` is returned.
+    pub(crate) fn reload(
+        &mut self,
+        templates: &HashMap<String, TemplateInfo>,
+        changed: &HashSet<String>,
+    ) -> bool {
+        /// Returns a reloaded copy of `engine` if any of its templates changed.
+        fn inner<E: Engine>(
+            engine: &E,
+            templates: &HashMap<String, TemplateInfo>,
+            changed: &HashSet<String>,
+        ) -> Result<Option<E>, ()> {
+            let mut named_templates = changed.iter()
+                .filter_map(|k| Some((k.as_str(), templates.get(k)?)))
+                .filter(|&(_, i)| i.engine_ext == E::EXT)
+                .filter_map(|(k, i)| Some((k, i.path.as_ref()?)))
+                .map(|(k, p)| (k, p.as_path()))
+                .peekable();
+
+            if named_templates.peek().is_none() {
+                return Ok(None);
+            }
+
+            let mut engine = engine.clone();
+            match Engine::reload(&mut engine, named_templates) {
+                true => Ok(Some(engine)),
+                false => Err(()),
+            }
+        }
+
+        #[cfg(feature = "tera")]
+        let Ok(tera) = inner(&self.tera, templates, changed) else {
+            return false;
+        };
+
+        #[cfg(feature = "handlebars")]
+        let Ok(handlebars) = inner(&self.handlebars, templates, changed) else {
+            return false;
+        };
+
+        #[cfg(feature = "minijinja")]
+        let Ok(minijinja) = inner(&self.minijinja, templates, changed) else {
+            return false;
+        };
+
+        #[cfg(feature = "tera")] if let Some(tera) = tera { self.tera = tera; }
+        #[cfg(feature = "handlebars")] if let Some(hb) = handlebars { self.handlebars = hb; }
+        #[cfg(feature = "minijinja")] if let Some(env) = minijinja { self.minijinja = env; }
+
+        true
+    }
+
     pub(crate) fn render<C: Serialize>(
         &self,
         name: &str,
@@ -154,6 +217,36 @@ impl Engines {
         None
     }
 
+    /// Renders the template `name` into `out` as it is rendered, returning
+    /// `false` if rendering fails.
+    pub(crate) fn render_to<C: Serialize>(
+        &self,
+        name: &str,
+        info: &TemplateInfo,
+        context: C,
+        out: &mut dyn io::Write,
+    ) -> bool {
+        #[cfg(feature = "tera")] {
+            if info.engine_ext == Tera::EXT {
+                return Engine::render_to(&self.tera, name, context, out);
+            }
+        }
+
+        #[cfg(feature = "handlebars")] {
+            if info.engine_ext == Handlebars::EXT {
+                return Engine::render_to(&self.handlebars, name, context, out);
+            }
+        }
+
+        #[cfg(feature = "minijinja")] {
+            if info.engine_ext == Environment::EXT {
+                return Engine::render_to(&self.minijinja, name, context, out);
+            }
+        }
+
+        false
+    }
+
     /// Returns iterator over template (name, engine_extension).
     pub(crate) fn templates(&self) -> impl Iterator<Item = (&str, &'static str)> {
         #[cfg(feature = "tera")]
@@ -1,3 +1,4 @@
+use std::io;
 use std::path::Path;
 use std::error::Error;
 
@@ -5,6 +6,10 @@ use tera::{Context, Tera};
 use rocket::serde::Serialize;
 
 use crate::engine::Engine;
+use crate::embedded::EmbeddedTemplate;
+
+/// The extensions of templates that are automatically escaped.
+const AUTOESCAPE: &[&str] = &[".html.tera", ".htm.tera", ".xml.tera", ".html", ".htm", ".xml"];
 
 impl Engine for Tera {
     const EXT: &'static str = "tera";
@@ -12,27 +17,39 @@ impl Engine for Tera {
     fn init<'a>(templates: impl Iterator<Item = (&'a str, &'a Path)>) -> Option<Self> {
         // Create the Tera instance.
         let mut tera = Tera::default();
-        let ext = [".html.tera", ".htm.tera", ".xml.tera", ".html", ".htm", ".xml"];
-        tera.autoescape_on(ext.to_vec());
+        tera.autoescape_on(AUTOESCAPE.to_vec());
+
+        // Finally, try to tell Tera about all of the templates.
+        Engine::reload(&mut tera, templates).then_some(tera)
+    }
+
+    fn init_embedded(templates: impl Iterator<Item = &'static EmbeddedTemplate>) -> Option<Self> {
+        let templates: Vec<_> = templates.collect();
+
+        // Tera decides whether to escape templates without a path by their
+        // name alone, so additionally match the names of those whose path
+        // would be escaped.
+        let mut tera = Tera::default();
+        let escaped = templates.iter()
+            .filter(|t| AUTOESCAPE.iter().any(|ext| t.path.ends_with(ext)))
+            .map(|t| t.name);
 
+        tera.autoescape_on(AUTOESCAPE.iter().copied().chain(escaped).collect());
+
+        // As with files, register all templates at once so that Tera doesn't
+        // complain about unregistered base templates.
+        let raw = templates.iter().map(|t| (t.name, t.contents));
+        loaded(tera.add_raw_templates(raw)).then_some(tera)
+    }
+
+    fn reload<'a>(&mut self, templates: impl Iterator<Item = (&'a str, &'a Path)>) -> bool {
         // Collect into a tuple of (name, path) for Tera. If we register one at
         // a time, it will complain about unregistered base templates.
         let files = templates.map(|(name, path)| (path, Some(name)));
 
-        // Finally try to tell Tera about all of the templates.
-        if let Err(e) = tera.add_template_files(files) {
-            span_error!("templating", "Tera templating initialization failed" => {
-                let mut error = Some(&e as &dyn Error);
-                while let Some(err) = error {
-                    error!("{err}");
-                    error = err.source();
-                }
-            });
-
-            None
-        } else {
-            Some(tera)
-        }
+        // Tera rebuilds inheritance chains for all templates, so templates
+        // extending a reloaded template pick up its changes.
+        loaded(self.add_template_files(files))
     }
 
     fn render<C: Serialize>(&self, template: &str, context: C) -> Option<String> {
@@ -60,4 +77,50 @@ impl Engine for Tera {
             }
         }
     }
+
+    fn render_to<C: Serialize>(&self, template: &str, ctxt: C, out: &mut dyn io::Write) -> bool {
+        if self.get_template(template).is_err() {
+            error!(template, "requested template does not exist");
+            return false;
+        };
+
+        let tera_ctx = match Context::from_serialize(ctxt) {
+            Ok(tera_ctx) => tera_ctx,
+            Err(e) => {
+                error!("Tera context error: {}.", e);
+                return false;
+            }
+        };
+
+        if let Err(e) = Tera::render_to(self, template, &tera_ctx, out) {
+            span_error!("templating", template, "failed to render Tera template" => {
+                let mut error = Some(&e as &dyn Error);
+                while let Some(err) = error {
+                    error!("{err}");
+                    error = err.source();
+                }
+            });
+
+            return false;
+        }
+
+        true
+    }
+}
+
+/// Logs the error in `result`, if any, returning `true` if there was none.
+fn loaded(result: tera::Result<()>) -> bool {
+    if let Err(e) = result {
+        span_error!("templating", "Tera template loading failed" => {
+            let mut error = Some(&e as &dyn Error);
+            while let Some(err) = error {
+                error!("{err}");
+                error = err.source();
+            }
+        });
+
+        return false;
+    }
+
+    true
 }
@@ -6,48 +6,60 @@ use rocket::trace::Trace;
 use crate::context::{Callback, Context, ContextManager};
 use crate::template::DEFAULT_TEMPLATE_DIR;
 use crate::engine::Engines;
+use crate::embedded::EmbeddedTemplates;
 
 /// The TemplateFairing initializes the template system on attach, running
-/// custom_callback after templates have been loaded. In debug mode, the fairing
-/// checks for modifications to templates before every request and reloads them
-/// if necessary.
+/// custom_callback after templates have been loaded. In debug mode, templates
+/// are reloaded in the background as they change on disk.
 pub struct TemplateFairing {
     /// The user-provided customization callback, allowing the use of
     /// functionality specific to individual template engines. In debug mode,
     /// this callback might be run multiple times as templates are reloaded.
     pub callback: Callback,
+    /// The templates embedded into the binary, if templates are embedded.
+    pub embedded: Option<EmbeddedTemplates>,
 }
 
 #[rocket::async_trait]
 impl Fairing for TemplateFairing {
     fn info(&self) -> Info {
-        let kind = Kind::Ignite | Kind::Liftoff;
-        #[cfg(debug_assertions)] let kind = kind | Kind::Request;
-
-        Info { kind, name: "Templating" }
+        Info { kind: Kind::Ignite | Kind::Liftoff, name: "Templating" }
     }
 
-    /// Initializes the template context. Templates will be searched for in the
-    /// `template_dir` config variable or the default ([DEFAULT_TEMPLATE_DIR]).
+    /// Initializes the template context. Unless templates are embedded,
+    /// templates will be searched for in the `template_dir` config variable or
+    /// the default ([DEFAULT_TEMPLATE_DIR]).
     /// The user's callback, if any was supplied, is called to customize the
     /// template engines. In debug mode, the `ContextManager::new` method
     /// initializes a directory watcher for auto-reloading of templates.
     async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
-        let configured_dir = rocket.figment()
-            .extract_inner::<RelativePathBuf>("template_dir")
-            .map(|path| path.relative());
+        let ctxt = match &self.embedded {
+            // Debug builds read embedded templates from disk, if they're still
+            // there, so that they can be reloaded.
+            Some(embedded) if cfg!(debug_assertions) && embedded.dir().is_dir() => {
+                Context::initialize(embedded.dir(), &self.callback)
+            }
+            Some(embedded) => Context::embedded(embedded, &self.callback),
+            None => {
+                let configured_dir = rocket.figment()
+                    .extract_inner::<RelativePathBuf>("template_dir")
+                    .map(|path| path.relative());
 
-        let path = match configured_dir {
-            Ok(dir) => dir,
-            Err(e) if e.missing() => DEFAULT_TEMPLATE_DIR.into(),
-            Err(e) => {
-                e.trace_error();
-                return Err(rocket);
+                let path = match configured_dir {
+                    Ok(dir) => dir,
+                    Err(e) if e.missing() => DEFAULT_TEMPLATE_DIR.into(),
+                    Err(e) => {
+                        e.trace_error();
+                        return Err(rocket);
+                    }
+                };
+
+                Context::initialize(&path, &self.callback)
             }
         };
 
-        if let Some(ctxt) = Context::initialize(&path, &self.callback) {
-            Ok(rocket.manage(ContextManager::new(ctxt)))
+        if let Some(ctxt) = ctxt {
+            Ok(rocket.manage(ContextManager::new(ctxt, &self.callback)))
         } else {
             error!("Template initialization failed. Aborting launch.");
             Err(rocket)
@@ -63,12 +75,4 @@ impl Fairing for TemplateFairing {
             info!(engines = ?Engines::ENABLED_EXTENSIONS);
         });
     }
-
-    #[cfg(debug_assertions)]
-    async fn on_request(&self, req: &mut rocket::Request<'_>, _data: &mut rocket::Data<'_>) {
-        let cm = req.rocket().state::<ContextManager>()
-            .expect("Template ContextManager registered in on_ignite");
-
-        cm.reload_if_needed(&self.callback);
-    }
 }
@@ -163,18 +163,56 @@
 //! automatically removed._
 //!
 //! In debug mode (without the `--release` flag passed to `cargo`), templates
-//! are **automatically reloaded** from disk when changes are made. In release
+//! are **automatically reloaded** from disk when changes are made. Changes are
+//! detected via filesystem events and applied in the background: modified
+//! templates are reloaded individually while adding or removing templates
+//! reloads all templates and reruns the customization callback. In release
 //! builds, template reloading is disabled to improve performance and cannot be
 //! enabled.
 //!
+//! To have browsers reload pages as templates change, mount the routes
+//! returned by [`Template::live_reload()`] and include the script they serve
+//! in your templates.
+//!
 //! [attached]: rocket::Rocket::attach()
 //!
+//! ### Embedded Templates
+//!
+//! To ship a single binary without a template directory, templates can be
+//! embedded into the binary at compile time with [`embed!`] and rendered via
+//! the [`Template::embed()`] or [`Template::try_embed()`] fairings:
+//!
+//! ```rust
+//! # #[macro_use] extern crate rocket;
+//! use rocket_dyn_templates::{Template, embed};
+//!
+//! #[launch]
+//! fn rocket() -> _ {
+//!     # /*
+//!     rocket::build().attach(Template::embed(embed!("templates")))
+//!     # */ rocket::build().attach(Template::embed(embed!("tests/templates")))
+//! }
+//! ```
+//!
+//! Embedded templates are named, and are rendered with [`Template::render()`],
+//! exactly as templates read from disk are. In debug builds, the templates are
+//! read from the directory they were embedded from and reloaded as usual.
+//! Note that adding a template to the directory does not cause the crate to
+//! be recompiled; modifying an embedded template does.
+//!
 //! ### Metadata and Rendering to `String`
 //!
 //! The [`Metadata`] request guard allows dynamically querying templating
 //! metadata, such as whether a template is known to exist
 //! ([`Metadata::contains_template()`]), and to render templates to `String`
 //! ([`Metadata::render()`]).
+//!
+//! ### Streaming
+//!
+//! [`Template::render()`] renders the entire template into memory before it
+//! is sent. For large pages, [`Template::stream()`] instead streams the
+//! template into the response body as it is rendered, bounding memory use and
+//! sending the beginning of the page early.
 
 #![doc(html_root_url = "https://api.rocket.rs/master/rocket_dyn_templates")]
 #![doc(html_favicon_url = "https://rocket.rs/images/favicon.ico")]
@@ -205,7 +243,17 @@ mod fairing;
 mod context;
 mod metadata;
 mod template;
+mod live_reload;
+mod embedded;
+mod stream;
 
 pub use engine::Engines;
 pub use metadata::Metadata;
 pub use template::Template;
+pub use embedded::EmbeddedTemplates;
+
+#[doc(hidden)]
+pub use embedded::EmbeddedTemplate;
+
+#[doc(inline)]
+pub use rocket_dyn_templates_codegen::embed;
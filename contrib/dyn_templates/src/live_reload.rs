@@ -0,0 +1,36 @@
+use std::pin::pin;
+
+use rocket::Shutdown;
+use rocket::http::ContentType;
+use rocket::futures::future::{select, Either};
+use rocket::response::stream::{Event, EventStream};
+
+use crate::Metadata;
+
+/// Subscribes to the event stream mounted alongside it and reloads the page
+/// when templates are reloaded.
+const CLIENT: &str = r#"(() => {
+  const events = new EventSource(new URL("events", document.currentScript.src));
+  events.addEventListener("reload", () => window.location.reload());
+})();
+"#;
+
+/// Streams a `reload` event each time templates are reloaded.
+#[get("/events")]
+pub(crate) fn events(metadata: Metadata<'_>, mut shutdown: Shutdown) -> Option<EventStream![]> {
+    let mut reloads = metadata.reloads()?;
+    Some(EventStream! {
+        loop {
+            let reloaded = pin!(reloads.changed());
+            match select(reloaded, &mut shutdown).await {
+                Either::Left((Ok(()), _)) => yield Event::data("reload").event("reload"),
+                Either::Left((Err(_), _)) | Either::Right(_) => break,
+            }
+        }
+    })
+}
+
+#[get("/client.js")]
+pub(crate) fn client() -> (ContentType, &'static str) {
+    (ContentType::JavaScript, CLIENT)
+}
@@ -5,6 +5,7 @@ use rocket::{Request, Rocket, Ignite, Sentinel};
 use rocket::http::{Status, ContentType};
 use rocket::request::{self, FromRequest};
 use rocket::serde::Serialize;
+use rocket::tokio::sync::watch;
 
 use crate::{Template, context::ContextManager};
 
@@ -82,6 +83,12 @@ impl Metadata<'_> {
         self.0.is_reloading()
     }
 
+    /// Returns a receiver notified each time templates are reloaded, if
+    /// template reloading is enabled.
+    pub(crate) fn reloads(&self) -> Option<watch::Receiver<()>> {
+        self.0.subscribe()
+    }
+
     /// Directly render the template named `name` with the context `context`
     /// into a `String`. Also returns the template's detected `ContentType`. See
     /// [`Template::render()`] for more details on rendering.
@@ -0,0 +1,99 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use rocket::tokio::io::{AsyncRead, ReadBuf};
+use rocket::tokio::sync::mpsc;
+
+/// Amount of rendered output buffered before it is sent to the response body.
+const CHUNK_SIZE: usize = 4096;
+
+/// Number of chunks that can be queued before rendering waits for the body to
+/// be read. Bounds the memory used by a streamed template.
+const QUEUED_CHUNKS: usize = 4;
+
+/// An `io::Write` that sends rendered output, in chunks, to a [`ChunkReader`].
+///
+/// Writes block when the reader is behind, so the writer must only be used on
+/// a thread where blocking is allowed.
+pub(crate) struct ChunkWriter {
+    tx: mpsc::Sender<io::Result<Vec<u8>>>,
+    buf: Vec<u8>,
+}
+
+/// An `AsyncRead` over the chunks sent by a [`ChunkWriter`].
+///
+/// The reader returns EOF once the writer is dropped, or the error the writer
+/// [failed](ChunkWriter::fail()) with, if any.
+pub(crate) struct ChunkReader {
+    rx: mpsc::Receiver<io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+/// Returns a connected writer and reader.
+pub(crate) fn channel() -> (ChunkWriter, ChunkReader) {
+    let (tx, rx) = mpsc::channel(QUEUED_CHUNKS);
+    let writer = ChunkWriter { tx, buf: Vec::with_capacity(CHUNK_SIZE) };
+    let reader = ChunkReader { rx, chunk: vec![], pos: 0 };
+    (writer, reader)
+}
+
+impl ChunkWriter {
+    /// Returns `true` if the reader has been dropped.
+    pub(crate) fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+
+    /// Discards any buffered output and sends an error with `message` to the
+    /// reader in its place.
+    pub(crate) fn fail(self, message: &'static str) {
+        let error = io::Error::new(io::ErrorKind::Other, message);
+        let _ = self.tx.blocking_send(Err(error));
+    }
+}
+
+impl io::Write for ChunkWriter {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(bytes);
+        if self.buf.len() >= CHUNK_SIZE {
+            self.flush()?;
+        }
+
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+
+        let chunk = std::mem::replace(&mut self.buf, Vec::with_capacity(CHUNK_SIZE));
+        self.tx.blocking_send(Ok(chunk))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "response body was dropped"))
+    }
+}
+
+impl AsyncRead for ChunkReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        while self.pos == self.chunk.len() {
+            match ready!(self.rx.poll_recv(cx)) {
+                Some(Ok(chunk)) => {
+                    self.chunk = chunk;
+                    self.pos = 0;
+                }
+                Some(Err(e)) => return Poll::Ready(Err(e)),
+                None => return Poll::Ready(Ok(())),
+            }
+        }
+
+        let n = std::cmp::min(buf.remaining(), self.chunk.len() - self.pos);
+        buf.put_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Poll::Ready(Ok(()))
+    }
+}
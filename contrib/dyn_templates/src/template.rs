@@ -1,18 +1,22 @@
 use std::borrow::Cow;
+use std::io::Write;
 use std::path::PathBuf;
+use std::sync::Arc;
 
-use rocket::{Rocket, Orbit, Ignite, Sentinel};
+use rocket::{Rocket, Orbit, Ignite, Sentinel, Route};
 use rocket::request::Request;
 use rocket::fairing::Fairing;
-use rocket::response::{self, Responder};
+use rocket::response::{self, Responder, Response};
 use rocket::http::{ContentType, Status};
 use rocket::figment::{value::Value, error::Error};
 use rocket::trace::Trace;
 use rocket::serde::Serialize;
 
 use crate::Engines;
+use crate::embedded::{EmbeddedTemplate, EmbeddedTemplates};
 use crate::fairing::TemplateFairing;
 use crate::context::{Context, ContextManager};
+use crate::stream;
 
 pub(crate) const DEFAULT_TEMPLATE_DIR: &str = "templates";
 
@@ -27,16 +31,19 @@ pub(crate) const DEFAULT_TEMPLATE_DIR: &str = "templates";
 pub struct Template {
     name: Cow<'static, str>,
     value: Result<Value, Error>,
+    streamed: bool,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct TemplateInfo {
     /// The complete path, including `template_dir`, to this template, if any.
     pub(crate) path: Option<PathBuf>,
     /// The extension for the engine of this template.
     pub(crate) engine_ext: &'static str,
     /// The extension before the engine extension in the template, if any.
-    pub(crate) data_type: ContentType
+    pub(crate) data_type: ContentType,
+    /// The embedded template, if this template was embedded into the binary.
+    pub(crate) embedded: Option<&'static EmbeddedTemplate>,
 }
 
 impl Template {
@@ -112,7 +119,9 @@ impl Template {
     /// This variant of [`Template::custom()`] allows a fallible `f`. If `f`
     /// returns an error during initialization, it will cancel the launch. If
     /// `f` returns an error during template reloading (in debug mode), then the
-    /// newly-reloaded templates are discarded.
+    /// newly-reloaded templates are discarded. Note that `f` is only run again
+    /// when templates are added or removed; modified templates are reloaded in
+    /// place.
     ///
     /// # Example
     ///
@@ -136,7 +145,104 @@ impl Template {
     pub fn try_custom<F: Send + Sync + 'static>(f: F) -> impl Fairing
         where F: Fn(&mut Engines) -> Result<(), Box<dyn std::error::Error>>
     {
-        TemplateFairing { callback: Box::new(f) }
+        TemplateFairing { callback: Arc::new(f), embedded: None }
+    }
+
+    /// Returns a fairing that initializes and maintains templating state using
+    /// templates embedded into the binary via [`embed!`](crate::embed!).
+    ///
+    /// Embedded templates are named and rendered exactly as templates read
+    /// from disk are: [`Template::render()`] works identically in both modes.
+    /// The `template_dir` configuration parameter is ignored.
+    ///
+    /// In release builds, templates are loaded from the binary, so no template
+    /// directory needs to be present at runtime. In debug builds, templates
+    /// are instead read from the directory they were embedded from, when it
+    /// exists, and are [reloaded](crate#discovery-automatic-reloads-and-engine-customization)
+    /// as they change. To customize the templating engines, use
+    /// [`Template::try_embed()`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket_dyn_templates::{Template, embed};
+    ///
+    /// #[launch]
+    /// fn rocket() -> _ {
+    ///     # /*
+    ///     rocket::build().attach(Template::embed(embed!("templates")))
+    ///     # */ rocket::build().attach(Template::embed(embed!("tests/templates")))
+    /// }
+    /// ```
+    pub fn embed(templates: EmbeddedTemplates) -> impl Fairing {
+        Template::try_embed(templates, |_| Ok(()))
+    }
+
+    /// Returns a fairing that initializes and maintains templating state using
+    /// embedded templates, customizing the templating engines via the function
+    /// `f`.
+    ///
+    /// This is the embedded variant of [`Template::try_custom()`]. See
+    /// [`Template::embed()`] for details on embedded templates.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket_dyn_templates::{Template, embed};
+    ///
+    /// #[launch]
+    /// fn rocket() -> _ {
+    ///     # /*
+    ///     let templates = embed!("templates");
+    ///     # */ let templates = embed!("tests/templates");
+    ///     rocket::build().attach(Template::try_embed(templates, |engines| {
+    ///         // engines.handlebars.register_helper ...
+    ///         Ok(())
+    ///     }))
+    /// }
+    /// ```
+    pub fn try_embed<F: Send + Sync + 'static>(templates: EmbeddedTemplates, f: F) -> impl Fairing
+        where F: Fn(&mut Engines) -> Result<(), Box<dyn std::error::Error>>
+    {
+        TemplateFairing { callback: Arc::new(f), embedded: Some(templates) }
+    }
+
+    /// Returns routes that notify browsers when templates are reloaded.
+    ///
+    /// The routes are a [Server-Sent Events](rocket::response::stream::EventStream)
+    /// stream at `events`, which emits a `reload` event each time templates
+    /// are reloaded, and a script at `client.js` which subscribes to the
+    /// stream and reloads the page when an event is received. Mount the routes
+    /// and include the script in a template to have pages reload as their
+    /// templates change.
+    ///
+    /// Templates are only reloaded in debug mode. Otherwise, or if a template
+    /// directory watcher could not be initialized, the stream responds with a
+    /// `404 Not Found`, and the script does nothing further.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket_dyn_templates::Template;
+    ///
+    /// #[launch]
+    /// fn rocket() -> _ {
+    ///     rocket::build()
+    ///         .attach(Template::fairing())
+    ///         .mount("/__live_reload", Template::live_reload())
+    /// }
+    /// ```
+    ///
+    /// Then, in a template, typically one that every page extends:
+    ///
+    /// ```html
+    /// <script src="/__live_reload/client.js"></script>
+    /// ```
+    pub fn live_reload() -> Vec<Route> {
+        routes![crate::live_reload::events, crate::live_reload::client]
     }
 
     /// Render the template named `name` with the context `context`. The
@@ -178,9 +284,45 @@ impl Template {
         Template {
             name: name.into(),
             value: Value::serialize(context),
+            streamed: false,
         }
     }
 
+    /// Render the template named `name` with the context `context`, streaming
+    /// the rendered template into the response body as it is rendered.
+    ///
+    /// Unlike [`Template::render()`], which renders the entire template into
+    /// memory before responding, the template is rendered on a blocking
+    /// thread and sent to the client in chunks of a few kilobytes. Only a few
+    /// chunks are buffered at a time, bounding the memory used to render large
+    /// pages, and the beginning of the page, such as its `<head>`, is sent
+    /// before the rest of the page has been rendered.
+    ///
+    /// Because the response status is sent before rendering completes, a
+    /// template that fails to render partway through results in a truncated
+    /// response rather than a `500 Internal Server Error`. A template that
+    /// doesn't exist or a `context` that fails to serialize still results in a
+    /// `500`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket_dyn_templates::{Template, context};
+    ///
+    /// #[get("/report")]
+    /// fn report() -> Template {
+    ///     let rows: Vec<usize> = (0..100_000).collect();
+    ///     Template::stream("report", context! { rows })
+    /// }
+    /// ```
+    #[inline]
+    pub fn stream<S, C>(name: S, context: C) -> Template
+        where S: Into<Cow<'static, str>>, C: Serialize
+    {
+        Template { streamed: true, ..Template::render(name, context) }
+    }
+
     /// Render the template named `name` with the context `context` into a
     /// `String`. This method should **not** be used in any running Rocket
     /// application. This method should only be used during testing to validate
@@ -237,6 +379,57 @@ impl Template {
     #[inline(always)]
     pub(crate) fn finalize(self, ctxt: &Context) -> Result<(ContentType, String), Status> {
         let template = &*self.name;
+        let (info, value) = Template::prepare(ctxt, template, self.value)?;
+        let string = ctxt.engines.render(template, info, value).ok_or_else(|| {
+            error!(template, "template failed to render");
+            Status::InternalServerError
+        })?;
+
+        Ok((info.data_type.clone(), string))
+    }
+
+    /// Renders this template on a blocking thread, returning a response whose
+    /// body streams the rendered template. This method is called by the
+    /// `Template` `Responder` implementation for templates created with
+    /// `Template::stream()`.
+    ///
+    /// The template is rendered with the snapshot `ctxt` of the context, so
+    /// that templates reloaded meanwhile don't wait on, or affect, rendering.
+    pub(crate) fn finalize_streamed(self, ctxt: Arc<Context>) -> response::Result<'static> {
+        let Template { name, value, .. } = self;
+        let (info, value) = Template::prepare(&ctxt, &name, value)?;
+        let data_type = info.data_type.clone();
+
+        let (mut writer, reader) = stream::channel();
+        rocket::tokio::task::spawn_blocking(move || {
+            let template = &*name;
+            let rendered = ctxt.templates.get(template)
+                .map_or(false, |info| ctxt.engines.render_to(template, info, value, &mut writer));
+
+            if rendered && writer.flush().is_ok() {
+                return;
+            }
+
+            if writer.is_closed() {
+                debug!(template, "streamed template response was dropped");
+            } else {
+                error!(template, "template failed to render");
+                writer.fail("template failed to render");
+            }
+        });
+
+        Response::build()
+            .header(data_type)
+            .streamed_body(reader)
+            .ok()
+    }
+
+    /// Looks up the info for `template` in `ctxt` and unwraps its `value`.
+    fn prepare<'c>(
+        ctxt: &'c Context,
+        template: &str,
+        value: Result<Value, Error>,
+    ) -> Result<(&'c TemplateInfo, Value), Status> {
         let info = ctxt.templates.get(template).ok_or_else(|| {
             let ts: Vec<_> = ctxt.templates.keys().map(|s| s.as_str()).collect();
             error!(
@@ -247,23 +440,20 @@ impl Template {
             Status::InternalServerError
         })?;
 
-        let value = self.value.map_err(|e| {
+        let value = value.map_err(|e| {
             span_error!("templating", "template context failed to serialize" => e.trace_error());
             Status::InternalServerError
         })?;
 
-        let string = ctxt.engines.render(template, info, value).ok_or_else(|| {
-            error!(template, "template failed to render");
-            Status::InternalServerError
-        })?;
-
-        Ok((info.data_type.clone(), string))
+        Ok((info, value))
     }
 }
 
 /// Returns a response with the Content-Type derived from the template's
-/// extension and a fixed-size body containing the rendered template. If
-/// rendering fails, an `Err` of `Status::InternalServerError` is returned.
+/// extension and a fixed-size body containing the rendered template or, if the
+/// template was created with [`Template::stream()`], a streamed body into which
+/// the template is rendered. If rendering fails, an `Err` of
+/// `Status::InternalServerError` is returned.
 impl<'r> Responder<'r, 'static> for Template {
     fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
         let ctxt = req.rocket()
@@ -277,6 +467,10 @@ impl<'r> Responder<'r, 'static> for Template {
                 Status::InternalServerError
             })?;
 
+        if self.streamed {
+            return self.finalize_streamed(ctxt.snapshot());
+        }
+
         self.finalize(&ctxt.context())?.respond_to(req)
     }
 }
@@ -6,7 +6,7 @@ use rocket::{Rocket, Build};
 use rocket::config::Config;
 use rocket::figment::value::Value;
 use rocket::serde::{Serialize, Deserialize};
-use rocket_dyn_templates::{Template, Metadata, context};
+use rocket_dyn_templates::{Template, Metadata, EmbeddedTemplates, context};
 
 #[get("/<engine>/<name>")]
 fn template_check(md: Metadata<'_>, engine: &str, name: &str) -> Option<()> {
@@ -28,6 +28,12 @@ fn rocket() -> Rocket<Build> {
         .mount("/", routes![template_check, is_reloading])
 }
 
+fn embedded_rocket(templates: EmbeddedTemplates) -> Rocket<Build> {
+    rocket::build()
+        .attach(Template::embed(templates))
+        .mount("/", routes![template_check, is_reloading])
+}
+
 #[test]
 fn test_callback_error() {
     use rocket::{local::blocking::Client, error::ErrorKind::FailedFairings};
@@ -238,6 +244,86 @@ mod tera_tests {
         assert_eq!(md_rendered, Some((ContentType::HTML, ESCAPED_EXPECTED.into())));
     }
 
+    #[async_test]
+    async fn test_tera_streamed_templates() {
+        use rocket::local::asynchronous::Client;
+
+        #[get("/stream/<content>")]
+        fn stream(content: &str) -> Template {
+            let content = content.repeat(10_000);
+            Template::stream("tera/html_test", context! { title: "_test_", content })
+        }
+
+        #[get("/stream/missing")]
+        fn missing() -> Template {
+            Template::stream("tera/missing", context! {})
+        }
+
+        let rocket = rocket().mount("/", routes![stream, missing]);
+        let client = Client::debug(rocket).await.unwrap();
+        let content = "<hi> & bye";
+        let expected = Template::show(client.rocket(), "tera/html_test", context! {
+            title: "_test_",
+            content: content.repeat(10_000),
+        }).unwrap();
+
+        // Large enough to be streamed in many chunks.
+        assert!(expected.len() > 100_000);
+
+        let response = client.get(uri!(stream(content))).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::HTML));
+        assert_eq!(response.into_string().await, Some(expected));
+
+        let response = client.get("/stream/missing").dispatch().await;
+        assert_eq!(response.status(), Status::InternalServerError);
+    }
+
+    #[async_test]
+    async fn test_tera_embedded_templates() {
+        use rocket::local::asynchronous::Client;
+        use rocket_dyn_templates::{embed, EmbeddedTemplate};
+
+        // In debug builds, these are read from `tests/templates` on disk.
+        let embedded = embed!("tests/templates");
+
+        // The directory doesn't exist, so these are always read from the binary.
+        static TEMPLATES: &[EmbeddedTemplate] = &[
+            EmbeddedTemplate {
+                name: "tera/base",
+                path: "tera/base.txt.tera",
+                contents: include_str!("templates/tera/base.txt.tera"),
+            },
+            EmbeddedTemplate {
+                name: "tera/html_test",
+                path: "tera/html_test.html.tera",
+                contents: include_str!("templates/tera/html_test.html.tera"),
+            },
+            EmbeddedTemplate {
+                name: "tera/txt_test",
+                path: "tera/txt_test.txt.tera",
+                contents: include_str!("templates/tera/txt_test.txt.tera"),
+            },
+        ];
+
+        let compiled = EmbeddedTemplates::new("/rocket/missing/templates", TEMPLATES);
+        for templates in [embedded, compiled] {
+            let client = Client::debug(embedded_rocket(templates)).await.unwrap();
+            let req = client.get("/");
+            let metadata = Metadata::from_request(&req).await.unwrap();
+
+            let mut map = HashMap::new();
+            map.insert("title", "_test_");
+            map.insert("content", "<script />");
+
+            let md_rendered = metadata.render("tera/txt_test", &map);
+            assert_eq!(md_rendered, Some((ContentType::Text, UNESCAPED_EXPECTED.into())));
+
+            let md_rendered = metadata.render("tera/html_test", &map);
+            assert_eq!(md_rendered, Some((ContentType::HTML, ESCAPED_EXPECTED.into())));
+        }
+    }
+
     #[async_test]
     async fn test_globby_paths() {
         use rocket::local::asynchronous::Client;
@@ -313,6 +399,41 @@ mod handlebars_tests {
         assert_eq!(md_rendered, Some((ContentType::HTML, EXPECTED.into())));
     }
 
+    #[async_test]
+    async fn test_handlebars_streamed_templates() {
+        use rocket::local::asynchronous::Client;
+
+        #[get("/stream/<content>")]
+        fn stream(content: &str) -> Template {
+            let content = content.repeat(10_000);
+            Template::stream("hbs/test", context! { title: "_test_", content })
+        }
+
+        #[get("/stream/missing")]
+        fn missing() -> Template {
+            Template::stream("hbs/missing", context! {})
+        }
+
+        let rocket = rocket().mount("/", routes![stream, missing]);
+        let client = Client::debug(rocket).await.unwrap();
+        let content = "<hi> & bye";
+        let expected = Template::show(client.rocket(), "hbs/test", context! {
+            title: "_test_",
+            content: content.repeat(10_000),
+        }).unwrap();
+
+        // Large enough to be streamed in many chunks.
+        assert!(expected.len() > 100_000);
+
+        let response = client.get(uri!(stream(content))).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::HTML));
+        assert_eq!(response.into_string().await, Some(expected));
+
+        let response = client.get("/stream/missing").dispatch().await;
+        assert_eq!(response.status(), Status::InternalServerError);
+    }
+
     // u128 is not supported. enable when it is.
     // #[test]
     // fn test_handlebars_u128() {
@@ -383,9 +504,6 @@ mod handlebars_tests {
         write_file(&reload_path, NEW_TEXT);
 
         for _ in 0..6 {
-            // dispatch any request to trigger a template reload
-            client.get("/").dispatch();
-
             // if the new content is correct, we are done
             let new_rendered = Template::show(client.rocket(), RELOAD_TEMPLATE, ());
             if new_rendered == Some(NEW_TEXT.into()) {
@@ -399,6 +517,51 @@ mod handlebars_tests {
 
         panic!("failed to reload modified template in 1.5s");
     }
+
+    #[async_test]
+    #[cfg(debug_assertions)]
+    async fn test_live_reload() {
+        use std::time::Duration;
+
+        use rocket::local::asynchronous::Client;
+        use rocket::tokio::io::AsyncReadExt;
+        use rocket::tokio::time::timeout;
+
+        let live_path = template_root().join("hbs").join("live.txt.hbs");
+        std::fs::write(&live_path, "initial").expect("write file");
+
+        let rocket = rocket().mount("/live", Template::live_reload());
+        let client = Client::debug(rocket).await.unwrap();
+        if client.get("/is_reloading").dispatch().await.status() != Status::Ok {
+            return;
+        }
+
+        let script = client.get("/live/client.js").dispatch().await;
+        assert_eq!(script.content_type(), Some(ContentType::JavaScript));
+
+        let mut events = client.get("/live/events").dispatch().await;
+        assert_eq!(events.content_type(), Some(ContentType::EventStream));
+
+        // modify the template, then wait for the reload to be announced
+        std::fs::write(&live_path, "modified").expect("write file");
+        let announced = timeout(Duration::from_secs(2), async {
+            let mut stream = String::new();
+            let mut buf = [0; 256];
+            while !stream.contains("event:reload") {
+                let n = events.read(&mut buf).await.expect("read event stream");
+                if n == 0 {
+                    return false;
+                }
+
+                stream.push_str(std::str::from_utf8(&buf[..n]).unwrap());
+            }
+
+            true
+        }).await;
+
+        std::fs::write(&live_path, "initial").expect("write file");
+        assert_eq!(announced, Ok(true), "reload was not announced in 2s");
+    }
 }
 
 #[cfg(feature = "minijinja")]
@@ -439,6 +602,41 @@ mod j2_tests {
         assert_eq!(md_rendered, Some((ContentType::HTML, ESCAPED_EXPECTED.into())));
     }
 
+    #[async_test]
+    async fn test_j2_streamed_templates() {
+        use rocket::local::asynchronous::Client;
+
+        #[get("/stream/<content>")]
+        fn stream(content: &str) -> Template {
+            let content = content.repeat(10_000);
+            Template::stream("j2/html_test", context! { title: "_test_", content })
+        }
+
+        #[get("/stream/missing")]
+        fn missing() -> Template {
+            Template::stream("j2/missing", context! {})
+        }
+
+        let rocket = rocket().mount("/", routes![stream, missing]);
+        let client = Client::debug(rocket).await.unwrap();
+        let content = "<hi> & bye";
+        let expected = Template::show(client.rocket(), "j2/html_test", context! {
+            title: "_test_",
+            content: content.repeat(10_000),
+        }).unwrap();
+
+        // Large enough to be streamed in many chunks.
+        assert!(expected.len() > 100_000);
+
+        let response = client.get(uri!(stream(content))).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::HTML));
+        assert_eq!(response.into_string().await, Some(expected));
+
+        let response = client.get("/stream/missing").dispatch().await;
+        assert_eq!(response.status(), Status::InternalServerError);
+    }
+
     #[async_test]
     async fn test_globby_paths() {
         use rocket::local::asynchronous::Client;
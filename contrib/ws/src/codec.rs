@@ -0,0 +1,303 @@
+//! Typed, serialized [`Message`] streams.
+//!
+//! A [`DuplexStream`] is converted into a [`TypedStream`] via
+//! [`DuplexStream::json()`] or [`DuplexStream::msgpack()`], enabled by the
+//! `json` and `msgpack` crate features, respectively. A `TypedStream<T, C>`
+//! yields values of type `T` deserialized from incoming messages with the
+//! codec `C` and accepts any `Serialize` value to send.
+//!
+//! # Example
+//!
+//! ```rust
+//! # #[cfg(feature = "json")] mod example {
+//! # use rocket::get;
+//! # use rocket_ws as ws;
+//! use rocket::futures::{SinkExt, StreamExt};
+//! use rocket::serde::{Serialize, Deserialize};
+//!
+//! #[derive(Deserialize)]
+//! #[serde(crate = "rocket::serde")]
+//! struct Subscribe { topic: String }
+//!
+//! #[derive(Serialize)]
+//! #[serde(crate = "rocket::serde")]
+//! struct Subscribed<'a> { topic: &'a str, ok: bool }
+//!
+//! #[get("/updates")]
+//! fn updates(ws: ws::WebSocket) -> ws::Channel<'static> {
+//!     ws.channel(move |stream| Box::pin(async move {
+//!         let mut stream = stream.json::<Subscribe>();
+//!         while let Some(request) = stream.next().await {
+//!             // A malformed message has already closed the connection.
+//!             let Ok(request) = request else { break };
+//!             stream.send(Subscribed { topic: &request.topic, ok: true }).await?;
+//!         }
+//!
+//!         Ok(())
+//!     }))
+//! }
+//! # }
+//! ```
+
+use std::{fmt, io};
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use rocket::futures::{Sink, SinkExt, Stream, StreamExt};
+use rocket::serde::{Serialize, DeserializeOwned};
+
+use crate::frame::{CloseCode, CloseFrame};
+use crate::result::{Result, Error};
+use crate::stream::DuplexStream;
+use crate::Message;
+
+/// A [`DuplexStream`] of typed values serialized by the codec `C`.
+///
+/// See the [module docs](self) for details and an example.
+///
+/// # Malformed Messages
+///
+/// If an incoming message can't be deserialized into a `T`, the connection is
+/// closed with a close code of `1008` (policy violation), the stream yields a
+/// single `Err(DecodeError)`, and then the stream ends. Ping and pong messages
+/// are handled transparently and never yielded.
+pub struct TypedStream<T, C> {
+    stream: DuplexStream,
+    state: State,
+    _codec: PhantomData<fn() -> (T, C)>,
+}
+
+/// An error reading a value from a [`TypedStream`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DecodeError {
+    /// The connection failed.
+    Ws(Error),
+    /// The message was not of a kind the codec accepts, such as a text
+    /// message for a binary codec.
+    Unexpected(&'static str),
+    /// The message was not valid JSON for the expected type.
+    #[cfg(feature = "json")]
+    Json(rocket::serde::json::serde_json::Error),
+    /// The message was not valid MessagePack for the expected type.
+    #[cfg(feature = "msgpack")]
+    MsgPack(rocket::serde::msgpack::Error),
+}
+
+/// A serialization format for a [`TypedStream`].
+///
+/// This trait is sealed: it is implemented by [`Json`] and [`MsgPack`] only.
+pub trait Codec: private::Sealed + Send + 'static {
+    #[doc(hidden)]
+    fn encode<T: Serialize>(value: &T) -> Result<Message>;
+
+    #[doc(hidden)]
+    fn decode<T: DeserializeOwned>(message: &Message) -> Result<T, DecodeError>;
+}
+
+/// The JSON [`Codec`]. Values are sent as text messages. Both text and binary
+/// messages are accepted.
+#[cfg(feature = "json")]
+#[derive(Debug)]
+pub struct Json;
+
+/// The MessagePack [`Codec`]. Values are sent and accepted as binary messages.
+#[cfg(feature = "msgpack")]
+#[derive(Debug)]
+pub struct MsgPack;
+
+enum State {
+    Open,
+    Closing(Option<DecodeError>),
+    Flushing(Option<DecodeError>),
+    Closed,
+}
+
+mod private {
+    pub trait Sealed {}
+
+    #[cfg(feature = "json")]
+    impl Sealed for super::Json {}
+
+    #[cfg(feature = "msgpack")]
+    impl Sealed for super::MsgPack {}
+}
+
+fn encode_error<E: fmt::Display>(error: E) -> Error {
+    Error::Io(io::Error::new(io::ErrorKind::InvalidData, error.to_string()))
+}
+
+#[cfg(feature = "json")]
+impl Codec for Json {
+    fn encode<T: Serialize>(value: &T) -> Result<Message> {
+        rocket::serde::json::to_string(value)
+            .map(Message::Text)
+            .map_err(encode_error)
+    }
+
+    fn decode<T: DeserializeOwned>(message: &Message) -> Result<T, DecodeError> {
+        match message {
+            Message::Text(text) => rocket::serde::json::from_str(text).map_err(DecodeError::Json),
+            Message::Binary(data) => rocket::serde::json::from_slice(data).map_err(DecodeError::Json),
+            _ => Err(DecodeError::Unexpected("expected a text or binary message")),
+        }
+    }
+}
+
+#[cfg(feature = "msgpack")]
+impl Codec for MsgPack {
+    fn encode<T: Serialize>(value: &T) -> Result<Message> {
+        rocket::serde::msgpack::to_vec(value)
+            .map(Message::Binary)
+            .map_err(encode_error)
+    }
+
+    fn decode<T: DeserializeOwned>(message: &Message) -> Result<T, DecodeError> {
+        match message {
+            Message::Binary(data) => {
+                rocket::serde::msgpack::from_slice(data).map_err(DecodeError::MsgPack)
+            }
+            _ => Err(DecodeError::Unexpected("expected a binary message")),
+        }
+    }
+}
+
+impl<T, C: Codec> TypedStream<T, C> {
+    pub(crate) fn new(stream: DuplexStream) -> Self {
+        TypedStream { stream, state: State::Open, _codec: PhantomData }
+    }
+
+    /// Returns the underlying untyped stream.
+    pub fn into_inner(self) -> DuplexStream {
+        self.stream
+    }
+}
+
+impl DuplexStream {
+    /// Converts `self` into a stream of values of type `T` exchanged as JSON.
+    ///
+    /// See the [`codec`](crate::codec) module for an example.
+    #[cfg(feature = "json")]
+    pub fn json<T: DeserializeOwned>(self) -> TypedStream<T, Json> {
+        TypedStream::new(self)
+    }
+
+    /// Converts `self` into a stream of values of type `T` exchanged as
+    /// MessagePack.
+    ///
+    /// See the [`codec`](crate::codec) module for an example.
+    #[cfg(feature = "msgpack")]
+    pub fn msgpack<T: DeserializeOwned>(self) -> TypedStream<T, MsgPack> {
+        TypedStream::new(self)
+    }
+}
+
+impl<T: DeserializeOwned, C: Codec> Stream for TypedStream<T, C> {
+    type Item = Result<T, DecodeError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match &mut this.state {
+                State::Open => {
+                    let message = match ready!(this.stream.poll_next_unpin(cx)) {
+                        Some(Ok(message)) => message,
+                        Some(Err(e)) => {
+                            this.state = State::Closed;
+                            return Poll::Ready(Some(Err(DecodeError::Ws(e))));
+                        }
+                        None => {
+                            this.state = State::Closed;
+                            return Poll::Ready(None);
+                        }
+                    };
+
+                    match message {
+                        Message::Close(_) => {
+                            this.state = State::Closed;
+                            return Poll::Ready(None);
+                        }
+                        Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => continue,
+                        _ => match C::decode(&message) {
+                            Ok(value) => return Poll::Ready(Some(Ok(value))),
+                            Err(e) => this.state = State::Closing(Some(e)),
+                        }
+                    }
+                }
+                State::Closing(error) => {
+                    // Take the error only once ready: `poll_ready` may be pending.
+                    let ready = ready!(this.stream.poll_ready_unpin(cx));
+                    let error = error.take();
+                    if ready.is_ok() {
+                        let reason = "malformed message".into();
+                        let frame = CloseFrame { code: CloseCode::Policy, reason };
+                        let _ = this.stream.start_send_unpin(Message::Close(Some(frame)));
+                    }
+
+                    this.state = State::Flushing(error);
+                }
+                State::Flushing(error) => {
+                    let _ = ready!(this.stream.poll_flush_unpin(cx));
+                    let error = error.take();
+                    this.state = State::Closed;
+                    return Poll::Ready(error.map(Err));
+                }
+                State::Closed => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl<T, C: Codec, S: Serialize> Sink<S> for TypedStream<T, C> {
+    type Error = Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.get_mut().stream.poll_ready_unpin(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: S) -> Result<()> {
+        let message = C::encode(&item)?;
+        self.get_mut().stream.start_send_unpin(message)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.get_mut().stream.poll_flush_unpin(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.get_mut().stream.poll_close_unpin(cx)
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Ws(e) => write!(f, "websocket error: {e}"),
+            DecodeError::Unexpected(e) => write!(f, "unexpected message: {e}"),
+            #[cfg(feature = "json")]
+            DecodeError::Json(e) => write!(f, "invalid json: {e}"),
+            #[cfg(feature = "msgpack")]
+            DecodeError::MsgPack(e) => write!(f, "invalid msgpack: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DecodeError::Ws(e) => Some(e),
+            DecodeError::Unexpected(_) => None,
+            #[cfg(feature = "json")]
+            DecodeError::Json(e) => Some(e),
+            #[cfg(feature = "msgpack")]
+            DecodeError::MsgPack(e) => Some(e),
+        }
+    }
+}
+
+impl From<Error> for DecodeError {
+    fn from(error: Error) -> Self {
+        DecodeError::Ws(error)
+    }
+}
@@ -0,0 +1,123 @@
+use std::time::Duration;
+
+/// Configuration for [RFC 7692] permessage-deflate compression.
+///
+/// Enabled via [`WebSocket::deflate()`]. When enabled and the client offers
+/// the extension, messages are transparently compressed and decompressed. The
+/// defaults, obtained via [`Default::default()`], negotiate compression with
+/// the maximum window sizes and with context takeover, yielding the best
+/// compression ratio at the cost of memory per connection.
+///
+/// These settings are separate from [`Config`](crate::Config) as the latter is
+/// `tungstenite`'s own configuration, re-exported as is, which has no notion
+/// of compression: `tungstenite` rejects compressed frames, so compression is
+/// implemented beneath it.
+///
+/// # Example
+///
+/// ```rust
+/// # use rocket::get;
+/// # use rocket_ws as ws;
+/// #
+/// #[get("/dashboard")]
+/// fn dashboard(ws: ws::WebSocket) -> ws::Stream!['static] {
+///     let ws = ws.deflate(ws::DeflateConfig {
+///         // Don't retain compression state between messages.
+///         server_no_context_takeover: true,
+///         // Use a 4KiB compression window.
+///         server_max_window_bits: 12,
+///         ..Default::default()
+///     });
+///
+///     ws::Stream! { ws =>
+///         for await message in ws {
+///             yield message?;
+///         }
+///     }
+/// }
+/// ```
+///
+/// [`WebSocket::deflate()`]: crate::WebSocket::deflate()
+/// [RFC 7692]: https://datatracker.ietf.org/doc/html/rfc7692
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeflateConfig {
+    /// The compression level, from `0` (none) to `9` (best). Default: `6`.
+    pub level: u32,
+    /// Outgoing messages smaller than this many bytes are sent uncompressed.
+    /// Default: `128`.
+    pub threshold: usize,
+    /// Whether the server resets its compression context after each message.
+    /// This reduces memory usage at the cost of compression ratio. The server
+    /// always does so if the client requests it. Default: `false`.
+    pub server_no_context_takeover: bool,
+    /// Whether to ask the client to reset its compression context after each
+    /// message. Default: `false`.
+    pub client_no_context_takeover: bool,
+    /// The base-2 logarithm of the server's maximum compression window size,
+    /// between `9` and `15`. A lower value reduces memory usage. The client
+    /// may request a smaller value. Default: `15`.
+    pub server_max_window_bits: u8,
+    /// The base-2 logarithm of the client's maximum compression window size,
+    /// between `8` and `15`. If less than `15`, compression is only negotiated
+    /// with clients that support limiting their window. Default: `15`.
+    pub client_max_window_bits: u8,
+}
+
+impl Default for DeflateConfig {
+    fn default() -> Self {
+        DeflateConfig {
+            level: 6,
+            threshold: 128,
+            server_no_context_takeover: false,
+            client_no_context_takeover: false,
+            server_max_window_bits: 15,
+            client_max_window_bits: 15,
+        }
+    }
+}
+
+/// Configuration for heartbeats and idle timeouts.
+///
+/// Enabled via [`WebSocket::heartbeat()`]. The defaults, obtained via
+/// [`Default::default()`], send no pings and never close idle connections.
+///
+/// # Example
+///
+/// ```rust
+/// # use rocket::get;
+/// # use rocket_ws as ws;
+/// use std::time::Duration;
+///
+/// #[get("/echo")]
+/// fn echo_stream(ws: ws::WebSocket) -> ws::Stream!['static] {
+///     let ws = ws.heartbeat(ws::HeartbeatConfig {
+///         // Ping every 30 seconds; drop the client if no pong within 10.
+///         ping_interval: Some(Duration::from_secs(30)),
+///         pong_timeout: Some(Duration::from_secs(10)),
+///         // Close the connection after 5 minutes without a message.
+///         max_idle: Some(Duration::from_secs(300)),
+///     });
+///
+///     ws::Stream! { ws =>
+///         for await message in ws {
+///             yield message?;
+///         }
+///     }
+/// }
+/// ```
+///
+/// [`WebSocket::heartbeat()`]: crate::WebSocket::heartbeat()
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HeartbeatConfig {
+    /// How often to send a ping to the client. `None` disables pings.
+    /// Default: `None`.
+    pub ping_interval: Option<Duration>,
+    /// How long to wait for a pong after sending a ping before considering the
+    /// connection dead and dropping it. `None` means pongs are not awaited.
+    /// Only applies when `ping_interval` is set. Default: `None`.
+    pub pong_timeout: Option<Duration>,
+    /// How long the connection may go without sending or receiving a data
+    /// message before it is closed with a close code of `1000` (normal).
+    /// `None` means idle connections are never closed. Default: `None`.
+    pub max_idle: Option<Duration>,
+}
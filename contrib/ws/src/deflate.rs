@@ -0,0 +1,788 @@
+//! RFC 7692 permessage-deflate negotiation and framing.
+//!
+//! `tungstenite` rejects frames with reserved bits set, so compression is
+//! implemented beneath it: [`DeflateStream`] wraps the raw connection,
+//! decompressing incoming frames and compressing outgoing frames such that
+//! `tungstenite` only ever sees uncompressed frames. The server uses it, as
+//! does the [local](crate::local) client in the client role.
+
+use std::{fmt, io};
+use std::collections::HashSet;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
+use rocket::data::IoStream;
+use rocket::tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::{Config, DeflateConfig};
+use crate::tungstenite::protocol::Role;
+
+/// The extension token for permessage-deflate.
+const EXTENSION: &str = "permessage-deflate";
+
+/// The empty stored block removed from the end of each compressed message by
+/// the sender and appended by the receiver.
+const TRAILER: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+/// The number of pending outgoing bytes after which writes apply backpressure.
+const WRITE_HIGH_WATER: usize = 128 * 1024;
+
+/// A permessage-deflate offer from the client's `Sec-WebSocket-Extensions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Offer(Vec<(String, Option<String>)>);
+
+/// Negotiated permessage-deflate parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Params {
+    level: u32,
+    threshold: usize,
+    server_no_context_takeover: bool,
+    client_no_context_takeover: bool,
+    server_max_window_bits: Option<u8>,
+    client_max_window_bits: Option<u8>,
+}
+
+/// Parses the permessage-deflate offers in `Sec-WebSocket-Extensions` header
+/// values, in the client's order of preference.
+pub(crate) fn offers<'a, I: Iterator<Item = &'a str>>(headers: I) -> Vec<Offer> {
+    headers.flat_map(|h| h.split(','))
+        .filter_map(|extension| {
+            let mut parts = extension.split(';').map(|p| p.trim());
+            if !parts.next()?.eq_ignore_ascii_case(EXTENSION) {
+                return None;
+            }
+
+            let params = parts.filter(|p| !p.is_empty())
+                .map(|p| match p.split_once('=') {
+                    Some((k, v)) => {
+                        let value = v.trim().trim_matches('"').to_string();
+                        (k.trim().to_ascii_lowercase(), Some(value))
+                    },
+                    None => (p.to_ascii_lowercase(), None),
+                })
+                .collect();
+
+            Some(Offer(params))
+        })
+        .collect()
+}
+
+/// Returns the parameters the server accepted in the `Sec-WebSocket-Extensions`
+/// header values of its handshake response, if it accepted permessage-deflate.
+/// Messages are compressed as per [`DeflateConfig::default()`].
+pub(crate) fn accepted<'a, I: Iterator<Item = &'a str>>(headers: I) -> Option<Params> {
+    let config = DeflateConfig::default();
+    let mut params = Params {
+        level: config.level,
+        threshold: config.threshold,
+        server_no_context_takeover: false,
+        client_no_context_takeover: false,
+        server_max_window_bits: None,
+        client_max_window_bits: None,
+    };
+
+    let offer = offers(headers).into_iter().next()?;
+    for (name, value) in offer.0 {
+        match (name.as_str(), value.as_deref()) {
+            ("server_no_context_takeover", None) => params.server_no_context_takeover = true,
+            ("client_no_context_takeover", None) => params.client_no_context_takeover = true,
+            ("server_max_window_bits", Some(bits)) => {
+                params.server_max_window_bits = Some(window_bits(bits)?);
+            }
+            ("client_max_window_bits", Some(bits)) => {
+                params.client_max_window_bits = Some(window_bits(bits)?);
+            }
+            _ => return None,
+        }
+    }
+
+    Some(params)
+}
+
+/// Returns the parameters for the first offer in `offers` that is acceptable
+/// under `config`, if any.
+pub(crate) fn negotiate(config: &DeflateConfig, offers: &[Offer]) -> Option<Params> {
+    offers.iter().find_map(|offer| accept(config, offer))
+}
+
+fn accept(config: &DeflateConfig, offer: &Offer) -> Option<Params> {
+    let server_bits = config.server_max_window_bits.clamp(9, 15);
+    let client_bits = config.client_max_window_bits.clamp(8, 15);
+    let mut params = Params {
+        level: config.level.min(9),
+        threshold: config.threshold,
+        server_no_context_takeover: config.server_no_context_takeover,
+        client_no_context_takeover: config.client_no_context_takeover,
+        server_max_window_bits: None,
+        client_max_window_bits: None,
+    };
+
+    let mut seen = HashSet::new();
+    let mut client_bits_offered = false;
+    for (name, value) in &offer.0 {
+        if !seen.insert(name.as_str()) {
+            return None;
+        }
+
+        match (name.as_str(), value.as_deref()) {
+            ("server_no_context_takeover", None) => params.server_no_context_takeover = true,
+            ("client_no_context_takeover", None) => params.client_no_context_takeover = true,
+            ("server_max_window_bits", Some(bits)) => {
+                // Raw deflate can't compress with a window smaller than 2^9.
+                let bits = window_bits(bits)?.min(server_bits);
+                if bits < 9 {
+                    return None;
+                }
+
+                params.server_max_window_bits = Some(bits);
+            }
+            ("client_max_window_bits", None) => client_bits_offered = true,
+            ("client_max_window_bits", Some(bits)) => {
+                window_bits(bits)?;
+                client_bits_offered = true;
+            }
+            _ => return None,
+        }
+    }
+
+    if params.server_max_window_bits.is_none() && server_bits < 15 {
+        params.server_max_window_bits = Some(server_bits);
+    }
+
+    // The client can only be limited if it indicated that it supports it.
+    if client_bits < 15 {
+        if !client_bits_offered {
+            return None;
+        }
+
+        params.client_max_window_bits = Some(client_bits);
+    }
+
+    Some(params)
+}
+
+fn window_bits(value: &str) -> Option<u8> {
+    let bits = value.parse::<u8>().ok()?;
+    (value.bytes().all(|b| b.is_ascii_digit()) && (8..=15).contains(&bits)).then_some(bits)
+}
+
+impl fmt::Display for Params {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(EXTENSION)?;
+        if self.server_no_context_takeover {
+            f.write_str("; server_no_context_takeover")?;
+        }
+
+        if self.client_no_context_takeover {
+            f.write_str("; client_no_context_takeover")?;
+        }
+
+        if let Some(bits) = self.server_max_window_bits {
+            write!(f, "; server_max_window_bits={bits}")?;
+        }
+
+        if let Some(bits) = self.client_max_window_bits {
+            write!(f, "; client_max_window_bits={bits}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The raw connection, compressing and decompressing messages if
+/// permessage-deflate was negotiated and passing bytes through otherwise.
+pub(crate) struct DeflateStream<I = IoStream> {
+    io: I,
+    codec: Option<Box<Codec>>,
+}
+
+struct Codec {
+    inbound: Inbound,
+    outbound: Outbound,
+}
+
+/// Decompresses frames from the peer.
+struct Inbound {
+    decompress: Decompress,
+    /// Whether frames are masked: the peer is the client.
+    masked: bool,
+    max_frame_size: usize,
+    max_message_size: usize,
+    /// Bytes read from the connection not yet forming a complete frame.
+    raw: Vec<u8>,
+    /// Rewritten frames ready to be read by `tungstenite`.
+    ready: Vec<u8>,
+    pos: usize,
+    message: Option<Incoming>,
+}
+
+/// The state of the current incoming data message.
+struct Incoming {
+    compressed: bool,
+    /// The message's opcode if a frame for it has yet to be emitted.
+    opcode: Option<u8>,
+    size: usize,
+}
+
+/// Compresses frames to the peer.
+struct Outbound {
+    compress: Compress,
+    /// Whether frames are masked: the peer is the server.
+    masked: bool,
+    threshold: usize,
+    reset: bool,
+    /// Bytes written by `tungstenite` not yet forming a complete frame.
+    raw: Vec<u8>,
+    /// Rewritten frames ready to be written to the connection.
+    ready: Vec<u8>,
+    pos: usize,
+    /// Whether the current outgoing data message is compressed.
+    compressed: Option<bool>,
+}
+
+/// A parsed frame header.
+struct Header {
+    fin: bool,
+    rsv1: bool,
+    opcode: u8,
+    mask: Option<[u8; 4]>,
+    len: usize,
+}
+
+impl<I> DeflateStream<I> {
+    pub(crate) fn new(io: I, config: &Config, params: Option<Params>, role: Role) -> Self {
+        let codec = params.map(|params| Box::new(Codec {
+            inbound: Inbound {
+                // The peer's window is never larger than 2^15.
+                decompress: Decompress::new(false),
+                masked: role == Role::Server,
+                max_frame_size: config.max_frame_size.unwrap_or(usize::MAX),
+                max_message_size: config.max_message_size.unwrap_or(usize::MAX),
+                raw: vec![],
+                ready: vec![],
+                pos: 0,
+                message: None,
+            },
+            outbound: Outbound {
+                // Raw deflate can't compress with a window smaller than 2^9.
+                compress: Compress::new_with_window_bits(
+                    Compression::new(params.level),
+                    false,
+                    params.max_window_bits(role).unwrap_or(15).max(9),
+                ),
+                masked: role == Role::Client,
+                threshold: params.threshold,
+                reset: params.no_context_takeover(role),
+                raw: vec![],
+                ready: vec![],
+                pos: 0,
+                compressed: None,
+            },
+        }));
+
+        DeflateStream { io, codec }
+    }
+
+    pub(crate) fn get_ref(&self) -> &I {
+        &self.io
+    }
+}
+
+impl Params {
+    /// The maximum window size of the endpoint in `role`.
+    fn max_window_bits(&self, role: Role) -> Option<u8> {
+        match role {
+            Role::Server => self.server_max_window_bits,
+            Role::Client => self.client_max_window_bits,
+        }
+    }
+
+    /// Whether the endpoint in `role` resets its context after each message.
+    fn no_context_takeover(&self, role: Role) -> bool {
+        match role {
+            Role::Server => self.server_no_context_takeover,
+            Role::Client => self.client_no_context_takeover,
+        }
+    }
+}
+
+/// Parses the frame header at the start of `buf`, returning the header and
+/// its length or `None` if `buf` doesn't contain a complete header.
+fn parse_header(buf: &[u8]) -> io::Result<Option<(Header, usize)>> {
+    let [b0, b1, rest @ ..] = buf else {
+        return Ok(None);
+    };
+
+    let (len, mut offset) = match (b1 & 0x7f, rest) {
+        (126, [a, b, ..]) => (u16::from_be_bytes([*a, *b]) as u64, 4),
+        (127, [a, b, c, d, e, f, g, h, ..]) => {
+            (u64::from_be_bytes([*a, *b, *c, *d, *e, *f, *g, *h]), 10)
+        }
+        (126 | 127, _) => return Ok(None),
+        (n, _) => (n as u64, 2),
+    };
+
+    let mask = match b1 & 0x80 != 0 {
+        true => match buf.get(offset..offset + 4) {
+            Some(key) => {
+                offset += 4;
+                Some([key[0], key[1], key[2], key[3]])
+            }
+            None => return Ok(None),
+        },
+        false => None,
+    };
+
+    let len = usize::try_from(len).map_err(|_| invalid("frame length exceeds address space"))?;
+    let header = Header {
+        fin: b0 & 0x80 != 0,
+        rsv1: b0 & 0x40 != 0,
+        opcode: b0 & 0x0f,
+        mask,
+        len,
+    };
+
+    Ok(Some((header, offset)))
+}
+
+/// Appends a frame to `out`. If `masked`, the frame is masked with an all-zero
+/// key, which leaves the payload unchanged.
+fn write_frame(out: &mut Vec<u8>, fin: bool, rsv1: bool, opcode: u8, masked: bool, payload: &[u8]) {
+    out.push((fin as u8) << 7 | (rsv1 as u8) << 6 | opcode);
+    let mask_bit = (masked as u8) << 7;
+    match payload.len() {
+        n if n < 126 => out.push(mask_bit | n as u8),
+        n if n <= u16::MAX as usize => {
+            out.push(mask_bit | 126);
+            out.extend_from_slice(&(n as u16).to_be_bytes());
+        }
+        n => {
+            out.push(mask_bit | 127);
+            out.extend_from_slice(&(n as u64).to_be_bytes());
+        }
+    }
+
+    if masked {
+        out.extend_from_slice(&[0; 4]);
+    }
+
+    out.extend_from_slice(payload);
+}
+
+fn invalid<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+impl Inbound {
+    /// Rewrites the next complete frame in `raw` into `ready`. Returns `false`
+    /// if `raw` doesn't contain a complete frame.
+    fn decode(&mut self) -> io::Result<bool> {
+        let Some((header, header_len)) = parse_header(&self.raw)? else {
+            return Ok(false);
+        };
+
+        if header.len > self.max_frame_size {
+            return Err(invalid("frame exceeds the maximum frame size"));
+        }
+
+        let end = header_len.saturating_add(header.len);
+        if self.raw.len() < end {
+            return Ok(false);
+        }
+
+        let frame: Vec<u8> = self.raw.drain(..end).collect();
+
+        // Control frames are never compressed. Wrongly (un)masked frames and
+        // reserved bits in continuation frames are left to `tungstenite` to
+        // reject.
+        let (is_control, is_continuation) = (header.opcode & 0x08 != 0, header.opcode == 0);
+        let misframed = header.mask.is_some() != self.masked;
+        if is_control || misframed || (is_continuation && header.rsv1) {
+            self.ready.extend_from_slice(&frame);
+            return Ok(true);
+        }
+
+        if !is_continuation {
+            self.message = Some(Incoming {
+                compressed: header.rsv1,
+                opcode: Some(header.opcode),
+                size: 0,
+            });
+        }
+
+        if !self.message.as_ref().is_some_and(|m| m.compressed) {
+            self.ready.extend_from_slice(&frame);
+            if header.fin {
+                self.message = None;
+            }
+
+            return Ok(true);
+        }
+
+        let Some(message) = self.message.as_mut() else {
+            return Ok(true);
+        };
+
+        let mut payload = frame[header_len..].to_vec();
+        if let Some(key) = header.mask {
+            payload.iter_mut().enumerate().for_each(|(i, b)| *b ^= key[i % 4]);
+        }
+
+        if header.fin {
+            payload.extend_from_slice(&TRAILER);
+        }
+
+        let mut inflated = Vec::new();
+        let limit = self.max_message_size.saturating_sub(message.size);
+        inflate(&mut self.decompress, &payload, &mut inflated, limit)?;
+        message.size += inflated.len();
+
+        let mut chunks = inflated.chunks(self.max_frame_size.clamp(1, 1 << 20)).peekable();
+        if chunks.peek().is_none() && header.fin {
+            let opcode = message.opcode.take().unwrap_or(0);
+            write_frame(&mut self.ready, true, false, opcode, self.masked, &[]);
+        }
+
+        while let Some(chunk) = chunks.next() {
+            let fin = header.fin && chunks.peek().is_none();
+            let opcode = message.opcode.take().unwrap_or(0);
+            write_frame(&mut self.ready, fin, false, opcode, self.masked, chunk);
+        }
+
+        if header.fin {
+            self.message = None;
+        }
+
+        Ok(true)
+    }
+}
+
+/// Decompresses all of `input` into `out`, failing if more than `limit` bytes
+/// would be produced.
+fn inflate(
+    decompress: &mut Decompress,
+    mut input: &[u8],
+    out: &mut Vec<u8>,
+    limit: usize,
+) -> io::Result<()> {
+    loop {
+        out.reserve(input.len().clamp(4096, 64 * 1024));
+        let (before_in, before_out) = (decompress.total_in(), out.len());
+        let status = decompress.decompress_vec(input, out, FlushDecompress::Sync)
+            .map_err(invalid)?;
+
+        let consumed = (decompress.total_in() - before_in) as usize;
+        input = &input[consumed..];
+        if out.len() > limit {
+            return Err(invalid("message exceeds the maximum message size"));
+        }
+
+        // The client ended the deflate stream: the next message starts anew.
+        if status == Status::StreamEnd {
+            decompress.reset(false);
+            return Ok(());
+        }
+
+        if input.is_empty() && out.len() < out.capacity() {
+            return Ok(());
+        }
+
+        if consumed == 0 && out.len() == before_out {
+            return Err(invalid("compressed message is truncated or corrupt"));
+        }
+    }
+}
+
+/// Compresses all of `input` into `out`.
+fn deflate(
+    compress: &mut Compress,
+    mut input: &[u8],
+    out: &mut Vec<u8>,
+    flush: FlushCompress,
+) -> io::Result<()> {
+    loop {
+        out.reserve((input.len() / 2).clamp(4096, 64 * 1024));
+        let (before_in, before_out) = (compress.total_in(), out.len());
+        compress.compress_vec(input, out, flush).map_err(invalid)?;
+        let consumed = (compress.total_in() - before_in) as usize;
+        input = &input[consumed..];
+        if input.is_empty() && out.len() < out.capacity() {
+            return Ok(());
+        }
+
+        if consumed == 0 && out.len() == before_out {
+            return Err(io::Error::new(io::ErrorKind::Other, "compression made no progress"));
+        }
+    }
+}
+
+impl Outbound {
+    /// Rewrites the next complete frame in `raw` into `ready`. Returns `false`
+    /// if `raw` doesn't contain a complete frame.
+    fn encode(&mut self) -> io::Result<bool> {
+        let Some((header, header_len)) = parse_header(&self.raw)? else {
+            return Ok(false);
+        };
+
+        let end = header_len.saturating_add(header.len);
+        if self.raw.len() < end {
+            return Ok(false);
+        }
+
+        let mut frame: Vec<u8> = self.raw.drain(..end).collect();
+        if header.opcode & 0x08 != 0 || header.mask.is_some() != self.masked {
+            self.ready.extend_from_slice(&frame);
+            return Ok(true);
+        }
+
+        let is_first = header.opcode != 0;
+        if is_first {
+            let large = !header.fin || header.len >= self.threshold;
+            self.compressed = Some(!header.rsv1 && large);
+        }
+
+        let compressed = self.compressed.unwrap_or(false);
+        if header.fin {
+            self.compressed = None;
+        }
+
+        if !compressed {
+            self.ready.extend_from_slice(&frame);
+            return Ok(true);
+        }
+
+        if let Some(key) = header.mask {
+            let payload = &mut frame[header_len..];
+            payload.iter_mut().enumerate().for_each(|(i, b)| *b ^= key[i % 4]);
+        }
+
+        let flush = if header.fin { FlushCompress::Sync } else { FlushCompress::None };
+        let mut deflated = Vec::with_capacity(header.len / 2 + 64);
+        deflate(&mut self.compress, &frame[header_len..], &mut deflated, flush)?;
+        if header.fin {
+            if deflated.ends_with(&TRAILER) {
+                deflated.truncate(deflated.len() - TRAILER.len());
+            }
+
+            if self.reset {
+                self.compress.reset();
+            }
+        }
+
+        let (fin, opcode) = (header.fin, header.opcode);
+        write_frame(&mut self.ready, fin, is_first, opcode, self.masked, &deflated);
+        Ok(true)
+    }
+
+    /// Writes all of `ready` to `io`.
+    fn poll_drain<I>(&mut self, io: &mut I, cx: &mut Context<'_>) -> Poll<io::Result<()>>
+        where I: AsyncWrite + Unpin
+    {
+        while self.pos < self.ready.len() {
+            let n = ready!(Pin::new(&mut *io).poll_write(cx, &self.ready[self.pos..]))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+
+            self.pos += n;
+        }
+
+        self.ready.clear();
+        self.pos = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<I: AsyncRead + Unpin> AsyncRead for DeflateStream<I> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let Some(codec) = this.codec.as_mut() else {
+            return Pin::new(&mut this.io).poll_read(cx, buf);
+        };
+
+        let inbound = &mut codec.inbound;
+        loop {
+            if inbound.pos < inbound.ready.len() {
+                let n = buf.remaining().min(inbound.ready.len() - inbound.pos);
+                buf.put_slice(&inbound.ready[inbound.pos..inbound.pos + n]);
+                inbound.pos += n;
+                if inbound.pos == inbound.ready.len() {
+                    inbound.ready.clear();
+                    inbound.pos = 0;
+                }
+
+                return Poll::Ready(Ok(()));
+            }
+
+            if inbound.decode()? {
+                continue;
+            }
+
+            let mut chunk = [0u8; 8192];
+            let mut chunk_buf = ReadBuf::new(&mut chunk);
+            ready!(Pin::new(&mut this.io).poll_read(cx, &mut chunk_buf))?;
+            if chunk_buf.filled().is_empty() {
+                return Poll::Ready(Ok(()));
+            }
+
+            inbound.raw.extend_from_slice(chunk_buf.filled());
+        }
+    }
+}
+
+impl<I: AsyncWrite + Unpin> AsyncWrite for DeflateStream<I> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        data: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let Some(codec) = this.codec.as_mut() else {
+            return Pin::new(&mut this.io).poll_write(cx, data);
+        };
+
+        let outbound = &mut codec.outbound;
+        if outbound.ready.len() - outbound.pos >= WRITE_HIGH_WATER {
+            ready!(outbound.poll_drain(&mut this.io, cx))?;
+        }
+
+        outbound.raw.extend_from_slice(data);
+        while outbound.encode()? {}
+        if let Poll::Ready(Err(e)) = outbound.poll_drain(&mut this.io, cx) {
+            return Poll::Ready(Err(e));
+        }
+
+        Poll::Ready(Ok(data.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if let Some(codec) = this.codec.as_mut() {
+            ready!(codec.outbound.poll_drain(&mut this.io, cx))?;
+        }
+
+        Pin::new(&mut this.io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if let Some(codec) = this.codec.as_mut() {
+            ready!(codec.outbound.poll_drain(&mut this.io, cx))?;
+        }
+
+        Pin::new(&mut this.io).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn negotiate_header(config: DeflateConfig, header: &str) -> Option<String> {
+        negotiate(&config, &offers([header].into_iter())).map(|p| p.to_string())
+    }
+
+    #[test]
+    fn test_negotiation() {
+        let config = DeflateConfig::default();
+        let header = negotiate_header(config, "permessage-deflate; client_max_window_bits");
+        assert_eq!(header.unwrap(), "permessage-deflate");
+
+        let header = negotiate_header(config, "x-webkit-deflate-frame, permessage-deflate");
+        assert_eq!(header.unwrap(), "permessage-deflate");
+
+        let header = negotiate_header(config, "permessage-deflate; server_max_window_bits=10");
+        assert_eq!(header.unwrap(), "permessage-deflate; server_max_window_bits=10");
+
+        let header = negotiate_header(config, "permessage-deflate; server_max_window_bits=8");
+        assert!(header.is_none());
+
+        let header = negotiate_header(config, "permessage-deflate; server_max_window_bits=8, \
+            permessage-deflate; server_no_context_takeover");
+        assert_eq!(header.unwrap(), "permessage-deflate; server_no_context_takeover");
+
+        let header = negotiate_header(config, "permessage-deflate; unknown");
+        assert!(header.is_none());
+
+        let header = negotiate_header(config, "permessage-deflate; \
+            server_no_context_takeover; server_no_context_takeover");
+        assert!(header.is_none());
+
+        let config = DeflateConfig {
+            client_no_context_takeover: true,
+            server_max_window_bits: 12,
+            client_max_window_bits: 10,
+            ..config
+        };
+
+        assert!(negotiate_header(config, "permessage-deflate").is_none());
+        let header = negotiate_header(config, "permessage-deflate; client_max_window_bits=\"15\"");
+        assert_eq!(header.unwrap(), "permessage-deflate; client_no_context_takeover; \
+            server_max_window_bits=12; client_max_window_bits=10");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let offers = offers(["permessage-deflate"].into_iter());
+        let params = negotiate(&DeflateConfig::default(), &offers).unwrap();
+        let mut outbound = Outbound {
+            compress: Compress::new(Compression::new(params.level), false),
+            masked: false,
+            threshold: params.threshold,
+            reset: false,
+            raw: vec![],
+            ready: vec![],
+            pos: 0,
+            compressed: None,
+        };
+
+        let mut inbound = Inbound {
+            decompress: Decompress::new(false),
+            masked: true,
+            max_frame_size: usize::MAX,
+            max_message_size: usize::MAX,
+            raw: vec![],
+            ready: vec![],
+            pos: 0,
+            message: None,
+        };
+
+        let message = "hello, compressed world! ".repeat(100);
+        for _ in 0..2 {
+            write_frame(&mut outbound.raw, true, false, 0x1, false, message.as_bytes());
+            assert!(outbound.encode().unwrap());
+
+            let (header, len) = parse_header(&outbound.ready).unwrap().unwrap();
+            assert!(header.rsv1 && header.fin && header.len < message.len());
+
+            // Mask the frame as a client would.
+            let payload = outbound.ready[len..].to_vec();
+            outbound.ready.clear();
+            let key = [1, 2, 3, 4];
+            let masked: Vec<u8> = payload.iter().enumerate().map(|(i, b)| b ^ key[i % 4]).collect();
+            inbound.raw.push(0x80 | 0x40 | 0x1);
+            inbound.raw.push(0x80 | 126);
+            inbound.raw.extend_from_slice(&(masked.len() as u16).to_be_bytes());
+            inbound.raw.extend_from_slice(&key);
+            inbound.raw.extend_from_slice(&masked);
+
+            assert!(inbound.decode().unwrap());
+            let (header, len) = parse_header(&inbound.ready).unwrap().unwrap();
+            assert!(!header.rsv1 && header.fin && header.opcode == 0x1);
+            assert_eq!(header.mask, Some([0; 4]));
+            assert_eq!(&inbound.ready[len..], message.as_bytes());
+            inbound.ready.clear();
+        }
+
+        // Small messages are sent uncompressed.
+        write_frame(&mut outbound.raw, true, false, 0x1, false, b"hi");
+        assert!(outbound.encode().unwrap());
+        let (header, _) = parse_header(&outbound.ready).unwrap().unwrap();
+        assert!(!header.rsv1);
+    }
+}
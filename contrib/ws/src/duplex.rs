@@ -1,11 +1,21 @@
+use std::io;
+use std::collections::VecDeque;
+use std::future::{poll_fn, Future};
 use std::pin::Pin;
-use std::task::{Context, Poll};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::time::Duration;
 
+use rocket::Shutdown;
 use rocket::data::IoStream;
-use rocket::futures::{StreamExt, SinkExt, Sink};
+use rocket::futures::{FutureExt, StreamExt, SinkExt, Sink};
 use rocket::futures::stream::{Stream, FusedStream};
+use rocket::futures::task::AtomicWaker;
+use rocket::tokio::time::{sleep, Instant, Sleep};
 
-use crate::frame::{Message, CloseFrame};
+use crate::{Config, HeartbeatConfig};
+use crate::deflate::{DeflateStream, Params};
+use crate::frame::{Message, CloseFrame, CloseCode};
 use crate::result::{Result, Error};
 
 /// A readable and writeable WebSocket [`Message`] `async` stream.
@@ -31,22 +41,262 @@ use crate::result::{Result, Error};
 /// }
 /// ```
 ///
+/// ## Heartbeats and Shutdown
+///
+/// For as long as the connection is open, whether or not the stream is being
+/// read from or written to, Rocket sends pings, enforces pong deadlines, and
+/// closes idle connections as configured via [`WebSocket::heartbeat()`]. To do
+/// so, incoming messages are read ahead of the handler: up to 16 messages
+/// other than pings and pongs, which are discarded, oldest first, if the
+/// handler falls behind. When Rocket
+/// begins a graceful shutdown, a `1001` (going away) close frame is sent to
+/// the client, and the connection ends once the client acknowledges the close.
+///
+/// [`WebSocket::heartbeat()`]: crate::WebSocket::heartbeat()
 /// [`StreamExt`]: rocket::futures::StreamExt
 /// [`SinkExt`]: rocket::futures::SinkExt
-pub struct DuplexStream(tokio_tungstenite::WebSocketStream<IoStream>);
+pub struct DuplexStream {
+    shared: Arc<Mutex<Shared>>,
+    terminated: bool,
+}
+
+/// The number of incoming messages read ahead of the handler.
+const READ_AHEAD: usize = 16;
+
+/// State shared by a [`DuplexStream`] and its heartbeat.
+struct Shared {
+    inner: tokio_tungstenite::WebSocketStream<DeflateStream>,
+    heartbeat: Heartbeat,
+    /// Messages read from `inner` but not yet by the handler.
+    incoming: VecDeque<Result<Message>>,
+    /// Whether `inner` has ended.
+    ended: bool,
+    wakers: Arc<Wakers>,
+    /// Wakes both the handler and heartbeat; used to poll `inner`.
+    waker: Waker,
+}
+
+/// Connection liveness state: timers, shutdown, and a pending control message.
+struct Heartbeat {
+    shutdown: Shutdown,
+    closing: bool,
+    ping: Option<(Duration, Pin<Box<Sleep>>)>,
+    pong_timeout: Option<Duration>,
+    pong_deadline: Option<Pin<Box<Sleep>>>,
+    idle: Option<(Duration, Pin<Box<Sleep>>)>,
+    pending: Option<Message>,
+    flushing: bool,
+}
+
+/// The tasks waiting on `inner`, which may be different tasks.
+#[derive(Default)]
+struct Wakers {
+    handler: AtomicWaker,
+    heartbeat: AtomicWaker,
+}
+
+impl Wake for Wakers {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.handler.wake();
+        self.heartbeat.wake();
+    }
+}
 
 impl DuplexStream {
-    pub(crate) async fn new(stream: IoStream, config: crate::Config) -> Self {
+    pub(crate) async fn new(
+        stream: IoStream,
+        config: Config,
+        heartbeat: HeartbeatConfig,
+        deflate: Option<Params>,
+        shutdown: Shutdown,
+    ) -> Self {
         use tokio_tungstenite::WebSocketStream;
         use crate::tungstenite::protocol::Role;
 
+        let stream = DeflateStream::new(stream, &config, deflate, Role::Server);
         let inner = WebSocketStream::from_raw_socket(stream, Role::Server, Some(config));
-        DuplexStream(inner.await)
+        let timer = |d: Duration| (d, Box::pin(sleep(d)));
+        let heartbeat = Heartbeat {
+            shutdown,
+            closing: false,
+            ping: heartbeat.ping_interval.map(timer),
+            pong_timeout: heartbeat.pong_timeout,
+            pong_deadline: None,
+            idle: heartbeat.max_idle.map(timer),
+            pending: None,
+            flushing: false,
+        };
+
+        let wakers = Arc::new(Wakers::default());
+        let shared = Shared {
+            inner: inner.await,
+            heartbeat,
+            incoming: VecDeque::new(),
+            ended: false,
+            waker: Waker::from(wakers.clone()),
+            wakers,
+        };
+
+        DuplexStream { shared: Arc::new(Mutex::new(shared)), terminated: false }
     }
 
     /// Close the stream now. This does not typically need to be called.
     pub async fn close(&mut self, msg: Option<CloseFrame<'_>>) -> Result<()> {
-        self.0.close(msg).await
+        self.send(Message::Close(msg.map(|msg| msg.into_owned()))).await
+    }
+
+    /// Returns a future that drives the connection's heartbeat independently
+    /// of the handler. It resolves with an error if a pong deadline is missed
+    /// and successfully once a close initiated by the server completes. It
+    /// never resolves otherwise.
+    pub(crate) fn heartbeat(&self) -> impl Future<Output = Result<()>> + Send + 'static {
+        let shared = self.shared.clone();
+        poll_fn(move |cx| shared.lock().expect("stream lock").poll_heartbeat(cx))
+    }
+
+    /// Runs `f` on the shared state with `cx`'s waker registered as the
+    /// handler's and a context that wakes both the handler and heartbeat.
+    fn lock_with<T, F>(&self, cx: &mut Context<'_>, f: F) -> T
+        where F: FnOnce(&mut Shared, &mut Context<'_>) -> T
+    {
+        let mut shared = self.shared.lock().expect("stream lock");
+        shared.wakers.handler.register(cx.waker());
+        let waker = shared.waker.clone();
+        f(&mut shared, &mut Context::from_waker(&waker))
+    }
+}
+
+impl Shared {
+    /// Reads messages from `inner` into `incoming` until `inner` is pending or
+    /// has ended, or `READ_AHEAD` messages other than pings and pongs are
+    /// waiting. To make room, pings and pongs, which have already been
+    /// answered or processed, are discarded, oldest first. Returns `true` if
+    /// any message was read.
+    fn read_ahead(&mut self) -> bool {
+        let waker = self.waker.clone();
+        let mut cx = Context::from_waker(&waker);
+        let mut read = false;
+        while !self.ended {
+            if self.incoming.len() >= READ_AHEAD {
+                let control = self.incoming.iter()
+                    .position(|m| matches!(m, Ok(m) if m.is_ping() || m.is_pong()));
+
+                match control {
+                    Some(i) => drop(self.incoming.remove(i)),
+                    None => break,
+                }
+            }
+
+            match self.inner.poll_next_unpin(&mut cx) {
+                Poll::Ready(Some(Ok(message))) => {
+                    if message.is_pong() {
+                        self.heartbeat.pong_deadline = None;
+                    }
+
+                    self.touch(&message);
+                    self.incoming.push_back(Ok(message));
+                }
+                Poll::Ready(Some(Err(e))) => {
+                    self.ended = true;
+                    self.incoming.push_back(Err(e));
+                }
+                Poll::Ready(None) => self.ended = true,
+                Poll::Pending => break,
+            }
+
+            read = true;
+        }
+
+        read
+    }
+
+    /// Sends pings, enforces deadlines, and initiates a close on shutdown or
+    /// when idle, as necessary. Pongs that have arrived are read first so that
+    /// they meet their deadline even if the handler isn't reading.
+    fn poll_heartbeat(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.wakers.heartbeat.register(cx.waker());
+        if self.read_ahead() {
+            self.wakers.handler.wake();
+        }
+
+        let hb = &mut self.heartbeat;
+        if hb.closing && self.ended {
+            return Poll::Ready(Ok(()));
+        }
+
+        if !hb.closing {
+            let close = |code, reason: &'static str| {
+                Message::Close(Some(CloseFrame { code, reason: reason.into() }))
+            };
+
+            if hb.shutdown.poll_unpin(cx).is_ready() {
+                hb.closing = true;
+                hb.pending = Some(close(CloseCode::Away, "server shutting down"));
+            } else if hb.idle.as_mut().map_or(false, |(_, t)| t.as_mut().poll(cx).is_ready()) {
+                hb.closing = true;
+                hb.pending = Some(close(CloseCode::Normal, "idle timeout"));
+            }
+        }
+
+        if let Some(deadline) = hb.pong_deadline.as_mut() {
+            if deadline.as_mut().poll(cx).is_ready() {
+                let error = io::Error::new(io::ErrorKind::TimedOut, "pong deadline exceeded");
+                return Poll::Ready(Err(Error::Io(error)));
+            }
+        }
+
+        if let Some((interval, timer)) = hb.ping.as_mut() {
+            if !hb.closing && timer.as_mut().poll(cx).is_ready() {
+                timer.as_mut().reset(Instant::now() + *interval);
+                let _ = timer.as_mut().poll(cx);
+                if hb.pending.is_none() && hb.pong_deadline.is_none() {
+                    hb.pending = Some(Message::Ping(vec![]));
+                    if let Some(timeout) = hb.pong_timeout {
+                        let mut deadline = Box::pin(sleep(timeout));
+                        let _ = deadline.as_mut().poll(cx);
+                        hb.pong_deadline = Some(deadline);
+                    }
+                }
+            }
+        }
+
+        let mut cx = Context::from_waker(&self.waker);
+        if self.heartbeat.pending.is_some() {
+            if let Poll::Ready(result) = self.inner.poll_ready_unpin(&mut cx) {
+                result?;
+                if let Some(message) = self.heartbeat.pending.take() {
+                    self.inner.start_send_unpin(message)?;
+                    self.heartbeat.flushing = true;
+                }
+            }
+        }
+
+        if self.heartbeat.flushing {
+            if let Poll::Ready(result) = self.inner.poll_flush_unpin(&mut cx) {
+                self.heartbeat.flushing = false;
+                result?;
+            }
+        }
+
+        Poll::Pending
+    }
+
+    /// Records activity on the connection, resetting the idle timer if
+    /// `message` is a data message. Control messages, such as heartbeat pings
+    /// and pongs, don't count as activity. The timer is polled, and thus
+    /// rearmed, by the next call to `poll_heartbeat()`.
+    fn touch(&mut self, message: &Message) {
+        if !message.is_text() && !message.is_binary() {
+            return;
+        }
+
+        if let Some((max_idle, timer)) = self.heartbeat.idle.as_mut() {
+            timer.as_mut().reset(Instant::now() + *max_idle);
+        }
     }
 }
 
@@ -54,17 +304,31 @@ impl Stream for DuplexStream {
     type Item = Result<Message>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        self.get_mut().0.poll_next_unpin(cx)
-    }
+        let this = self.get_mut();
+        if this.terminated {
+            return Poll::Ready(None);
+        }
+
+        let item = this.lock_with(cx, |shared, _| {
+            if shared.read_ahead() {
+                shared.wakers.heartbeat.wake();
+            }
+
+            match shared.incoming.pop_front() {
+                Some(item) => Poll::Ready(Some(item)),
+                None if shared.ended => Poll::Ready(None),
+                None => Poll::Pending,
+            }
+        });
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        self.0.size_hint()
+        this.terminated = matches!(item, Poll::Ready(None));
+        item
     }
 }
 
 impl FusedStream for DuplexStream {
     fn is_terminated(&self) -> bool {
-        self.0.is_terminated()
+        self.terminated
     }
 }
 
@@ -72,18 +336,20 @@ impl Sink<Message> for DuplexStream {
     type Error = Error;
 
     fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        self.get_mut().0.poll_ready_unpin(cx)
+        self.lock_with(cx, |shared, cx| shared.inner.poll_ready_unpin(cx))
     }
 
     fn start_send(self: Pin<&mut Self>, item: Message) -> Result<(), Self::Error> {
-        self.get_mut().0.start_send_unpin(item)
+        let mut shared = self.shared.lock().expect("stream lock");
+        shared.touch(&item);
+        shared.inner.start_send_unpin(item)
     }
 
     fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        self.get_mut().0.poll_flush_unpin(cx)
+        self.lock_with(cx, |shared, cx| shared.inner.poll_flush_unpin(cx))
     }
 
     fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        self.get_mut().0.poll_close_unpin(cx)
+        self.lock_with(cx, |shared, cx| shared.inner.poll_close_unpin(cx))
     }
 }
@@ -0,0 +1,414 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use rocket::{Rocket, Orbit, Build, Ignite, Sentinel};
+use rocket::fairing::{self, Fairing, Info, Kind};
+use rocket::futures::{SinkExt, StreamExt, future::{select, Either}};
+use rocket::request::{FromRequest, Request, Outcome};
+use rocket::tokio::sync::mpsc;
+use rocket::http::Status;
+
+use crate::Message;
+use crate::frame::{CloseFrame, CloseCode};
+use crate::result::Result;
+use crate::stream::DuplexStream;
+
+/// A publish/subscribe hub fanning out [`Message`]s to WebSocket clients in
+/// named rooms.
+///
+/// A `Hub` is a [`Fairing`] that must be attached to the application, after
+/// which it is available as a request guard. Clients join a room, either via
+/// [`Hub::join()`], which drives a connection until it closes, or via
+/// [`Hub::subscribe()`], which returns a [`Member`] handle for custom
+/// handling. A client is a member of a room for as long as its `join()` future
+/// runs or its `Member` lives, and each room's member count is available via
+/// [`Hub::presence()`]. Messages are broadcast to rooms via
+/// [`Hub::broadcast()`], which may be called from any route.
+///
+/// # Slow Consumers
+///
+/// Every member has a bounded queue of pending messages, the capacity of which
+/// is set via [`Hub::capacity()`]. When a member's queue is full, the hub
+/// applies the configured [`SlowConsumer`] policy: the message is dropped for
+/// that member, or the member is disconnected with a close code of `1008`
+/// (policy violation).
+///
+/// # Shutdown
+///
+/// When Rocket begins a graceful shutdown, every member is disconnected with a
+/// close code of `1001` (going away) and subsequent joins close immediately.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use rocket_ws as ws;
+/// use ws::{Hub, SlowConsumer};
+///
+/// #[get("/chat/<room>")]
+/// fn chat(ws: ws::WebSocket, hub: Hub, room: &str) -> ws::Channel<'_> {
+///     // Forward messages from the client to everyone in the room, and
+///     // messages to the room to the client, until the client disconnects.
+///     ws.channel(move |stream| Box::pin(async move {
+///         hub.join(room, stream).await
+///     }))
+/// }
+///
+/// #[post("/announce/<room>", data = "<text>")]
+/// fn announce(hub: Hub, room: &str, text: String) -> String {
+///     let delivered = hub.broadcast(room, text);
+///     format!("delivered to {delivered} of {} members", hub.presence(room))
+/// }
+///
+/// #[launch]
+/// fn rocket() -> _ {
+///     let hub = Hub::new().capacity(32).slow_consumer(SlowConsumer::Disconnect);
+///     rocket::build()
+///         .attach(hub)
+///         .mount("/", routes![chat, announce])
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Hub {
+    capacity: usize,
+    policy: SlowConsumer,
+    inner: Arc<Inner>,
+}
+
+/// The policy applied to a [`Hub`] member whose message queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SlowConsumer {
+    /// Drop the message for the slow member only. This is the default.
+    #[default]
+    Drop,
+    /// Disconnect the slow member.
+    Disconnect,
+}
+
+/// A member of a [`Hub`] room, returned by [`Hub::subscribe()`].
+///
+/// The member leaves the room when the `Member` is dropped.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use rocket_ws as ws;
+/// use rocket::futures::SinkExt;
+/// use ws::Hub;
+///
+/// /// Sends room messages to the client, ignoring messages from the client.
+/// #[get("/feed/<room>")]
+/// fn feed(ws: ws::WebSocket, hub: Hub, room: &str) -> ws::Channel<'_> {
+///     ws.channel(move |mut stream| Box::pin(async move {
+///         let mut member = hub.subscribe(room);
+///         while let Some(message) = member.recv().await {
+///             stream.send(message).await?;
+///         }
+///
+///         Ok(())
+///     }))
+/// }
+/// ```
+pub struct Member {
+    hub: Hub,
+    room: Arc<str>,
+    id: u64,
+    rx: mpsc::Receiver<Message>,
+}
+
+#[derive(Default)]
+struct Inner {
+    rooms: Mutex<HashMap<Arc<str>, HashMap<u64, mpsc::Sender<Message>>>>,
+    next_id: AtomicU64,
+    closing: AtomicBool,
+}
+
+impl Hub {
+    /// The default capacity of each member's message queue.
+    pub const DEFAULT_CAPACITY: usize = 64;
+
+    /// Creates a new hub with a per-member queue capacity of
+    /// [`Hub::DEFAULT_CAPACITY`] and the [`SlowConsumer::Drop`] policy.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_ws::Hub;
+    ///
+    /// let hub = Hub::new();
+    /// ```
+    pub fn new() -> Self {
+        Hub {
+            capacity: Self::DEFAULT_CAPACITY,
+            policy: SlowConsumer::default(),
+            inner: Arc::default(),
+        }
+    }
+
+    /// Sets the capacity of each member's message queue to `capacity`, which
+    /// must be at least `1`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_ws::Hub;
+    ///
+    /// let hub = Hub::new().capacity(16);
+    /// ```
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Sets the policy applied to members whose queue is full.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_ws::{Hub, SlowConsumer};
+    ///
+    /// let hub = Hub::new().slow_consumer(SlowConsumer::Disconnect);
+    /// ```
+    pub fn slow_consumer(mut self, policy: SlowConsumer) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Joins the client connected via `stream` to `room` until it disconnects.
+    ///
+    /// Text and binary messages from the client are broadcast to every member
+    /// of the room, including the client itself. Messages broadcast to the
+    /// room are sent to the client. Returns when the client closes the
+    /// connection, is disconnected as a slow consumer, or the server shuts
+    /// down.
+    ///
+    /// See [`Hub`] for an example.
+    pub async fn join(&self, room: &str, stream: DuplexStream) -> Result<()> {
+        let mut member = self.subscribe(room);
+        let (mut sink, mut source) = stream.split();
+        loop {
+            match select(Box::pin(member.recv()), source.next()).await {
+                Either::Left((Some(message), _)) => sink.send(message).await?,
+                Either::Left((None, _)) => {
+                    let (code, reason) = match self.is_closing() {
+                        true => (CloseCode::Away, "shutdown"),
+                        false => (CloseCode::Policy, "slow consumer"),
+                    };
+
+                    // The stream may have begun closing on shutdown itself.
+                    let frame = CloseFrame { code, reason: reason.into() };
+                    let _ = sink.send(Message::Close(Some(frame))).await;
+                    return Ok(());
+                }
+                Either::Right((Some(message), _)) => match message? {
+                    message @ (Message::Text(_) | Message::Binary(_)) => {
+                        self.broadcast(room, message);
+                    }
+                    Message::Close(_) => return Ok(()),
+                    _ => {}
+                },
+                Either::Right((None, _)) => return Ok(()),
+            }
+        }
+    }
+
+    /// Subscribes to `room`, returning a [`Member`] that receives messages
+    /// broadcast to the room until it is dropped.
+    ///
+    /// See [`Member`] for an example.
+    pub fn subscribe(&self, room: &str) -> Member {
+        let (tx, rx) = mpsc::channel(self.capacity);
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        let room: Arc<str> = room.into();
+        if !self.is_closing() {
+            let mut rooms = self.inner.rooms.lock().expect("hub lock");
+            rooms.entry(room.clone()).or_default().insert(id, tx);
+        }
+
+        Member { hub: self.clone(), room, id, rx }
+    }
+
+    /// Broadcasts `message` to every member of `room`. Returns the number of
+    /// members the message was queued for.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_ws::Hub;
+    ///
+    /// let hub = Hub::new();
+    /// assert_eq!(hub.broadcast("lobby", "hello"), 0);
+    ///
+    /// let _member = hub.subscribe("lobby");
+    /// assert_eq!(hub.broadcast("lobby", "hello"), 1);
+    /// ```
+    pub fn broadcast<M: Into<Message>>(&self, room: &str, message: M) -> usize {
+        use mpsc::error::TrySendError;
+
+        let message = message.into();
+        let mut delivered = 0;
+        let mut rooms = self.inner.rooms.lock().expect("hub lock");
+        let Some(members) = rooms.get_mut(room) else {
+            return 0;
+        };
+
+        members.retain(|_, tx| match tx.try_send(message.clone()) {
+            Ok(()) => {
+                delivered += 1;
+                true
+            }
+            Err(TrySendError::Full(_)) => self.policy == SlowConsumer::Drop,
+            Err(TrySendError::Closed(_)) => false,
+        });
+
+        if members.is_empty() {
+            rooms.remove(room);
+        }
+
+        delivered
+    }
+
+    /// Returns the number of members in `room`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_ws::Hub;
+    ///
+    /// let hub = Hub::new();
+    /// let member = hub.subscribe("lobby");
+    /// assert_eq!(hub.presence("lobby"), 1);
+    ///
+    /// drop(member);
+    /// assert_eq!(hub.presence("lobby"), 0);
+    /// ```
+    pub fn presence(&self, room: &str) -> usize {
+        let rooms = self.inner.rooms.lock().expect("hub lock");
+        rooms.get(room).map_or(0, |members| members.len())
+    }
+
+    /// Returns the names of the rooms with at least one member along with
+    /// their member counts.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_ws::Hub;
+    ///
+    /// let hub = Hub::new();
+    /// let _member = hub.subscribe("lobby");
+    /// assert_eq!(hub.rooms(), vec![("lobby".to_string(), 1)]);
+    /// ```
+    pub fn rooms(&self) -> Vec<(String, usize)> {
+        let rooms = self.inner.rooms.lock().expect("hub lock");
+        rooms.iter().map(|(room, members)| (room.to_string(), members.len())).collect()
+    }
+
+    /// Disconnects every member and rejects further subscriptions.
+    fn close(&self) {
+        self.inner.closing.store(true, Ordering::Release);
+        self.inner.rooms.lock().expect("hub lock").clear();
+    }
+
+    fn is_closing(&self) -> bool {
+        self.inner.closing.load(Ordering::Acquire)
+    }
+
+    fn leave(&self, room: &str, id: u64) {
+        let mut rooms = self.inner.rooms.lock().expect("hub lock");
+        if let Some(members) = rooms.get_mut(room) {
+            members.remove(&id);
+            if members.is_empty() {
+                rooms.remove(room);
+            }
+        }
+    }
+}
+
+impl Member {
+    /// Returns the name of the room `self` is a member of.
+    pub fn room(&self) -> &str {
+        &self.room
+    }
+
+    /// Receives the next message broadcast to the room. Returns `None` if the
+    /// member was disconnected as a slow consumer or the server is shutting
+    /// down.
+    pub async fn recv(&mut self) -> Option<Message> {
+        self.rx.recv().await
+    }
+
+    /// Broadcasts `message` to every member of the room, including `self`.
+    /// Returns the number of members the message was queued for.
+    pub fn broadcast<M: Into<Message>>(&self, message: M) -> usize {
+        self.hub.broadcast(&self.room, message)
+    }
+
+    /// Returns the number of members in the room, including `self`.
+    pub fn presence(&self) -> usize {
+        self.hub.presence(&self.room)
+    }
+}
+
+impl Drop for Member {
+    fn drop(&mut self) {
+        self.hub.leave(&self.room, self.id);
+    }
+}
+
+impl Default for Hub {
+    fn default() -> Self {
+        Hub::new()
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for Hub {
+    fn info(&self) -> Info {
+        Info {
+            name: "WebSocket Hub",
+            kind: Kind::Ignite | Kind::Liftoff | Kind::Singleton,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        Ok(rocket.manage(self.clone()))
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<Orbit>) {
+        let (hub, shutdown) = (self.clone(), rocket.shutdown());
+        rocket::tokio::spawn(async move {
+            shutdown.await;
+            hub.close();
+        });
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Hub {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, ()> {
+        match req.rocket().state::<Hub>() {
+            Some(hub) => Outcome::Success(hub.clone()),
+            None => {
+                rocket::error!("`Hub` request guard used without attaching a `Hub` fairing");
+                Outcome::Error((Status::InternalServerError, ()))
+            }
+        }
+    }
+}
+
+impl Sentinel for Hub {
+    fn abort(rocket: &Rocket<Ignite>) -> bool {
+        if rocket.state::<Hub>().is_none() {
+            rocket::error!("`Hub` request guard used without attaching a `Hub` fairing\n\
+                attach a `Hub` via `rocket.attach(Hub::new())`");
+            return true;
+        }
+
+        false
+    }
+}
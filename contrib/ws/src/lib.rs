@@ -69,6 +69,18 @@
 //!     }
 //! }
 //! ```
+//!
+//! Compression, via [`WebSocket::deflate()`], and heartbeats, via
+//! [`WebSocket::heartbeat()`], are configured separately.
+//!
+//! With the `json` or `msgpack` features enabled, a [`DuplexStream`] can be
+//! converted into a stream of typed values.
+#![cfg_attr(any(feature = "json", feature = "msgpack"), doc = "See [`codec`] for details.")]
+//!
+//! WebSocket routes can be tested without a network connection using a local
+//! [`Client`](rocket::local::asynchronous::Client). See [`local`] for details.
+//!
+//! [`DuplexStream`]: crate::stream::DuplexStream
 
 #![doc(html_root_url = "https://api.rocket.rs/master/rocket_ws")]
 #![doc(html_favicon_url = "https://rocket.rs/images/favicon.ico")]
@@ -78,10 +90,17 @@ mod tungstenite {
     #[doc(inline)] pub use tokio_tungstenite::tungstenite::*;
 }
 
+mod config;
+#[cfg(any(feature = "json", feature = "msgpack"))]
+pub mod codec;
+mod deflate;
 mod duplex;
+mod hub;
 mod websocket;
+pub mod local;
 
 pub use self::websocket::{WebSocket, Channel};
+pub use self::hub::{Hub, Member, SlowConsumer};
 
 /// A WebSocket message.
 ///
@@ -159,6 +178,8 @@ pub use self::tungstenite::Message;
 ///
 pub use self::tungstenite::protocol::WebSocketConfig as Config;
 
+pub use self::config::{DeflateConfig, HeartbeatConfig};
+
 /// Structures for constructing raw WebSocket frames.
 pub mod frame {
     #[doc(hidden)] pub use crate::Message;
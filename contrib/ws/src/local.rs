@@ -0,0 +1,141 @@
+//! Testing WebSocket routes with a local [`Client`].
+//!
+//! The [`LocalWebSocket`] extension trait, implemented for
+//! [`LocalRequest`], performs a WebSocket handshake against a local, in-memory
+//! instance of Rocket via [`LocalRequest::upgrade()`] and returns a client-side
+//! [`WebSocketStream`] for sending and receiving messages. Messages are
+//! compressed if permessage-deflate is requested via the
+//! `Sec-WebSocket-Extensions` header and accepted by the route.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use rocket::{get, routes};
+//! # use rocket_ws as ws;
+//! use rocket::futures::{SinkExt, StreamExt};
+//! use rocket::local::asynchronous::Client;
+//! use ws::local::LocalWebSocket;
+//!
+//! #[get("/echo")]
+//! fn echo(ws: ws::WebSocket) -> ws::Stream!['static] {
+//!     ws::Stream! { ws =>
+//!         for await message in ws {
+//!             yield message?;
+//!         }
+//!     }
+//! }
+//!
+//! # rocket::async_test(async {
+//! let client = Client::tracked(rocket::build().mount("/", routes![echo])).await.unwrap();
+//! let mut stream = client.get("/echo").websocket().await.expect("handshake");
+//!
+//! stream.send(ws::Message::text("hello")).await.unwrap();
+//! let reply = stream.next().await.unwrap().unwrap();
+//! assert_eq!(reply, ws::Message::text("hello"));
+//!
+//! stream.close(None).await.unwrap();
+//! # });
+//! ```
+//!
+//! [`Client`]: rocket::local::asynchronous::Client
+//! [`LocalRequest::upgrade()`]: rocket::local::asynchronous::LocalRequest::upgrade()
+
+use std::io;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use rocket::http::Header;
+use rocket::local::asynchronous::{LocalRequest, LocalResponse, LocalUpgrade};
+use rocket::tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::Config;
+use crate::deflate::{self, DeflateStream};
+use crate::tungstenite::handshake::client::generate_key;
+use crate::tungstenite::protocol::Role;
+
+/// A client-side WebSocket message stream over a local, in-memory connection.
+///
+/// The response to the handshake request can be retrieved via
+/// `stream.get_ref().response()`. See [`LocalStream`].
+pub type WebSocketStream<'c> = tokio_tungstenite::WebSocketStream<LocalStream<'c>>;
+
+/// The client's end of a local WebSocket connection, compressing and
+/// decompressing messages if permessage-deflate was negotiated.
+///
+/// Dereferences to the underlying [`LocalUpgrade`].
+pub struct LocalStream<'c>(DeflateStream<LocalUpgrade<'c>>);
+
+impl<'c> Deref for LocalStream<'c> {
+    type Target = LocalUpgrade<'c>;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.get_ref()
+    }
+}
+
+impl AsyncRead for LocalStream<'_> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for LocalStream<'_> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_shutdown(cx)
+    }
+}
+
+/// Extension trait to perform a WebSocket handshake with a [`LocalRequest`].
+///
+/// See the [module docs](self) for an example.
+#[rocket::async_trait]
+pub trait LocalWebSocket<'c> {
+    /// Dispatches `self` as a WebSocket handshake request and, on success,
+    /// returns the client's end of the connection. If the route doesn't accept
+    /// the handshake, returns the response instead.
+    ///
+    /// The `Connection`, `Upgrade`, and `Sec-WebSocket-Version` headers are
+    /// set, replacing any existing values, as is the `Sec-WebSocket-Key`
+    /// header if it isn't already present. Other handshake headers, such as
+    /// `Sec-WebSocket-Protocol`, can be set on `self` beforehand.
+    ///
+    /// To request compression, set `Sec-WebSocket-Extensions` to
+    /// `permessage-deflate`, optionally with parameters. If the route accepts
+    /// it, messages are compressed and decompressed by the returned stream.
+    async fn websocket(self) -> Result<WebSocketStream<'c>, LocalResponse<'c>>;
+}
+
+#[rocket::async_trait]
+impl<'c> LocalWebSocket<'c> for LocalRequest<'c> {
+    async fn websocket(mut self) -> Result<WebSocketStream<'c>, LocalResponse<'c>> {
+        self.replace_header(Header::new("Connection", "Upgrade"));
+        self.replace_header(Header::new("Upgrade", "websocket"));
+        self.replace_header(Header::new("Sec-WebSocket-Version", "13"));
+        if !self.headers().contains("Sec-WebSocket-Key") {
+            self.add_header(Header::new("Sec-WebSocket-Key", generate_key()));
+        }
+
+        let io = self.upgrade().await?;
+        let extensions = io.response().headers().get("Sec-WebSocket-Extensions");
+        let params = deflate::accepted(extensions);
+        let io = DeflateStream::new(io, &Config::default(), params, Role::Client);
+        Ok(WebSocketStream::from_raw_socket(LocalStream(io), Role::Client, None).await)
+    }
+}
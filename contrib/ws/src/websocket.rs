@@ -1,12 +1,15 @@
 use std::io;
+use std::pin::pin;
 
+use rocket::Shutdown;
 use rocket::data::{IoHandler, IoStream};
-use rocket::futures::{self, StreamExt, SinkExt, future::BoxFuture, stream::SplitStream};
+use rocket::futures::{self, StreamExt, SinkExt, stream::SplitStream};
+use rocket::futures::future::{select, BoxFuture, Either};
 use rocket::response::{self, Responder, Response};
 use rocket::request::{FromRequest, Request, Outcome};
-use rocket::http::Status;
+use rocket::http::{HttpVersion, Status};
 
-use crate::{Config, Message};
+use crate::{deflate, Config, DeflateConfig, HeartbeatConfig, Message};
 use crate::stream::DuplexStream;
 use crate::result::{Result, Error};
 
@@ -24,15 +27,41 @@ use crate::result::{Result, Error};
 /// [`Stream!`](crate::Stream!), [`WebSocket::channel()`], or
 /// [`WebSocket::stream()`]. The connection can be configured via
 /// [`WebSocket::config()`]; see [`Config`] for details on configuring a
-/// connection.
+/// connection. Compression and heartbeats are enabled via
+/// [`WebSocket::deflate()`] and [`WebSocket::heartbeat()`].
 ///
 /// ### Forwarding
 ///
 /// If the incoming request is not a valid WebSocket request, the guard
 /// forwards with a status of `BadRequest`. The guard never fails.
+///
+/// ### Subprotocols
+///
+/// The subprotocols offered by the client via `Sec-WebSocket-Protocol` are
+/// available via [`WebSocket::protocols()`]. A server that speaks a
+/// subprotocol selects one via [`WebSocket::protocol()`], which echoes the
+/// selection back to the client in the handshake response. If the client
+/// offered none of the server's protocols, the handshake is rejected.
 pub struct WebSocket {
     config: Config,
+    compression: Option<DeflateConfig>,
+    heartbeat: HeartbeatConfig,
     key: String,
+    offered: Vec<String>,
+    protocol: Protocol,
+    extensions: Vec<deflate::Offer>,
+    deflate: Option<deflate::Params>,
+    shutdown: Shutdown,
+}
+
+/// The state of subprotocol selection.
+enum Protocol {
+    /// The server didn't request a subprotocol.
+    None,
+    /// The server selected this subprotocol, which the client offered.
+    Selected(String),
+    /// The client offered none of the server's subprotocols.
+    Unavailable,
 }
 
 impl WebSocket {
@@ -63,6 +92,60 @@ impl WebSocket {
         self
     }
 
+    /// Enable permessage-deflate compression, configured by `config`, if the
+    /// client supports it. See [`DeflateConfig`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::get;
+    /// # use rocket_ws as ws;
+    /// #
+    /// #[get("/echo")]
+    /// fn echo_stream(ws: ws::WebSocket) -> ws::Stream!['static] {
+    ///     let ws = ws.deflate(ws::DeflateConfig::default());
+    ///
+    ///     ws::Stream! { ws =>
+    ///         for await message in ws {
+    ///             yield message?;
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn deflate(mut self, config: DeflateConfig) -> Self {
+        self.compression = Some(config);
+        self
+    }
+
+    /// Change the default heartbeat and idle timeout configuration, which
+    /// disables both, to `config`. See [`HeartbeatConfig`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::get;
+    /// # use rocket_ws as ws;
+    /// use std::time::Duration;
+    ///
+    /// #[get("/echo")]
+    /// fn echo_stream(ws: ws::WebSocket) -> ws::Stream!['static] {
+    ///     let ws = ws.heartbeat(ws::HeartbeatConfig {
+    ///         ping_interval: Some(Duration::from_secs(30)),
+    ///         ..Default::default()
+    ///     });
+    ///
+    ///     ws::Stream! { ws =>
+    ///         for await message in ws {
+    ///             yield message?;
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn heartbeat(mut self, config: HeartbeatConfig) -> Self {
+        self.heartbeat = config;
+        self
+    }
+
     /// Create a read/write channel to the client and call `handler` with it.
     ///
     /// This method takes a `FnOnce`, `handler`, that consumes a read/write
@@ -168,6 +251,12 @@ impl WebSocket {
     /// This is the value returned via the [`Sec-WebSocket-Accept`] header
     /// during the acceptance response.
     ///
+    /// WebSockets bootstrapped over HTTP/2 or HTTP/3 via extended `CONNECT`
+    /// ([RFC 8441], [RFC 9220]) carry no key. For these, the accept key is
+    /// empty, and no `Sec-WebSocket-Accept` header is sent.
+    ///
+    /// [RFC 8441]: https://datatracker.ietf.org/doc/html/rfc8441
+    /// [RFC 9220]: https://datatracker.ietf.org/doc/html/rfc9220
     /// [`Sec-WebSocket-Accept`]:
     /// https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Sec-WebSocket-Accept
     ///
@@ -187,6 +276,117 @@ impl WebSocket {
         &self.key
     }
 
+    /// Returns an iterator over the subprotocols offered by the client via
+    /// `Sec-WebSocket-Protocol` in the client's order of preference.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::get;
+    /// # use rocket_ws as ws;
+    /// #
+    /// #[get("/echo")]
+    /// fn echo_stream(ws: ws::WebSocket) -> ws::Stream!['static] {
+    ///     let offers_stomp = ws.protocols().any(|p| p == "v12.stomp");
+    ///     ws.stream(|io| io)
+    /// }
+    /// ```
+    pub fn protocols(&self) -> impl Iterator<Item = &str> {
+        self.offered.iter().map(|p| p.as_str())
+    }
+
+    /// Selects the first subprotocol in `supported`, in the server's order of
+    /// preference, that the client offered. The selection is sent to the
+    /// client in the `Sec-WebSocket-Protocol` header of the handshake
+    /// response.
+    ///
+    /// If the client offered none of the protocols in `supported`, the
+    /// handshake is rejected with a status of `BadRequest`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::get;
+    /// # use rocket_ws as ws;
+    /// #
+    /// #[get("/graphql")]
+    /// fn graphql(ws: ws::WebSocket) -> ws::Stream!['static] {
+    ///     // Prefer `graphql-transport-ws`, but also speak `graphql-ws`.
+    ///     let ws = ws.protocol(["graphql-transport-ws", "graphql-ws"]);
+    ///
+    ///     ws::Stream! { ws =>
+    ///         for await message in ws {
+    ///             yield message?;
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn protocol<I, P>(mut self, supported: I) -> Self
+        where I: IntoIterator<Item = P>, P: AsRef<str>
+    {
+        let selected = supported.into_iter()
+            .find(|p| self.offered.iter().any(|offer| offer == p.as_ref()));
+
+        self.protocol = match selected {
+            Some(p) => Protocol::Selected(p.as_ref().to_string()),
+            None => Protocol::Unavailable,
+        };
+
+        self
+    }
+
+    /// Returns the subprotocol selected via [`WebSocket::protocol()`], if any.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::get;
+    /// # use rocket_ws as ws;
+    /// #
+    /// #[get("/echo")]
+    /// fn echo_stream(ws: ws::WebSocket) -> ws::Stream!['static] {
+    ///     assert!(ws.selected_protocol().is_none());
+    ///     let ws = ws.protocol(["chat"]);
+    ///     ws.stream(|io| io)
+    /// }
+    /// ```
+    pub fn selected_protocol(&self) -> Option<&str> {
+        match &self.protocol {
+            Protocol::Selected(protocol) => Some(protocol),
+            Protocol::None | Protocol::Unavailable => None,
+        }
+    }
+
+    /// Returns a response with the handshake response headers or an
+    /// error if the handshake must be rejected. Negotiates extensions.
+    fn handshake<'o>(&mut self) -> response::Result<'o> {
+        let mut response = Response::build();
+        response.raw_header("Sec-Websocket-Version", "13");
+        if !self.key.is_empty() {
+            response.raw_header("Sec-WebSocket-Accept", self.key.clone());
+        }
+
+        match &self.protocol {
+            Protocol::None => {},
+            Protocol::Selected(protocol) => {
+                response.raw_header("Sec-WebSocket-Protocol", protocol.clone());
+            }
+            Protocol::Unavailable => {
+                let offered = self.offered.join(", ");
+                rocket::warn!(%offered, "client offered no supported websocket subprotocol");
+                return Err(Status::BadRequest);
+            }
+        }
+
+        self.deflate = self.compression
+            .and_then(|config| deflate::negotiate(&config, &self.extensions));
+
+        if let Some(params) = &self.deflate {
+            response.raw_header("Sec-WebSocket-Extensions", params.to_string());
+        }
+
+        response.ok()
+    }
 }
 
 /// A streaming channel, returned by [`WebSocket::channel()`].
@@ -227,43 +427,68 @@ impl<'r> FromRequest<'r> for WebSocket {
             .any(|h| h.split(',').any(|v| eq(v.trim(), "websocket")));
 
         let is_13 = headers.get_one("Sec-WebSocket-Version").map_or(false, |v| v == "13");
-        let key = headers.get_one("Sec-WebSocket-Key").map(|k| derive_accept_key(k.as_bytes()));
+        // Extended `CONNECT` requests, over HTTP/2 and HTTP/3, carry no key.
+        let is_extended = matches!(req.version(), Some(HttpVersion::Http2 | HttpVersion::Http3))
+            && req.connect_protocol() == Some("websocket");
+        let key = match headers.get_one("Sec-WebSocket-Key") {
+            Some(k) => Some(derive_accept_key(k.as_bytes())),
+            None if is_extended => Some(String::new()),
+            None => None,
+        };
+
+        let offered = headers.get("Sec-WebSocket-Protocol")
+            .flat_map(|h| h.split(','))
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty())
+            .map(|p| p.to_string())
+            .collect();
+
+        let extensions = deflate::offers(headers.get("Sec-WebSocket-Extensions"));
         match key {
-            Some(key) if is_upgrade && is_ws && is_13 => {
-                Outcome::Success(WebSocket { key, config: Config::default() })
-            },
+            Some(key) if is_upgrade && is_ws && is_13 => Outcome::Success(WebSocket {
+                key,
+                offered,
+                extensions,
+                protocol: Protocol::None,
+                deflate: None,
+                shutdown: req.rocket().shutdown(),
+                config: Config::default(),
+                compression: None,
+                heartbeat: HeartbeatConfig::default(),
+            }),
             Some(_) | None => Outcome::Forward(Status::BadRequest)
         }
     }
 }
 
 impl<'r, 'o: 'r> Responder<'r, 'o> for Channel<'o> {
-    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'o> {
-        Response::build()
-            .raw_header("Sec-Websocket-Version", "13")
-            .raw_header("Sec-WebSocket-Accept", self.ws.key.clone())
-            .upgrade("websocket", self)
-            .ok()
+    fn respond_to(mut self, _: &'r Request<'_>) -> response::Result<'o> {
+        let mut response = self.ws.handshake()?;
+        response.add_upgrade("websocket", self);
+        Ok(response)
     }
 }
 
 impl<'r, 'o: 'r, S> Responder<'r, 'o> for MessageStream<'o, S>
     where S: futures::Stream<Item = Result<Message>> + Send + 'o
 {
-    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'o> {
-        Response::build()
-            .raw_header("Sec-Websocket-Version", "13")
-            .raw_header("Sec-WebSocket-Accept", self.ws.key.clone())
-            .upgrade("websocket", self)
-            .ok()
+    fn respond_to(mut self, _: &'r Request<'_>) -> response::Result<'o> {
+        let mut response = self.ws.handshake()?;
+        response.add_upgrade("websocket", self);
+        Ok(response)
     }
 }
 
 #[rocket::async_trait]
 impl IoHandler for Channel<'_> {
     async fn io(self: Box<Self>, io: IoStream) -> io::Result<()> {
-        let stream = DuplexStream::new(io, self.ws.config).await;
-        let result = (self.handler)(stream).await;
+        let ws = self.ws;
+        let stream = DuplexStream::new(io, ws.config, ws.heartbeat, ws.deflate, ws.shutdown).await;
+        let heartbeat = stream.heartbeat();
+        let result = match select((self.handler)(stream), pin!(heartbeat)).await {
+            Either::Left((result, _)) | Either::Right((result, _)) => result,
+        };
+
         handle_result(result).map(|_| ())
     }
 }
@@ -273,22 +498,32 @@ impl<'r, S> IoHandler for MessageStream<'r, S>
     where S: futures::Stream<Item = Result<Message>> + Send + 'r
 {
     async fn io(self: Box<Self>, io: IoStream) -> io::Result<()> {
-        let (mut sink, source) = DuplexStream::new(io, self.ws.config).await.split();
+        let ws = self.ws;
+        let stream = DuplexStream::new(io, ws.config, ws.heartbeat, ws.deflate, ws.shutdown).await;
+        let heartbeat = stream.heartbeat();
+        let (mut sink, source) = stream.split();
         let stream = (self.handler)(source);
-        rocket::tokio::pin!(stream);
-        while let Some(msg) = stream.next().await {
-            let result = match msg {
-                Ok(msg) if msg.is_close() => return Ok(()),
-                Ok(msg) => sink.send(msg).await,
-                Err(e) => Err(e)
-            };
-
-            if !handle_result(result)? {
-                return Ok(());
+        let forward = async move {
+            rocket::tokio::pin!(stream);
+            while let Some(msg) = stream.next().await {
+                let result = match msg {
+                    Ok(msg) if msg.is_close() => return Ok(()),
+                    Ok(msg) => sink.send(msg).await,
+                    Err(e) => Err(e)
+                };
+
+                if !handle_result(result)? {
+                    return Ok(());
+                }
             }
-        }
 
-        Ok(())
+            Ok(())
+        };
+
+        match select(pin!(forward), pin!(heartbeat)).await {
+            Either::Left((result, _)) => result,
+            Either::Right((result, _)) => handle_result(result).map(|_| ()),
+        }
     }
 }
 
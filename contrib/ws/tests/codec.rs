@@ -0,0 +1,174 @@
+#![cfg(any(feature = "json", feature = "msgpack"))]
+
+#[macro_use] extern crate rocket;
+
+use std::time::Duration;
+
+use rocket::State;
+use rocket::futures::{FutureExt, SinkExt, StreamExt};
+use rocket::local::asynchronous::Client;
+use rocket::serde::{Serialize, Deserialize};
+use rocket::tokio::sync::mpsc;
+use rocket::tokio::time::{sleep, timeout};
+use rocket_ws::{self as ws, Message};
+use rocket_ws::codec::{Codec, DecodeError, TypedStream};
+use rocket_ws::frame::CloseCode;
+use rocket_ws::local::{LocalWebSocket, WebSocketStream};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+/// Replies to every point with its coordinates swapped.
+async fn swap<C: Codec>(mut stream: TypedStream<Point, C>) -> ws::result::Result<()> {
+    while let Some(Ok(Point { x, y })) = stream.next().await {
+        stream.send(Point { x: y, y: x }).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[get("/json")]
+fn json(ws: ws::WebSocket) -> ws::Channel<'static> {
+    ws.channel(|stream| Box::pin(swap(stream.json())))
+}
+
+#[cfg(feature = "msgpack")]
+#[get("/msgpack")]
+fn msgpack(ws: ws::WebSocket) -> ws::Channel<'static> {
+    ws.channel(|stream| Box::pin(swap(stream.msgpack())))
+}
+
+async fn client() -> Client {
+    let rocket = rocket::build();
+
+    #[cfg(feature = "json")]
+    let rocket = rocket.mount("/", routes![json]);
+
+    #[cfg(feature = "msgpack")]
+    let rocket = rocket.mount("/", routes![msgpack]);
+
+    Client::tracked(rocket).await.unwrap()
+}
+
+async fn recv(stream: &mut WebSocketStream<'_>) -> Message {
+    timeout(Duration::from_secs(5), stream.next()).await
+        .expect("message before timeout")
+        .expect("open stream")
+        .expect("valid message")
+}
+
+async fn assert_policy_close(stream: &mut WebSocketStream<'_>) {
+    match recv(stream).await {
+        Message::Close(Some(frame)) => assert_eq!(frame.code, CloseCode::Policy),
+        message => panic!("expected a close frame, got {message:?}"),
+    }
+}
+
+#[cfg(feature = "json")]
+#[rocket::async_test]
+async fn json_values_round_trip() {
+    let client = client().await;
+    let mut stream = client.get("/json").websocket().await.expect("handshake");
+
+    stream.send(Message::text(r#"{"x":1,"y":2}"#)).await.unwrap();
+    assert_eq!(recv(&mut stream).await, Message::text(r#"{"x":2,"y":1}"#));
+
+    // Pings are handled transparently; binary messages are accepted too.
+    stream.send(Message::Ping(b"ping".to_vec())).await.unwrap();
+    assert_eq!(recv(&mut stream).await, Message::Pong(b"ping".to_vec()));
+
+    stream.send(Message::binary(br#"{"x":3,"y":4}"#.to_vec())).await.unwrap();
+    assert_eq!(recv(&mut stream).await, Message::text(r#"{"x":4,"y":3}"#));
+}
+
+#[cfg(feature = "json")]
+#[rocket::async_test]
+async fn malformed_json_closes_with_policy_violation() {
+    let client = client().await;
+    let mut stream = client.get("/json").websocket().await.expect("handshake");
+    stream.send(Message::text(r#"{"x":1}"#)).await.unwrap();
+    assert_policy_close(&mut stream).await;
+
+    let mut stream = client.get("/json").websocket().await.expect("handshake");
+    stream.send(Message::text("not json")).await.unwrap();
+    assert_policy_close(&mut stream).await;
+}
+
+#[cfg(feature = "msgpack")]
+#[rocket::async_test]
+async fn msgpack_values_round_trip() {
+    use rocket::serde::msgpack;
+
+    let client = client().await;
+    let mut stream = client.get("/msgpack").websocket().await.expect("handshake");
+
+    let point = msgpack::to_vec(&Point { x: 1, y: 2 }).unwrap();
+    stream.send(Message::binary(point)).await.unwrap();
+    match recv(&mut stream).await {
+        Message::Binary(data) => {
+            assert_eq!(msgpack::from_slice::<Point>(&data).unwrap(), Point { x: 2, y: 1 });
+        }
+        message => panic!("expected a binary message, got {message:?}"),
+    }
+}
+
+#[cfg(feature = "msgpack")]
+#[rocket::async_test]
+async fn malformed_msgpack_closes_with_policy_violation() {
+    let client = client().await;
+    let mut stream = client.get("/msgpack").websocket().await.expect("handshake");
+    stream.send(Message::binary(vec![0xc1])).await.unwrap();
+    assert_policy_close(&mut stream).await;
+
+    // MessagePack is only accepted in binary messages.
+    let mut stream = client.get("/msgpack").websocket().await.expect("handshake");
+    stream.send(Message::text(r#"{"x":1,"y":2}"#)).await.unwrap();
+    assert_policy_close(&mut stream).await;
+}
+
+/// Writes until the client applies backpressure, then reports whether the next
+/// item is a decoding error.
+#[cfg(feature = "json")]
+#[get("/json/backpressured")]
+fn backpressured(
+    ws: ws::WebSocket,
+    tx: &State<mpsc::UnboundedSender<bool>>,
+) -> ws::Channel<'static> {
+    let tx = tx.inner().clone();
+    ws.channel(move |stream| Box::pin(async move {
+        let mut stream = stream.json::<Point>();
+        let payload = "x".repeat(16 * 1024);
+        while let Some(Ok(())) = stream.feed(&payload).now_or_never() { }
+
+        let item = stream.next().await;
+        let _ = tx.send(matches!(item, Some(Err(DecodeError::Json(_)))));
+        Ok(())
+    }))
+}
+
+#[cfg(feature = "json")]
+#[rocket::async_test]
+async fn decode_error_survives_backpressure() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let rocket = rocket::build().manage(tx).mount("/", routes![backpressured]);
+    let client = Client::tracked(rocket).await.unwrap();
+    let mut stream = client.get("/json/backpressured").websocket().await.expect("handshake");
+
+    // Let the server hit backpressure while closing before reading anything.
+    stream.send(Message::text("not json")).await.unwrap();
+    sleep(Duration::from_millis(100)).await;
+    loop {
+        match recv(&mut stream).await {
+            Message::Text(_) => continue,
+            Message::Close(Some(frame)) => break assert_eq!(frame.code, CloseCode::Policy),
+            message => panic!("expected a text or close frame, got {message:?}"),
+        }
+    }
+
+    assert_eq!(rx.recv().await, Some(true));
+}
@@ -0,0 +1,89 @@
+#[macro_use] extern crate rocket;
+
+use rocket::futures::{SinkExt, StreamExt};
+use rocket::http::{Header, Status};
+use rocket::local::asynchronous::{Client, LocalRequest};
+use rocket::tokio::io::AsyncReadExt;
+use rocket_ws::{self as ws, local::LocalWebSocket};
+
+#[get("/echo")]
+fn echo(ws: ws::WebSocket) -> ws::Stream!['static] {
+    ws.deflate(ws::DeflateConfig::default()).stream(|io| io)
+}
+
+#[get("/greet")]
+fn greet(ws: ws::WebSocket) -> ws::Channel<'static> {
+    ws.deflate(ws::DeflateConfig::default()).channel(|mut stream| Box::pin(async move {
+        stream.send(ws::Message::text("hello! ".repeat(1024))).await
+    }))
+}
+
+async fn client() -> Client {
+    Client::tracked(rocket::build().mount("/", routes![echo, greet])).await.unwrap()
+}
+
+fn offer<'c>(request: LocalRequest<'c>, extensions: &str) -> LocalRequest<'c> {
+    request.header(Header::new("Sec-WebSocket-Extensions", extensions.to_string()))
+}
+
+async fn round_trip(client: &Client, extensions: &str) {
+    let mut stream = offer(client.get("/echo"), extensions).websocket().await
+        .expect("handshake");
+
+    let response = stream.get_ref().response();
+    assert_eq!(response.status(), Status::SwitchingProtocols);
+    let accepted = response.headers().get_one("Sec-WebSocket-Extensions").unwrap();
+    assert!(accepted.starts_with("permessage-deflate"));
+
+    let messages = [
+        ws::Message::text("hello, world! ".repeat(4096)),
+        ws::Message::text("hi"),
+        ws::Message::binary((0..=255u8).cycle().take(64 * 1024).collect::<Vec<_>>()),
+        ws::Message::text("hello, world! ".repeat(4096)),
+    ];
+
+    for message in messages {
+        stream.send(message.clone()).await.unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap(), message);
+    }
+
+    stream.close(None).await.unwrap();
+}
+
+#[rocket::async_test]
+async fn compressed_messages_round_trip() {
+    let client = client().await;
+    round_trip(&client, "permessage-deflate").await;
+    round_trip(&client, "permessage-deflate; client_max_window_bits").await;
+    round_trip(&client, "permessage-deflate; server_no_context_takeover; \
+        client_no_context_takeover; server_max_window_bits=9; client_max_window_bits=10").await;
+}
+
+#[rocket::async_test]
+async fn messages_are_compressed_on_the_wire() {
+    let client = client().await;
+    let mut io = offer(client.get("/greet"), "permessage-deflate")
+        .header(Header::new("Connection", "Upgrade"))
+        .header(Header::new("Upgrade", "websocket"))
+        .header(Header::new("Sec-WebSocket-Version", "13"))
+        .header(Header::new("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ=="))
+        .upgrade().await
+        .expect("handshake");
+
+    // A final, compressed (RSV1) text frame much smaller than the message.
+    let mut header = [0u8; 2];
+    io.read_exact(&mut header).await.unwrap();
+    assert_eq!(header[0], 0x80 | 0x40 | 0x1);
+    assert!(header[1] < 126);
+}
+
+#[rocket::async_test]
+async fn uncompressed_without_offer() {
+    let client = client().await;
+    let mut stream = client.get("/echo").websocket().await.expect("handshake");
+    assert!(stream.get_ref().response().headers().get_one("Sec-WebSocket-Extensions").is_none());
+
+    let message = ws::Message::text("hello, world! ".repeat(4096));
+    stream.send(message.clone()).await.unwrap();
+    assert_eq!(stream.next().await.unwrap().unwrap(), message);
+}
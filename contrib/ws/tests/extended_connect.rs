@@ -0,0 +1,30 @@
+#[macro_use] extern crate rocket;
+
+use rocket::http::{Header, HttpVersion, Status};
+use rocket::local::asynchronous::Client;
+use rocket_ws as ws;
+
+#[get("/echo")]
+fn echo(ws: ws::WebSocket) -> ws::Stream!['static] {
+    ws.stream(|io| io)
+}
+
+#[rocket::async_test]
+async fn keyless_requests_require_extended_connect() {
+    let client = Client::tracked(rocket::build().mount("/", routes![echo])).await.unwrap();
+    for version in [HttpVersion::Http11, HttpVersion::Http2, HttpVersion::Http3] {
+        // Without a key, only extended `CONNECT` requests, which have a
+        // `:protocol`, are WebSocket handshakes. The local client can't make
+        // those, so this isn't one, regardless of the HTTP version.
+        let mut request = client.get("/echo")
+            .header(Header::new("Connection", "upgrade"))
+            .header(Header::new("Upgrade", "websocket"))
+            .header(Header::new("Sec-WebSocket-Version", "13"));
+
+        request.override_version(version);
+        assert_eq!(request.connect_protocol(), None);
+
+        let response = request.dispatch().await;
+        assert_eq!(response.status(), Status::BadRequest, "{version:?}");
+    }
+}
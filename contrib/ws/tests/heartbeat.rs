@@ -0,0 +1,198 @@
+#[macro_use] extern crate rocket;
+
+use std::time::Duration;
+
+use rocket::futures::{SinkExt, StreamExt, TryStreamExt};
+use rocket::futures::future::{pending, ready};
+use rocket::http::Header;
+use rocket::local::asynchronous::Client;
+use rocket::tokio::io::AsyncReadExt;
+use rocket::tokio::time::{sleep, timeout, Instant};
+use rocket_ws::{self as ws, HeartbeatConfig, Message};
+use rocket_ws::frame::CloseCode;
+use rocket_ws::local::{LocalWebSocket, WebSocketStream};
+
+fn heartbeat(ping: Option<u64>, pong: Option<u64>, idle: Option<u64>) -> HeartbeatConfig {
+    HeartbeatConfig {
+        ping_interval: ping.map(Duration::from_millis),
+        pong_timeout: pong.map(Duration::from_millis),
+        max_idle: idle.map(Duration::from_millis),
+    }
+}
+
+/// An echo server, for data messages, with the heartbeat configuration given
+/// in milliseconds.
+#[get("/echo?<ping>&<pong>&<idle>")]
+fn echo(ws: ws::WebSocket, ping: Option<u64>, pong: Option<u64>, idle: Option<u64>)
+    -> ws::Stream!['static]
+{
+    let ws = ws.heartbeat(heartbeat(ping, pong, idle));
+    ws.stream(|io| io.try_filter(|m| ready(m.is_text() || m.is_binary())))
+}
+
+/// Sends a message every 10ms, never reading from the stream.
+#[get("/sink?<ping>&<pong>&<idle>")]
+fn sink(ws: ws::WebSocket, ping: Option<u64>, pong: Option<u64>, idle: Option<u64>)
+    -> ws::Channel<'static>
+{
+    let ws = ws.heartbeat(heartbeat(ping, pong, idle));
+    ws.channel(|mut stream| Box::pin(async move {
+        for i in 0.. {
+            stream.send(Message::text(format!("tick {i}"))).await?;
+            sleep(Duration::from_millis(10)).await;
+        }
+
+        Ok(())
+    }))
+}
+
+/// Waits on a future that never resolves, never touching the stream.
+#[get("/blocked?<ping>&<pong>&<idle>")]
+fn blocked(ws: ws::WebSocket, ping: Option<u64>, pong: Option<u64>, idle: Option<u64>)
+    -> ws::Channel<'static>
+{
+    let ws = ws.heartbeat(heartbeat(ping, pong, idle));
+    ws.channel(|_stream| Box::pin(async move {
+        pending::<()>().await;
+        Ok(())
+    }))
+}
+
+async fn client() -> Client {
+    Client::tracked(rocket::build().mount("/", routes![echo, sink, blocked])).await.unwrap()
+}
+
+async fn recv(stream: &mut WebSocketStream<'_>) -> Message {
+    timeout(Duration::from_secs(5), stream.next()).await
+        .expect("message before timeout")
+        .expect("open stream")
+        .expect("valid message")
+}
+
+/// Receives messages, skipping heartbeat pings, until a close frame arrives.
+/// Returns its close code and the number of pings skipped.
+async fn recv_close(stream: &mut WebSocketStream<'_>) -> (CloseCode, usize) {
+    let mut pings = 0;
+    loop {
+        match recv(stream).await {
+            Message::Ping(_) => pings += 1,
+            Message::Close(Some(frame)) => return (frame.code, pings),
+            message => panic!("expected a ping or close frame, got {message:?}"),
+        }
+    }
+}
+
+#[rocket::async_test]
+async fn pings_are_sent_periodically() {
+    let client = client().await;
+    let mut stream = client.get("/echo?ping=20&pong=1000").websocket().await
+        .expect("handshake");
+    for _ in 0..3 {
+        assert!(matches!(recv(&mut stream).await, Message::Ping(_)));
+    }
+
+    // The client's automatic pongs keep the connection alive.
+    stream.send(Message::text("still here")).await.unwrap();
+    loop {
+        match recv(&mut stream).await {
+            Message::Ping(_) => continue,
+            message => break assert_eq!(message, Message::text("still here")),
+        }
+    }
+}
+
+#[rocket::async_test]
+async fn missed_pong_drops_connection() {
+    let client = client().await;
+    let mut io = client.get("/echo?ping=20&pong=20")
+        .header(Header::new("Connection", "Upgrade"))
+        .header(Header::new("Upgrade", "websocket"))
+        .header(Header::new("Sec-WebSocket-Version", "13"))
+        .header(Header::new("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ=="))
+        .upgrade()
+        .await
+        .expect("upgraded");
+
+    // Never answer the ping: the server drops the connection without a close.
+    let mut bytes = vec![];
+    timeout(Duration::from_secs(5), io.read_to_end(&mut bytes)).await
+        .expect("connection dropped before timeout")
+        .unwrap();
+
+    assert_eq!(bytes, [0x89, 0x00]);
+    assert!(io.is_finished());
+}
+
+#[rocket::async_test]
+async fn idle_connection_is_closed_despite_pongs() {
+    let client = client().await;
+    let mut stream = client.get("/echo?ping=20&pong=1000&idle=200").websocket().await
+        .expect("handshake");
+
+    let start = Instant::now();
+    let (code, pings) = recv_close(&mut stream).await;
+    assert_eq!(code, CloseCode::Normal);
+    assert!(pings > 0, "pongs to {pings} pings must not count as activity");
+    assert!(start.elapsed() >= Duration::from_millis(200));
+}
+
+#[rocket::async_test]
+async fn data_messages_reset_idle_timer() {
+    let client = client().await;
+    let mut stream = client.get("/echo?idle=300").websocket().await.expect("handshake");
+
+    // Stay active for longer than `max_idle` in total.
+    for i in 0..5 {
+        let message = Message::text(format!("message {i}"));
+        stream.send(message.clone()).await.unwrap();
+        assert_eq!(recv(&mut stream).await, message);
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    assert_eq!(recv_close(&mut stream).await, (CloseCode::Normal, 0));
+}
+
+#[rocket::async_test]
+async fn shutdown_closes_with_going_away() {
+    let client = client().await;
+    let mut stream = client.get("/echo").websocket().await.expect("handshake");
+    stream.send(Message::text("hi")).await.unwrap();
+    assert_eq!(recv(&mut stream).await, Message::text("hi"));
+
+    client.rocket().shutdown().notify();
+    assert_eq!(recv_close(&mut stream).await, (CloseCode::Away, 0));
+}
+
+#[rocket::async_test]
+async fn pongs_are_processed_without_reads() {
+    let client = client().await;
+    let mut stream = client.get("/sink?ping=20&pong=100").websocket().await
+        .expect("handshake");
+
+    // Many pong deadlines pass, all met, while the handler only writes.
+    let (mut ticks, mut pings) = (0, 0);
+    while ticks < 50 {
+        match recv(&mut stream).await {
+            Message::Text(_) => ticks += 1,
+            Message::Ping(_) => pings += 1,
+            message => panic!("expected a tick or ping, got {message:?}"),
+        }
+    }
+
+    assert!(pings > 5, "expected many pings, got {pings}");
+}
+
+#[rocket::async_test]
+async fn heartbeat_runs_while_handler_is_blocked() {
+    let client = client().await;
+    let mut stream = client.get("/blocked?ping=20&pong=1000&idle=200").websocket().await
+        .expect("handshake");
+
+    let (code, pings) = recv_close(&mut stream).await;
+    assert_eq!(code, CloseCode::Normal);
+    assert!(pings > 0, "expected pings while blocked");
+
+    let mut stream = client.get("/blocked").websocket().await.expect("handshake");
+    client.rocket().shutdown().notify();
+    assert_eq!(recv_close(&mut stream).await, (CloseCode::Away, 0));
+}
@@ -0,0 +1,139 @@
+#[macro_use] extern crate rocket;
+
+use std::time::Duration;
+
+use rocket::futures::{SinkExt, StreamExt};
+use rocket::local::asynchronous::Client;
+use rocket::tokio::time::timeout;
+use rocket_ws::{self as ws, Hub, Message, SlowConsumer};
+use rocket_ws::frame::CloseCode;
+use rocket_ws::local::{LocalWebSocket, WebSocketStream};
+
+#[get("/chat/<room>")]
+fn chat(ws: ws::WebSocket, hub: Hub, room: &str) -> ws::Channel<'_> {
+    ws.channel(move |stream| Box::pin(async move {
+        hub.join(room, stream).await
+    }))
+}
+
+async fn client(hub: Hub) -> Client {
+    Client::tracked(rocket::build().attach(hub).mount("/", routes![chat])).await.unwrap()
+}
+
+async fn connect<'c>(client: &'c Client, room: &str) -> WebSocketStream<'c> {
+    let mut stream = client.get(format!("/chat/{room}")).websocket().await.expect("handshake");
+    sync(&mut stream).await;
+    stream
+}
+
+async fn recv(stream: &mut WebSocketStream<'_>) -> Message {
+    timeout(Duration::from_secs(5), stream.next()).await
+        .expect("message before timeout")
+        .expect("open stream")
+        .expect("valid message")
+}
+
+/// The server only makes progress while the client is polled. Round-trips a
+/// ping so that the server has joined the room and handled all prior messages.
+async fn sync(stream: &mut WebSocketStream<'_>) {
+    stream.send(Message::Ping(b"sync".to_vec())).await.unwrap();
+    assert_eq!(recv(stream).await, Message::Pong(b"sync".to_vec()));
+}
+
+#[rocket::async_test]
+async fn messages_are_broadcast_to_room_members() {
+    let client = client(Hub::new()).await;
+    let hub = client.rocket().state::<Hub>().unwrap();
+
+    let mut alice = connect(&client, "lobby").await;
+    let mut bob = connect(&client, "lobby").await;
+    let mut carol = connect(&client, "attic").await;
+    assert_eq!(hub.presence("lobby"), 2);
+    assert_eq!(hub.presence("attic"), 1);
+
+    // Messages from a member go to everyone in the room, including itself.
+    alice.send(Message::text("hi")).await.unwrap();
+    assert_eq!(recv(&mut alice).await, Message::text("hi"));
+    assert_eq!(recv(&mut bob).await, Message::text("hi"));
+
+    // ...but not to other rooms.
+    assert_eq!(hub.broadcast("attic", "psst"), 1);
+    assert_eq!(recv(&mut carol).await, Message::text("psst"));
+
+    assert_eq!(hub.broadcast("lobby", "news"), 2);
+    assert_eq!(recv(&mut alice).await, Message::text("news"));
+    assert_eq!(recv(&mut bob).await, Message::text("news"));
+    assert_eq!(hub.broadcast("cellar", "anyone?"), 0);
+}
+
+#[rocket::async_test]
+async fn members_leave_when_disconnected() {
+    let client = client(Hub::new()).await;
+    let hub = client.rocket().state::<Hub>().unwrap();
+
+    let alice = connect(&client, "lobby").await;
+    let mut bob = connect(&client, "lobby").await;
+    assert_eq!(hub.rooms(), vec![("lobby".to_string(), 2)]);
+
+    // Closing the connection leaves the room.
+    bob.close(None).await.unwrap();
+    while let Ok(Some(Ok(_))) = timeout(Duration::from_secs(5), bob.next()).await {}
+    assert!(bob.get_ref().is_finished());
+    assert_eq!(hub.presence("lobby"), 1);
+
+    // As does dropping it. Empty rooms are removed.
+    drop(alice);
+    assert_eq!(hub.presence("lobby"), 0);
+    assert!(hub.rooms().is_empty());
+}
+
+#[rocket::async_test]
+async fn slow_members_miss_messages() {
+    let hub = Hub::new().capacity(1);
+    let mut fast = hub.subscribe("lobby");
+    let mut slow = hub.subscribe("lobby");
+
+    assert_eq!(hub.broadcast("lobby", "a"), 2);
+    assert_eq!(fast.recv().await, Some(Message::text("a")));
+
+    // `slow`'s queue is full: only it misses `b` and it remains a member.
+    assert_eq!(hub.broadcast("lobby", "b"), 1);
+    assert_eq!(slow.presence(), 2);
+    assert_eq!(fast.recv().await, Some(Message::text("b")));
+    assert_eq!(slow.recv().await, Some(Message::text("a")));
+
+    assert_eq!(fast.broadcast("c"), 2);
+    assert_eq!(fast.recv().await, Some(Message::text("c")));
+    assert_eq!(slow.recv().await, Some(Message::text("c")));
+}
+
+#[rocket::async_test]
+async fn slow_members_are_disconnected() {
+    let hub = Hub::new().capacity(1).slow_consumer(SlowConsumer::Disconnect);
+    let mut fast = hub.subscribe("lobby");
+    let mut slow = hub.subscribe("lobby");
+
+    assert_eq!(hub.broadcast("lobby", "a"), 2);
+    assert_eq!(fast.recv().await, Some(Message::text("a")));
+
+    // `slow`'s queue is full: it's removed but can drain its queue.
+    assert_eq!(hub.broadcast("lobby", "b"), 1);
+    assert_eq!(hub.presence("lobby"), 1);
+    assert_eq!(slow.recv().await, Some(Message::text("a")));
+    assert_eq!(slow.recv().await, None);
+    assert_eq!(fast.recv().await, Some(Message::text("b")));
+
+    // Over a connection, the slow client is closed with a policy violation.
+    let hub = Hub::new().capacity(1).slow_consumer(SlowConsumer::Disconnect);
+    let client = client(hub.clone()).await;
+    let mut stream = connect(&client, "lobby").await;
+
+    assert_eq!(hub.broadcast("lobby", "a"), 1);
+    assert_eq!(hub.broadcast("lobby", "b"), 0);
+    assert_eq!(hub.presence("lobby"), 0);
+    assert_eq!(recv(&mut stream).await, Message::text("a"));
+    match recv(&mut stream).await {
+        Message::Close(Some(frame)) => assert_eq!(frame.code, CloseCode::Policy),
+        message => panic!("expected a close frame, got {message:?}"),
+    }
+}
@@ -0,0 +1,76 @@
+#[macro_use] extern crate rocket;
+
+use rocket::http::{Header, Status};
+use rocket::local::asynchronous::{Client, LocalRequest};
+use rocket_ws::{self as ws, local::LocalWebSocket};
+
+#[get("/chat")]
+fn chat(ws: ws::WebSocket) -> ws::Stream!['static] {
+    ws.protocol(["v2.chat", "v1.chat"]).stream(|io| io)
+}
+
+#[get("/echo")]
+fn echo(ws: ws::WebSocket) -> ws::Stream!['static] {
+    ws.stream(|io| io)
+}
+
+async fn client() -> Client {
+    Client::tracked(rocket::build().mount("/", routes![chat, echo])).await.unwrap()
+}
+
+fn offer<'c>(request: LocalRequest<'c>, protocols: &str) -> LocalRequest<'c> {
+    request.header(Header::new("Sec-WebSocket-Protocol", protocols.to_string()))
+}
+
+#[rocket::async_test]
+async fn server_preferred_offered_protocol_is_selected() {
+    let client = client().await;
+    let stream = offer(client.get("/chat"), "v1.chat, v2.chat").websocket().await
+        .expect("handshake");
+
+    let response = stream.get_ref().response();
+    assert_eq!(response.status(), Status::SwitchingProtocols);
+    assert_eq!(response.headers().get_one("Sec-WebSocket-Protocol"), Some("v2.chat"));
+
+    let stream = offer(client.get("/chat"), "mqtt, v1.chat").websocket().await
+        .expect("handshake");
+
+    let response = stream.get_ref().response();
+    assert_eq!(response.headers().get_one("Sec-WebSocket-Protocol"), Some("v1.chat"));
+}
+
+#[rocket::async_test]
+async fn unsupported_protocols_are_rejected() {
+    let client = client().await;
+    let response = offer(client.get("/chat"), "mqtt, wamp").websocket().await
+        .err().expect("handshake should be rejected");
+
+    assert_eq!(response.status(), Status::BadRequest);
+    assert!(response.headers().get_one("Sec-WebSocket-Protocol").is_none());
+}
+
+#[rocket::async_test]
+async fn missing_offer_is_rejected_when_protocol_is_required() {
+    let client = client().await;
+    let response = client.get("/chat").websocket().await
+        .err().expect("handshake should be rejected");
+
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
+#[rocket::async_test]
+async fn offers_are_ignored_without_negotiation() {
+    let client = client().await;
+    let stream = client.get("/echo").websocket().await.expect("handshake");
+
+    let response = stream.get_ref().response();
+    assert_eq!(response.status(), Status::SwitchingProtocols);
+    assert!(response.headers().get_one("Sec-WebSocket-Protocol").is_none());
+
+    let stream = offer(client.get("/echo"), "v1.chat").websocket().await
+        .expect("handshake");
+
+    let response = stream.get_ref().response();
+    assert_eq!(response.status(), Status::SwitchingProtocols);
+    assert!(response.headers().get_one("Sec-WebSocket-Protocol").is_none());
+}
@@ -287,6 +287,7 @@ impl Status {
         100, "100", Continue => "Continue",
         101, "101", SwitchingProtocols => "Switching Protocols",
         102, "102", Processing => "Processing",
+        103, "103", EarlyHints => "Early Hints",
         200, "200", Ok => "OK",
         201, "201", Created => "Created",
         202, "202", Accepted => "Accepted",
@@ -131,6 +131,9 @@ pub use crate::tls::TlsConfig;
 #[cfg(feature = "mtls")]
 pub use crate::mtls::MtlsConfig;
 
+#[cfg(feature = "http3")]
+pub use crate::listener::quic::QuicConfig;
+
 #[cfg(feature = "secrets")]
 mod secret_key;
 
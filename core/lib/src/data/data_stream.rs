@@ -69,7 +69,7 @@ pub type RawReader<'r> = StreamReader<RawStream<'r>, Bytes>;
 pub enum RawStream<'r> {
     Empty,
     Body(HyperBody),
-    #[cfg(feature = "http3-preview")]
+    #[cfg(feature = "http3")]
     H3Body(crate::listener::Cancellable<crate::listener::quic::QuicRx>),
     Multipart(multer::Field<'r>),
 }
@@ -346,7 +346,7 @@ impl Stream for RawStream<'_> {
                     .map_ok(|frame| frame.into_data().unwrap_or_else(|_| Bytes::new()))
                     .map_err(io::Error::other)
             },
-            #[cfg(feature = "http3-preview")]
+            #[cfg(feature = "http3")]
             RawStream::H3Body(stream) => Pin::new(stream).poll_next(cx),
             RawStream::Multipart(s) => Pin::new(s).poll_next(cx).map_err(io::Error::other),
             RawStream::Empty => Poll::Ready(None),
@@ -360,7 +360,7 @@ impl Stream for RawStream<'_> {
                 let (lower, upper) = (hint.lower(), hint.upper());
                 (lower as usize, upper.map(|x| x as usize))
             },
-            #[cfg(feature = "http3-preview")]
+            #[cfg(feature = "http3")]
             RawStream::H3Body(_) => (0, Some(0)),
             RawStream::Multipart(mp) => mp.size_hint(),
             RawStream::Empty => (0, Some(0)),
@@ -373,20 +373,26 @@ impl std::fmt::Display for RawStream<'_> {
         match self {
             RawStream::Empty => f.write_str("empty stream"),
             RawStream::Body(_) => f.write_str("request body"),
-            #[cfg(feature = "http3-preview")]
+            #[cfg(feature = "http3")]
             RawStream::H3Body(_) => f.write_str("http3 quic stream"),
             RawStream::Multipart(_) => f.write_str("multipart form field"),
         }
     }
 }
 
+impl<'r> From<()> for RawStream<'r> {
+    fn from(_: ()) -> Self {
+        Self::Empty
+    }
+}
+
 impl<'r> From<HyperBody> for RawStream<'r> {
     fn from(value: HyperBody) -> Self {
         Self::Body(value)
     }
 }
 
-#[cfg(feature = "http3-preview")]
+#[cfg(feature = "http3")]
 impl<'r> From<crate::listener::Cancellable<crate::listener::quic::QuicRx>> for RawStream<'r> {
     fn from(value: crate::listener::Cancellable<crate::listener::quic::QuicRx>) -> Self {
         Self::H3Body(value)
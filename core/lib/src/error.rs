@@ -83,6 +83,10 @@ pub enum ErrorKind {
     ),
     /// Shutdown failed. Contains the Rocket instance that failed to shutdown.
     Shutdown(Arc<Rocket<Orbit>>),
+    /// The TLS configuration is invalid.
+    #[cfg(feature = "tls")]
+    #[cfg_attr(nightly, doc(cfg(feature = "tls")))]
+    Tls(crate::tls::Error),
 }
 
 /// An error that occurs when a value was unexpectedly empty.
@@ -232,6 +236,8 @@ impl StdError for Error {
             ErrorKind::SentinelAborts(_) => None,
             ErrorKind::Liftoff(_, e) => Some(e),
             ErrorKind::Shutdown(_) => None,
+            #[cfg(feature = "tls")]
+            ErrorKind::Tls(e) => Some(e),
         }
     }
 }
@@ -249,6 +255,8 @@ impl fmt::Display for ErrorKind {
             ErrorKind::SentinelAborts(_) => "sentinel(s) aborted".fmt(f),
             ErrorKind::Liftoff(_, _) => "liftoff failed".fmt(f),
             ErrorKind::Shutdown(_) => "shutdown failed".fmt(f),
+            #[cfg(feature = "tls")]
+            ErrorKind::Tls(e) => write!(f, "invalid tls configuration: {e}"),
         }
     }
 }
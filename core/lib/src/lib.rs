@@ -63,11 +63,12 @@
 //! | `secrets`       | No       | Support for authenticated, encrypted [private cookies]. |
 //! | `tls`           | No       | Support for [TLS] encrypted connections.                |
 //! | `mtls`          | No       | Support for verified clients via [mutual TLS].          |
+//! | `acme`          | No       | Automatic TLS certificates via [ACME].                  |
 //! | `json`          | No       | Support for [JSON (de)serialization].                   |
 //! | `msgpack`       | No       | Support for [MessagePack (de)serialization].            |
 //! | `uuid`          | No       | Support for [UUID value parsing and (de)serialization]. |
 //! | `tokio-macros`  | No       | Enables the `macros` feature in the exported `tokio`    |
-//! | `http3-preview` | No       | Experimental preview support for [HTTP/3].              |
+//! | `http3`         | No       | Support for [HTTP/3] over QUIC.                         |
 //!
 //! Disabled features can be selectively enabled in `Cargo.toml`:
 //!
@@ -90,6 +91,7 @@
 //! [private cookies]: https://rocket.rs/master/guide/requests/#private-cookies
 //! [TLS]: https://rocket.rs/master/guide/configuration/#tls
 //! [mutual TLS]: crate::mtls
+//! [ACME]: crate::tls::acme
 //! [HTTP/3]: crate::listener::quic
 //!
 //! ## Configuration
@@ -116,6 +116,15 @@ impl Rocket<Orbit> {
             Outcome::Error(status) => self.dispatch_error(status, request).await,
         };
 
+        // Add the early hints sent during dispatch that weren't written out.
+        let hints = request.take_early_hints();
+        if !hints.is_empty() {
+            let own = response.take_early_hints();
+            for hints in hints.into_iter().chain(own) {
+                response.add_early_hints(hints);
+            }
+        }
+
         // Set the cookies. Note that error responses will only include cookies
         // set by the error handler. See `handle_error` for more.
         let delta_jar = request.cookies().take_delta_jar();
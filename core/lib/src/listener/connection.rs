@@ -24,6 +24,11 @@ pub trait Connection: AsyncRead + AsyncWrite + Send + Unpin {
     fn certificates(&self) -> Option<Certificates<'_>> { None }
 
     fn server_name(&self) -> Option<&str> { None }
+
+    /// Parameters negotiated in the TLS handshake, if the connection is TLS.
+    #[cfg(feature = "tls")]
+    #[cfg_attr(nightly, doc(cfg(feature = "tls")))]
+    fn tls_info(&self) -> Option<crate::tls::TlsInfo> { None }
 }
 
 impl<A: Connection, B: Connection> Connection for Either<A, B> {
@@ -40,6 +45,21 @@ impl<A: Connection, B: Connection> Connection for Either<A, B> {
             Either::Right(c) => c.certificates(),
         }
     }
+
+    fn server_name(&self) -> Option<&str> {
+        match self {
+            Either::Left(c) => c.server_name(),
+            Either::Right(c) => c.server_name(),
+        }
+    }
+
+    #[cfg(feature = "tls")]
+    fn tls_info(&self) -> Option<crate::tls::TlsInfo> {
+        match self {
+            Either::Left(c) => c.tls_info(),
+            Either::Right(c) => c.tls_info(),
+        }
+    }
 }
 
 impl Certificates<'_> {
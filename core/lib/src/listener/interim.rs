@@ -0,0 +1,298 @@
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{ready, Context, Poll};
+use std::pin::Pin;
+
+use bytes::{Buf, BytesMut};
+use either::Either;
+use futures::future::{self, Future};
+use parking_lot::Mutex;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf, ReadHalf, WriteHalf};
+use tokio::sync::Notify;
+
+use crate::http::{HeaderMap, Status};
+use crate::util::FutureExt;
+
+/// HTTP/1 I/O that writes informational responses as soon as they're sent.
+///
+/// `hyper` doesn't support sending informational responses other than
+/// `100 Continue`. Instead, the service sends them via an [`InterimSender`],
+/// which queues them and, while the service is being
+/// [driven](InterimSender::drive()), writes them directly to the I/O. As
+/// `hyper` doesn't write to the connection while the service is pending, and
+/// any responses still queued are written before `hyper`'s next write, the
+/// informational responses always precede the final response.
+///
+/// Only the write half of the I/O is shared with the sender: reads never wait
+/// on it, and writes only lock the queue when it's non-empty.
+pub struct Interim<I> {
+    read: ReadHalf<I>,
+    shared: Arc<Shared<I>>,
+}
+
+struct Shared<I> {
+    write: Mutex<WriteHalf<I>>,
+    queue: Mutex<BytesMut>,
+    /// Whether `queue` is non-empty. Only changed with `queue` locked.
+    queued: AtomicBool,
+    notify: Notify,
+}
+
+/// A handle to send informational responses on an [`Interim`] connection.
+#[derive(Clone)]
+pub struct InterimSender(Arc<dyn Queue>);
+
+/// Type-erased access to the queue and I/O of an [`Interim`] connection.
+trait Queue: Send + Sync {
+    fn push(&self, bytes: &[u8]);
+
+    fn notify(&self) -> &Notify;
+
+    /// Writes and flushes all queued informational responses to the I/O.
+    fn poll_drain(&self, cx: &mut Context<'_>) -> Poll<io::Result<()>>;
+}
+
+pub trait InterimExt: AsyncRead + AsyncWrite + Send + Sized + 'static {
+    fn interim(self) -> (Interim<Self>, InterimSender) {
+        let (read, write) = tokio::io::split(self);
+        let shared = Arc::new(Shared {
+            write: Mutex::new(write),
+            queue: Mutex::new(BytesMut::new()),
+            queued: AtomicBool::new(false),
+            notify: Notify::new(),
+        });
+
+        (Interim { read, shared: shared.clone() }, InterimSender(shared))
+    }
+}
+
+impl<T: AsyncRead + AsyncWrite + Send + 'static> InterimExt for T { }
+
+impl<I: AsyncWrite + Send> Queue for Shared<I> {
+    fn push(&self, bytes: &[u8]) {
+        let mut queue = self.queue.lock();
+        queue.extend_from_slice(bytes);
+        self.queued.store(true, Ordering::Release);
+        drop(queue);
+        self.notify.notify_one();
+    }
+
+    fn notify(&self) -> &Notify {
+        &self.notify
+    }
+
+    fn poll_drain(&self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if !self.queued.load(Ordering::Acquire) {
+            return Poll::Ready(Ok(()));
+        }
+
+        let mut queue = self.queue.lock();
+        let mut io = self.write.lock();
+        while !queue.is_empty() {
+            let n = ready!(Pin::new(&mut *io).poll_write(cx, &queue))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+
+            queue.advance(n);
+        }
+
+        self.queued.store(false, Ordering::Release);
+        Pin::new(&mut *io).poll_flush(cx)
+    }
+}
+
+impl InterimSender {
+    /// Sends an HTTP/1.1 informational response with status `status` and
+    /// headers `headers`. Headers with invalid names or values are skipped.
+    ///
+    /// The response is written while a future is being
+    /// [driven](InterimSender::drive()) or, at the latest, immediately before
+    /// the final response.
+    pub fn send(&self, status: Status, headers: &HeaderMap<'_>) {
+        let mut head = BytesMut::new();
+        head.extend_from_slice(format!("HTTP/1.1 {}\r\n", status).as_bytes());
+        for header in headers.iter() {
+            let name = http::HeaderName::from_bytes(header.name().as_str().as_bytes());
+            let value = http::HeaderValue::from_str(header.value());
+            let (Ok(name), Ok(value)) = (name, value) else {
+                warn!(header = %header.name(), "skipping invalid informational header");
+                continue;
+            };
+
+            head.extend_from_slice(name.as_str().as_bytes());
+            head.extend_from_slice(b": ");
+            head.extend_from_slice(value.as_bytes());
+            head.extend_from_slice(b"\r\n");
+        }
+
+        head.extend_from_slice(b"\r\n");
+        self.0.push(&head);
+    }
+
+    /// Drives `future` to completion while writing informational responses to
+    /// the connection as they're sent.
+    pub async fn drive<F: Future>(&self, future: F) -> F::Output {
+        let flush = async {
+            loop {
+                if let Err(e) = future::poll_fn(|cx| self.0.poll_drain(cx)).await {
+                    warn!(error = %e, "failed to write informational response");
+                    return future::pending().await;
+                }
+
+                self.0.notify().notified().await;
+            }
+        };
+
+        match future.race(flush).await {
+            Either::Left(output) | Either::Right(output) => output,
+        }
+    }
+}
+
+impl fmt::Debug for InterimSender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InterimSender").finish_non_exhaustive()
+    }
+}
+
+impl<I: AsyncRead> AsyncRead for Interim<I> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.read).poll_read(cx, buf)
+    }
+}
+
+impl<I: AsyncWrite + Send> AsyncWrite for Interim<I> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.shared.poll_drain(cx))?;
+        Pin::new(&mut *self.shared.write.lock()).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.shared.poll_drain(cx))?;
+        Pin::new(&mut *self.shared.write.lock()).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.shared.poll_drain(cx))?;
+        Pin::new(&mut *self.shared.write.lock()).poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.shared.poll_drain(cx))?;
+        Pin::new(&mut *self.shared.write.lock()).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.shared.write.lock().is_write_vectored()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::sync::Arc;
+
+    use hyper::service::service_fn;
+    use hyper_util::rt::TokioIo;
+    use parking_lot::Mutex;
+    use tokio::io::{duplex, AsyncRead, AsyncReadExt, AsyncWriteExt};
+    use tokio::sync::oneshot;
+
+    use crate::http::{Header, HeaderMap, Status};
+    use super::InterimExt;
+
+    const HINT: &str = "HTTP/1.1 103 Early Hints\r\nlink: </style.css>; rel=preload\r\n\r\n";
+
+    fn hints() -> HeaderMap<'static> {
+        let mut hints = HeaderMap::new();
+        hints.add(Header::new("Link", "</style.css>; rel=preload"));
+        hints
+    }
+
+    async fn read_head<R: AsyncRead + Unpin>(io: &mut R) -> String {
+        let mut head = vec![];
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(io.read_u8().await.unwrap());
+        }
+
+        String::from_utf8(head).unwrap()
+    }
+
+    /// Serves one connection, via `hyper`, on `io` that responds to requests by
+    /// sending hints, then waiting for `ready`, if any, and then responding.
+    fn serve(io: tokio::io::DuplexStream, ready: Option<oneshot::Receiver<()>>) {
+        let (io, interim) = io.interim();
+        let ready = Arc::new(Mutex::new(ready));
+        let service = service_fn(move |_| {
+            let (interim, ready) = (interim.clone(), ready.lock().take());
+            async move {
+                interim.send(Status::EarlyHints, &hints());
+                if let Some(ready) = ready {
+                    interim.drive(ready).await.unwrap();
+                }
+
+                Ok::<_, Infallible>(hyper::Response::new(String::from("hi")))
+            }
+        });
+
+        let server = hyper::server::conn::http1::Builder::new();
+        tokio::spawn(server.serve_connection(TokioIo::new(io), service));
+    }
+
+    #[tokio::test]
+    async fn early_hints_are_written_before_response() {
+        let (mut client, server) = duplex(4096);
+        let (ready_tx, ready_rx) = oneshot::channel();
+        serve(server, Some(ready_rx));
+
+        client.write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\n\r\n").await.unwrap();
+
+        // The hints arrive while the service is still pending.
+        assert_eq!(read_head(&mut client).await, HINT);
+        ready_tx.send(()).unwrap();
+
+        let head = read_head(&mut client).await;
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n"), "{head}");
+    }
+
+    #[tokio::test]
+    async fn reads_do_not_wait_on_writes() {
+        let (mut client, server) = duplex(4096);
+        let (mut io, interim) = server.interim();
+        interim.send(Status::EarlyHints, &hints());
+
+        let shared = io.shared.clone();
+        let _write = shared.write.lock();
+        client.write_all(b"ping").await.unwrap();
+        let mut buf = [0; 4];
+        io.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+    }
+
+    #[tokio::test]
+    async fn queued_hints_are_written_before_response_head() {
+        let (mut client, server) = duplex(4096);
+        serve(server, None);
+
+        client.write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\n\r\n").await.unwrap();
+        assert_eq!(read_head(&mut client).await, HINT);
+
+        let head = read_head(&mut client).await;
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n"), "{head}");
+    }
+}
@@ -1,4 +1,5 @@
 mod cancellable;
+mod interim;
 mod bounced;
 mod listener;
 mod endpoint;
@@ -10,7 +11,7 @@ mod default;
 #[cfg_attr(nightly, doc(cfg(unix)))]
 pub mod unix;
 pub mod tcp;
-#[cfg(feature = "http3-preview")]
+#[cfg(feature = "http3")]
 pub mod quic;
 
 pub use endpoint::*;
@@ -20,4 +21,5 @@ pub use bind::*;
 pub use default::*;
 
 pub(crate) use cancellable::*;
+pub(crate) use interim::*;
 pub(crate) use bounced::*;
@@ -1,12 +1,12 @@
-//! Experimental support for Quic and HTTP/3.
+//! Support for QUIC and HTTP/3.
 //!
-//! To enable Rocket's experimental support for HTTP/3 and Quic, enable the
-//! `http3-preview` feature and provide a valid TLS configuration:
+//! To enable Rocket's support for HTTP/3 and QUIC, enable the `http3` feature
+//! and provide a valid TLS configuration:
 //!
 //! ```toml
 //! // Add the following to your Cargo.toml:
 //! [dependencies]
-//! rocket = { version = "0.6.0-dev", features = ["http3-preview"] }
+//! rocket = { version = "0.6.0-dev", features = ["http3"] }
 //!
 //! // In your Rocket.toml or other equivalent config source:
 //! [default.tls]
@@ -14,34 +14,170 @@
 //! key = "private/rsa_sha256_key.pem"
 //! ```
 //!
-//! The launch message confirms that Rocket is serving traffic over Quic in
+//! Rocket then serves HTTP/3 over QUIC on the same port, over UDP, as it serves
+//! HTTP/1 and HTTP/2 over TCP, advertising the former via the `Alt-Svc` header.
+//! The launch message confirms that Rocket is serving traffic over QUIC in
 //! addition to TCP:
 //!
 //! ```sh
-//! > 🚀 Rocket has launched on https://127.0.0.1:8000 (QUIC + mTLS)
+//! > 🚀 Rocket has launched on https://127.0.0.1:8000 (QUIC)
 //! ```
 //!
-//! mTLS is not yet supported via this implementation.
+//! The previous name of the feature, `http3-preview`, remains as an alias.
+//!
+//! # Configuration
+//!
+//! QUIC transport limits are configured via the `quic` configuration key. See
+//! [`QuicConfig`] for details.
+//!
+//! # Graceful Shutdown
+//!
+//! On [shutdown](crate::config::ShutdownConfig), Rocket stops accepting QUIC
+//! connections and sends an HTTP/3 `GOAWAY` to existing connections, refusing
+//! new requests while allowing in-flight requests the `grace` period to
+//! complete. Connections are closed once the `mercy` period elapses.
+//!
+//! # Upgrades
+//!
+//! Extended `CONNECT` requests for WebSockets ([RFC 9220]) are handled by the
+//! same [upgrade](crate::Response#upgrading) machinery as HTTP/1.1 upgrades.
+//! The request is presented to the application as a `GET` request with
+//! `Connection: upgrade` and `Upgrade: websocket` headers, and
+//! [`Request::connect_protocol()`](crate::Request::connect_protocol()) returns
+//! `websocket`. If the response upgrades the connection, a `200 OK` response is
+//! sent and the request stream becomes the
+//! [`IoStream`](crate::data::IoStream). As such, WebSocket routes, such as
+//! those implemented with `rocket_ws`, serve HTTP/3 clients unchanged.
+//!
+//! [RFC 9220]: https://datatracker.ietf.org/doc/html/rfc9220
+//!
+//! # mTLS
+//!
+//! Client certificates are not requested over QUIC, even when mTLS is
+//! configured: HTTP/3 requests never carry a client certificate, and the
+//! launch message for the QUIC endpoint omits `+ mTLS`. Since HTTP/3 would thus
+//! bypass client authentication, Rocket does not serve HTTP/3 when mTLS is
+//! configured as `mandatory`. Instead, it logs a warning and serves HTTP/1 and
+//! HTTP/2 only.
 
 use std::io;
 use std::fmt;
 use std::net::SocketAddr;
 use std::pin::pin;
+use std::time::Duration;
 
 use s2n_quic as quic;
 use s2n_quic_h3 as quic_h3;
 use quic_h3::h3 as h3;
 
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
+use figment::Figment;
 use futures::Stream;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
 use tokio::sync::Mutex;
 use tokio_stream::StreamExt;
 
 use crate::tls::{TlsConfig, Error};
 use crate::listener::Endpoint;
+use crate::request::ExtendedConnect;
 
 type H3Conn = h3::server::Connection<quic_h3::Connection, bytes::Bytes>;
 
+/// QUIC transport configuration.
+///
+/// Read from the `quic` configuration key when the `http3` feature is enabled.
+/// All values are optional and default as indicated below:
+///
+/// ```toml
+/// [default.quic]
+/// idle_timeout = 30
+/// max_bidi_streams = 100
+/// max_uni_streams = 100
+/// congestion_control = "cubic"
+/// ```
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::config::QuicConfig;
+/// use rocket::listener::quic::CongestionControl;
+///
+/// let config = QuicConfig {
+///     idle_timeout: 60,
+///     congestion_control: CongestionControl::Bbr,
+///     ..Default::default()
+/// };
+///
+/// let figment = rocket::Config::figment().merge(("quic", config));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuicConfig {
+    /// Seconds a connection may be idle before it is closed. The effective
+    /// timeout is the minimum of this value and the client's. `0` disables
+    /// the timeout. **(default: `30`)**
+    pub idle_timeout: u32,
+    /// Maximum number of concurrent bidirectional streams, and thus
+    /// concurrent requests, a client may open per connection.
+    /// **(default: `100`)**
+    pub max_bidi_streams: u64,
+    /// Maximum number of concurrent unidirectional streams a client may open
+    /// per connection. HTTP/3 clients require at least three.
+    /// **(default: `100`)**
+    pub max_uni_streams: u64,
+    /// The congestion control algorithm. **(default: `cubic`)**
+    pub congestion_control: CongestionControl,
+}
+
+/// A QUIC congestion control algorithm.
+///
+/// See [`QuicConfig::congestion_control`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CongestionControl {
+    /// [CUBIC](https://datatracker.ietf.org/doc/html/rfc9438).
+    #[default]
+    Cubic,
+    /// [BBRv2](https://datatracker.ietf.org/doc/html/draft-cardwell-iccrg-bbr-congestion-control).
+    Bbr,
+}
+
+impl Default for QuicConfig {
+    fn default() -> Self {
+        QuicConfig {
+            idle_timeout: 30,
+            max_bidi_streams: 100,
+            max_uni_streams: 100,
+            congestion_control: CongestionControl::Cubic,
+        }
+    }
+}
+
+impl QuicConfig {
+    /// Extracts the configuration from the `quic` key in `figment`, using the
+    /// defaults if the key is not present.
+    pub(crate) fn from_figment(figment: &Figment) -> Result<Self, figment::Error> {
+        #[derive(Deserialize)]
+        struct Config {
+            #[serde(default)]
+            quic: QuicConfig,
+        }
+
+        figment.extract::<Config>().map(|config| config.quic)
+    }
+
+    fn limits(&self) -> Result<quic::provider::limits::Limits, Error> {
+        let limits = quic::provider::limits::Limits::new()
+            .with_max_idle_timeout(Duration::from_secs(self.idle_timeout.into()))
+            .and_then(|l| l.with_max_open_remote_bidirectional_streams(self.max_bidi_streams))
+            .and_then(|l| l.with_max_open_remote_unidirectional_streams(self.max_uni_streams))
+            .map_err(|e| Error::Bind(Box::new(e)))?;
+
+        Ok(limits)
+    }
+}
+
 pub struct QuicListener {
     endpoint: SocketAddr,
     listener: Mutex<quic::Server>,
@@ -64,8 +200,17 @@ pub struct QuicRx(h3::server::RequestStream<quic_h3::RecvStream, Bytes>);
 pub struct QuicTx(h3::server::RequestStream<quic_h3::SendStream<Bytes>, Bytes>);
 
 impl QuicListener {
-    pub async fn bind(address: SocketAddr, tls: TlsConfig) -> Result<Self, Error> {
+    pub async fn bind(
+        address: SocketAddr,
+        tls: TlsConfig,
+        config: QuicConfig,
+    ) -> Result<Self, Error> {
         use quic::provider::tls::rustls::Server as H3TlsServer;
+        use quic::provider::congestion_controller::{Bbr, Cubic};
+
+        // Client certificates are never requested over QUIC.
+        #[cfg(feature = "mtls")]
+        let tls = TlsConfig { mutual: None, ..tls };
 
         let cert_chain = tls.load_certs()?
             .into_iter()
@@ -82,11 +227,21 @@ impl QuicListener {
             .build()
             .map_err(|e| Error::Bind(e))?;
 
-        let listener = quic::Server::builder()
+        let builder = quic::Server::builder()
             .with_tls(h3tls)?
             .with_io(address)?
-            .start()
-            .map_err(|e| Error::Bind(Box::new(e)))?;
+            .with_limits(config.limits()?)?;
+
+        let listener = match config.congestion_control {
+            CongestionControl::Cubic => builder
+                .with_congestion_controller(Cubic::default())?
+                .start(),
+            CongestionControl::Bbr => builder
+                .with_congestion_controller(Bbr::default())?
+                .start(),
+        };
+
+        let listener = listener.map_err(|e| Error::Bind(Box::new(e)))?;
 
         Ok(QuicListener {
             tls,
@@ -106,7 +261,12 @@ impl QuicListener {
     pub async fn connect(&self, accept: quic::Connection) -> io::Result<H3Stream> {
         let remote = accept.remote_addr();
         let quic_conn = quic_h3::Connection::new(accept);
-        let conn = H3Conn::new(quic_conn).await.map_err(io::Error::other)?;
+        let conn = h3::server::builder()
+            .enable_extended_connect(true)
+            .build(quic_conn)
+            .await
+            .map_err(io::Error::other)?;
+
         Ok(H3Stream(conn, remote))
     }
 
@@ -132,6 +292,12 @@ impl H3Stream {
 
         Ok(Some(H3Connection { remote, parts, tx: QuicTx(tx), rx: QuicRx(rx) }))
     }
+
+    /// Initiates a graceful shutdown of the connection by sending a `GOAWAY`.
+    /// Subsequent requests are refused while in-flight requests proceed.
+    pub async fn shutdown(&mut self) -> io::Result<()> {
+        self.0.shutdown(0).await.map_err(io::Error::other)
+    }
 }
 
 impl QuicTx {
@@ -151,16 +317,80 @@ impl QuicTx {
         self.0.finish().await.map_err(io::Error::other)
     }
 
+    /// Sends the head of `response`, which accepted an upgrade, as the
+    /// response to an extended `CONNECT`. The body is discarded.
+    pub async fn send_upgrade<S>(&mut self, response: http::Response<S>) -> io::Result<()> {
+        let (mut parts, _) = response.into_parts();
+        parts.status = http::StatusCode::OK;
+        parts.headers.remove(http::header::CONNECTION);
+        parts.headers.remove(http::header::UPGRADE);
+        let response = http::Response::from_parts(parts, ());
+        self.0.send_response(response).await.map_err(io::Error::other)
+    }
+
     pub fn cancel(&mut self) {
         self.0.stop_stream(h3::error::Code::H3_NO_ERROR);
     }
 }
 
+/// Forwards bytes between `io` and the request stream `(tx, rx)` until both
+/// directions have closed.
+pub async fn tunnel(mut tx: QuicTx, mut rx: QuicRx, io: DuplexStream) -> io::Result<()> {
+    let (mut reader, mut writer) = tokio::io::split(io);
+    let inbound = async move {
+        while let Some(bytes) = rx.next().await {
+            writer.write_all(&bytes?).await?;
+        }
+
+        writer.shutdown().await
+    };
+
+    let outbound = async move {
+        let mut buf = BytesMut::new();
+        loop {
+            buf.reserve(16 * 1024);
+            if reader.read_buf(&mut buf).await? == 0 {
+                break;
+            }
+
+            tx.0.send_data(buf.split().freeze()).await.map_err(io::Error::other)?;
+        }
+
+        tx.0.finish().await.map_err(io::Error::other)
+    };
+
+    futures::future::try_join(inbound, outbound).await?;
+    Ok(())
+}
+
 // FIXME: Expose certificates when possible.
 impl H3Connection {
     pub fn endpoint(&self) -> io::Result<Endpoint> {
         Ok(Endpoint::Quic(self.remote?).assume_tls())
     }
+
+    /// If this is an extended `CONNECT` request for a WebSocket (RFC 9220),
+    /// rewrites it as an HTTP/1.1-style upgrade request so that it's handled
+    /// by the regular upgrade machinery. Returns `true` if it was rewritten.
+    pub fn rewrite_extended_connect(&mut self) -> bool {
+        use http::header::{self, HeaderValue};
+
+        // This fails if `h3` doesn't support WebSockets over extended CONNECT.
+        let Ok(websocket) = "websocket".parse::<h3::ext::Protocol>() else {
+            return false;
+        };
+
+        let protocol = self.parts.extensions.get::<h3::ext::Protocol>();
+        if self.parts.method != http::Method::CONNECT || protocol != Some(&websocket) {
+            return false;
+        }
+
+        self.parts.method = http::Method::GET;
+        self.parts.headers.insert(header::CONNECTION, HeaderValue::from_static("upgrade"));
+        self.parts.headers.insert(header::UPGRADE, HeaderValue::from_static("websocket"));
+        self.parts.extensions.insert(ExtendedConnect("websocket".into()));
+        true
+    }
 }
 
 mod async_traits {
@@ -0,0 +1,126 @@
+use std::io;
+use std::future::poll_fn;
+use std::time::Duration;
+use std::{pin::Pin, task::{ready, Context, Poll}};
+
+use futures::Stream;
+use tokio::io::{AsyncRead, ReadBuf};
+
+use crate::response::stream::{Event, EventParser};
+use super::LocalResponse;
+
+/// A stream of Server-Sent [`Event`]s read from a [`LocalResponse`].
+///
+/// Returned by [`LocalResponse::into_events()`]. A `LocalEvents` is a
+/// [`Stream`] of `io::Result<Event>`: it parses the response body, in the
+/// `text/event-stream` format emitted by an
+/// [`EventStream`](crate::response::stream::EventStream), back into `Event`s as
+/// they're received. The stream ends when the response body does. Empty
+/// comments, such as those sent as heartbeats, are ignored.
+///
+/// To test infinite event streams, use [`LocalEvents::next_timeout()`], which
+/// fails if an event isn't received in time.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::local::asynchronous::Client;
+/// use rocket::response::stream::{Event, EventStream};
+/// use rocket::futures::StreamExt;
+/// use rocket::tokio::time::Duration;
+///
+/// #[get("/events")]
+/// fn events() -> EventStream![] {
+///     EventStream! {
+///         for i in 0.. {
+///             yield Event::data(i.to_string()).id(i.to_string());
+///         }
+///     }
+/// }
+///
+/// # rocket::async_test(async {
+/// let client = Client::tracked(rocket::build().mount("/", routes![events])).await.unwrap();
+/// let mut events = client.get("/events").dispatch().await.into_events();
+///
+/// let event = events.next().await.unwrap().unwrap();
+/// assert_eq!(event, Event::data("0").id("0"));
+///
+/// let timeout = Duration::from_secs(1);
+/// let event = events.next_timeout(timeout).await.unwrap();
+/// assert_eq!(event, Some(Event::data("1").id("1")));
+/// # });
+/// ```
+pub struct LocalEvents<'c> {
+    response: LocalResponse<'c>,
+    parser: EventParser,
+    done: bool,
+}
+
+impl<'c> LocalEvents<'c> {
+    pub(crate) fn new(response: LocalResponse<'c>) -> Self {
+        LocalEvents { response, parser: EventParser::default(), done: false }
+    }
+
+    /// Waits at most `timeout` for the next event. Returns `Ok(None)` if the
+    /// stream has ended and an error of kind [`io::ErrorKind::TimedOut`] if
+    /// no event was received in time.
+    ///
+    /// Partially received events are retained across calls, so this method can
+    /// be called again after a timeout to continue reading.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::asynchronous::LocalEvents;
+    /// use rocket::tokio::time::Duration;
+    ///
+    /// # async fn f(mut events: LocalEvents<'_>) {
+    /// let event = events.next_timeout(Duration::from_millis(500)).await;
+    /// # }
+    /// ```
+    pub async fn next_timeout(&mut self, timeout: Duration) -> io::Result<Option<Event>> {
+        let next = poll_fn(|cx| self.poll_next_event(cx));
+        match tokio::time::timeout(timeout, next).await {
+            Ok(result) => result,
+            Err(_) => Err(io::Error::new(io::ErrorKind::TimedOut, "timed out awaiting event")),
+        }
+    }
+
+    fn poll_next_event(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Option<Event>>> {
+        loop {
+            if let Some(event) = self.parser.next() {
+                return Poll::Ready(Ok(Some(event)));
+            }
+
+            if self.done {
+                return Poll::Ready(Ok(None));
+            }
+
+            let mut buf = [0; 1024];
+            let mut buf = ReadBuf::new(&mut buf);
+            ready!(Pin::new(&mut self.response).poll_read(cx, &mut buf))?;
+            match buf.filled() {
+                [] => self.done = true,
+                bytes => self.parser.feed(bytes),
+            }
+        }
+    }
+}
+
+impl Stream for LocalEvents<'_> {
+    type Item = io::Result<Event>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_next_event(cx).map(|result| result.transpose())
+    }
+}
+
+impl std::fmt::Debug for LocalEvents<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocalEvents")
+            .field("response", &self.response)
+            .field("done", &self.done)
+            .finish()
+    }
+}
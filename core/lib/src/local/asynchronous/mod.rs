@@ -7,9 +7,13 @@
 //! See the [top-level documentation](super) for more usage details.
 
 mod client;
+mod events;
 mod request;
 mod response;
+mod upgrade;
 
 pub use client::*;
+pub use events::*;
 pub use request::*;
 pub use response::*;
+pub use upgrade::*;
@@ -6,7 +6,7 @@ use crate::{Request, Data};
 use crate::http::{Status, Method};
 use crate::http::uri::Origin;
 
-use super::{Client, LocalResponse};
+use super::{Client, LocalResponse, LocalUpgrade};
 
 /// An `async` local request as returned by [`Client`](super::Client).
 ///
@@ -126,6 +126,38 @@ impl<'c> LocalRequest<'c> {
         response
     }
 
+    /// Dispatches the request and, if the response upgrades the connection
+    /// to the protocol named in the request's `Upgrade` header, returns the
+    /// client's end of the upgraded, in-memory connection. Otherwise, returns
+    /// the response as-is.
+    ///
+    /// This allows testing [`IoHandler`](crate::data::IoHandler)s, including
+    /// WebSocket routes, without a network connection. The request must
+    /// include an `Upgrade` header. For details and an example, see
+    /// [`LocalUpgrade`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::asynchronous::Client;
+    /// use rocket::http::{Header, Status};
+    ///
+    /// # rocket::async_test(async {
+    /// let client = Client::tracked(rocket::build()).await.unwrap();
+    /// let result = client.get("/")
+    ///     .header(Header::new("Upgrade", "raw-echo"))
+    ///     .upgrade()
+    ///     .await;
+    ///
+    /// // There's no route to upgrade, so we get the `404` response back.
+    /// let response = result.expect_err("no upgrade");
+    /// assert_eq!(response.status(), Status::NotFound);
+    /// # });
+    /// ```
+    pub async fn upgrade(self) -> Result<LocalUpgrade<'c>, LocalResponse<'c>> {
+        LocalUpgrade::new(self._dispatch().await)
+    }
+
     pub_request_impl!("# use rocket::local::asynchronous::Client;\n\
         use rocket::local::asynchronous::LocalRequest;" async await);
 }
@@ -5,7 +5,10 @@ use std::{pin::Pin, task::{Context, Poll}};
 use tokio::io::{AsyncRead, ReadBuf};
 
 use crate::http::CookieJar;
-use crate::{Request, Response};
+use crate::data::IoStream;
+use crate::{Request, Response, Rocket, Orbit};
+
+use super::LocalEvents;
 
 /// An `async` response from a dispatched [`LocalRequest`](super::LocalRequest).
 ///
@@ -108,6 +111,41 @@ impl<'c> LocalResponse<'c> {
     }
 }
 
+pub(crate) type IoFuture<'c> = Pin<Box<dyn Future<Output = io::Result<()>> + Send + 'c>>;
+
+impl<'c> LocalResponse<'c> {
+    /// Consumes `self` and returns a stream of the Server-Sent Events in its
+    /// body. For details and an example, see [`LocalEvents`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::asynchronous::LocalResponse;
+    ///
+    /// fn check(response: LocalResponse<'_>) {
+    ///     let events = response.into_events();
+    /// }
+    /// ```
+    pub fn into_events(self) -> LocalEvents<'c> {
+        LocalEvents::new(self)
+    }
+
+    /// Upgrades the response to the I/O handler matching the request's
+    /// `Upgrade` header, if any, returning the future running the handler on
+    /// `io`. As with the server, this sets the response's status to `101` and
+    /// its `Connection` and `Upgrade` headers.
+    ///
+    /// The future may refer to `_request` and so must be dropped before
+    /// `self`. `LocalUpgrade` ensures this via field order.
+    pub(crate) fn _upgrade(&mut self, io: IoStream) -> Option<IoFuture<'c>> {
+        // SAFETY: See `new()`. `request` has a stable address, and the only
+        // value borrowing from it, the future, is never exposed directly.
+        let request: &'c Request<'c> = unsafe { &*(&*self._request as *const _) };
+        let (_, handler) = Rocket::<Orbit>::extract_io_handler(request, &mut self.response)?;
+        Some(handler.io(io))
+    }
+}
+
 impl LocalResponse<'_> {
     pub(crate) fn _response(&self) -> &Response<'_> {
         &self.response
@@ -0,0 +1,187 @@
+use std::io;
+use std::{pin::Pin, task::{Context, Poll}};
+
+use tokio::io::{AsyncRead, AsyncWrite, DuplexStream, ReadBuf};
+
+use super::{LocalResponse, response::IoFuture};
+
+/// An upgraded, in-memory connection as returned by
+/// [`LocalRequest::upgrade()`](super::LocalRequest::upgrade()).
+///
+/// A `LocalUpgrade` is the client's end of a connection upgraded to a protocol
+/// handled by an [`IoHandler`](crate::data::IoHandler). It implements
+/// [`AsyncRead`] and [`AsyncWrite`]: bytes written are read by the I/O handler
+/// from its [`IoStream`](crate::data::IoStream), and bytes written by the
+/// handler are read from the `LocalUpgrade`.
+///
+/// The I/O handler makes progress only while the `LocalUpgrade` is being read
+/// from or written to. Once the handler returns, its end of the connection is
+/// closed, and reads from the `LocalUpgrade` return EOF. An error returned by
+/// the handler is logged.
+///
+/// The response that initiated the upgrade, with a status of `101 Switching
+/// Protocols`, can be retrieved via [`LocalUpgrade::response()`].
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::local::asynchronous::Client;
+/// use rocket::tokio::io::{AsyncReadExt, AsyncWriteExt};
+/// use rocket::http::{Header, Status};
+/// # use rocket::data::{IoHandler, IoStream};
+/// # use rocket::response::{self, Responder, Response};
+/// # use rocket::{Request, tokio::io};
+/// #
+/// # struct Echo;
+/// #
+/// # #[rocket::async_trait]
+/// # impl IoHandler for Echo {
+/// #     async fn io(self: Box<Self>, io: IoStream) -> io::Result<()> {
+/// #         let (mut reader, mut writer) = io::split(io);
+/// #         io::copy(&mut reader, &mut writer).await?;
+/// #         Ok(())
+/// #     }
+/// # }
+/// #
+/// # impl<'r> Responder<'r, 'static> for Echo {
+/// #     fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+/// #         Response::build().upgrade("raw-echo", self).ok()
+/// #     }
+/// # }
+///
+/// // A route whose responder upgrades to "raw-echo", echoing all bytes.
+/// #[get("/echo")]
+/// fn echo() -> Echo {
+///     Echo
+/// }
+///
+/// # rocket::async_test(async {
+/// let client = Client::tracked(rocket::build().mount("/", routes![echo])).await.unwrap();
+/// let mut io = client.get("/echo")
+///     .header(Header::new("Upgrade", "raw-echo"))
+///     .upgrade()
+///     .await
+///     .expect("upgraded");
+///
+/// assert_eq!(io.response().status(), Status::SwitchingProtocols);
+///
+/// io.write_all(b"hello").await.unwrap();
+/// let mut buf = [0; 5];
+/// io.read_exact(&mut buf).await.unwrap();
+/// assert_eq!(&buf, b"hello");
+/// # });
+/// ```
+pub struct LocalUpgrade<'c> {
+    // XXX: SAFETY: This (dependent) field must come first due to drop order!
+    handler: Option<IoFuture<'c>>,
+    io: DuplexStream,
+    response: LocalResponse<'c>,
+}
+
+impl<'c> LocalUpgrade<'c> {
+    /// The size, in bytes, of the buffer in each direction of the connection.
+    const BUFFER_SIZE: usize = 64 * 1024;
+
+    pub(crate) fn new(mut response: LocalResponse<'c>) -> Result<Self, LocalResponse<'c>> {
+        let (client, server) = tokio::io::duplex(Self::BUFFER_SIZE);
+        match response._upgrade(server.into()) {
+            Some(handler) => Ok(LocalUpgrade { handler: Some(handler), io: client, response }),
+            None => Err(response),
+        }
+    }
+
+    /// Returns the response that initiated the upgrade.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::asynchronous::LocalUpgrade;
+    /// use rocket::http::Status;
+    ///
+    /// fn check(io: &LocalUpgrade) {
+    ///     assert_eq!(io.response().status(), Status::SwitchingProtocols);
+    /// }
+    /// ```
+    pub fn response(&self) -> &LocalResponse<'c> {
+        &self.response
+    }
+
+    /// Returns `true` if the I/O handler has returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::asynchronous::LocalUpgrade;
+    ///
+    /// fn check(io: &LocalUpgrade) {
+    ///     let done = io.is_finished();
+    /// }
+    /// ```
+    pub fn is_finished(&self) -> bool {
+        self.handler.is_none()
+    }
+
+    /// Drives the I/O handler, if it's still running.
+    fn poll_handler(&mut self, cx: &mut Context<'_>) {
+        let Some(handler) = self.handler.as_mut() else {
+            return;
+        };
+
+        if let Poll::Ready(result) = handler.as_mut().poll(cx) {
+            if let Err(e) = result {
+                match e.kind() {
+                    io::ErrorKind::BrokenPipe => warn!("i/o handler closed"),
+                    _ => warn!(error = %e, "i/o handler terminated unsuccessfully"),
+                }
+            }
+
+            self.handler = None;
+        }
+    }
+}
+
+impl AsyncRead for LocalUpgrade<'_> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        this.poll_handler(cx);
+        Pin::new(&mut this.io).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for LocalUpgrade<'_> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        this.poll_handler(cx);
+        Pin::new(&mut this.io).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        this.poll_handler(cx);
+        Pin::new(&mut this.io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        this.poll_handler(cx);
+        Pin::new(&mut this.io).poll_shutdown(cx)
+    }
+}
+
+impl std::fmt::Debug for LocalUpgrade<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocalUpgrade")
+            .field("response", &self.response)
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
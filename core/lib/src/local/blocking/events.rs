@@ -0,0 +1,90 @@
+use std::io;
+use std::time::Duration;
+
+use futures::StreamExt;
+
+use crate::local::asynchronous;
+use crate::response::stream::Event;
+
+use super::Client;
+
+/// An iterator over the Server-Sent [`Event`]s read from a
+/// [`LocalResponse`](super::LocalResponse).
+///
+/// Returned by [`LocalResponse::into_events()`](super::LocalResponse::into_events()).
+/// A `LocalEvents` is an [`Iterator`] of `io::Result<Event>`: it parses the
+/// response body, in the `text/event-stream` format emitted by an
+/// [`EventStream`](crate::response::stream::EventStream), back into `Event`s as
+/// they're received. The iterator ends when the response body does. Empty
+/// comments, such as those sent as heartbeats, are ignored.
+///
+/// To test infinite event streams, use [`LocalEvents::next_timeout()`], which
+/// fails if an event isn't received in time.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::local::blocking::Client;
+/// use rocket::response::stream::{Event, EventStream};
+/// use rocket::tokio::time::Duration;
+///
+/// #[get("/events")]
+/// fn events() -> EventStream![] {
+///     EventStream! {
+///         for i in 0.. {
+///             yield Event::data(i.to_string()).id(i.to_string());
+///         }
+///     }
+/// }
+///
+/// let client = Client::tracked(rocket::build().mount("/", routes![events])).unwrap();
+/// let mut events = client.get("/events").dispatch().into_events();
+///
+/// let event = events.next().unwrap().unwrap();
+/// assert_eq!(event, Event::data("0").id("0"));
+///
+/// let event = events.next_timeout(Duration::from_secs(1)).unwrap();
+/// assert_eq!(event, Some(Event::data("1").id("1")));
+/// ```
+pub struct LocalEvents<'c> {
+    pub(in super) inner: asynchronous::LocalEvents<'c>,
+    pub(in super) client: &'c Client,
+}
+
+impl LocalEvents<'_> {
+    /// Waits at most `timeout` for the next event. Returns `Ok(None)` if the
+    /// stream has ended and an error of kind [`io::ErrorKind::TimedOut`] if
+    /// no event was received in time.
+    ///
+    /// Partially received events are retained across calls, so this method can
+    /// be called again after a timeout to continue reading.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::blocking::LocalEvents;
+    /// use rocket::tokio::time::Duration;
+    ///
+    /// # fn f(mut events: LocalEvents<'_>) {
+    /// let event = events.next_timeout(Duration::from_millis(500));
+    /// # }
+    /// ```
+    pub fn next_timeout(&mut self, timeout: Duration) -> io::Result<Option<Event>> {
+        self.client.block_on(self.inner.next_timeout(timeout))
+    }
+}
+
+impl Iterator for LocalEvents<'_> {
+    type Item = io::Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.client.block_on(self.inner.next())
+    }
+}
+
+impl std::fmt::Debug for LocalEvents<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inner.fmt(f)
+    }
+}
@@ -8,9 +8,11 @@
 //! See the [top-level documentation](super) for more usage details.
 
 mod client;
+mod events;
 mod request;
 mod response;
 
 pub use self::client::*;
+pub use self::events::*;
 pub use self::request::*;
 pub use self::response::*;
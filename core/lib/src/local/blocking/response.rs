@@ -3,7 +3,7 @@ use tokio::io::AsyncReadExt;
 
 use crate::{Response, local::asynchronous, http::CookieJar};
 
-use super::Client;
+use super::{Client, LocalEvents};
 
 /// A `blocking` response from a dispatched [`LocalRequest`](super::LocalRequest).
 ///
@@ -54,6 +54,24 @@ pub struct LocalResponse<'c> {
     pub(in super) client: &'c Client,
 }
 
+impl<'c> LocalResponse<'c> {
+    /// Consumes `self` and returns an iterator over the Server-Sent Events in
+    /// its body. For details and an example, see [`LocalEvents`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::blocking::LocalResponse;
+    ///
+    /// fn check(response: LocalResponse<'_>) {
+    ///     let events = response.into_events();
+    /// }
+    /// ```
+    pub fn into_events(self) -> LocalEvents<'c> {
+        LocalEvents { inner: self.inner.into_events(), client: self.client }
+    }
+}
+
 impl LocalResponse<'_> {
     fn _response(&self) -> &Response<'_> {
         self.inner._response()
@@ -37,6 +37,9 @@ macro_rules! pub_response_impl {
     getter_method!($doc_prelude, "HTTP headers",
         headers -> &crate::http::HeaderMap<'_>);
 
+    getter_method!($doc_prelude, "`103 Early Hints` header sets",
+        early_hints -> impl Iterator<Item = &crate::http::HeaderMap<'_>>);
+
     /// Return a cookie jar containing the HTTP cookies in the response.
     ///
     /// # Example
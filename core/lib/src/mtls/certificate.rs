@@ -1,6 +1,6 @@
 use ref_cast::RefCast;
 
-use crate::mtls::{x509, oid, bigint, Name, Result, Error};
+use crate::mtls::{x509, oid, bigint, san, Name, SubjectAltName, Result, Error};
 use crate::request::{Request, FromRequest, Outcome};
 use crate::http::Status;
 
@@ -271,6 +271,173 @@ impl<'a> Certificate<'a> {
         self.inner().extensions()
     }
 
+    /// Returns an iterator over the DNS, URI, email, and IP subject alternative
+    /// names in the X.509 certificate.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::mtls::{Certificate, SubjectAltName};
+    ///
+    /// #[get("/auth")]
+    /// fn auth(cert: Certificate<'_>) {
+    ///     let hosts = cert.subject_alt_names()
+    ///         .filter_map(|name| match name {
+    ///             SubjectAltName::Dns(host) => Some(host),
+    ///             _ => None,
+    ///         });
+    ///
+    ///     for host in hosts {
+    ///         println!("certified for {host}");
+    ///     }
+    /// }
+    /// ```
+    pub fn subject_alt_names<'b>(&'b self) -> impl Iterator<Item = SubjectAltName<'a>> + 'b
+        where 'a: 'b
+    {
+        san::subject_alt_names(&self.x509)
+    }
+
+    /// Returns the first URI subject alternative name that is a SPIFFE ID,
+    /// that is, begins with `spiffe://`, if any.
+    ///
+    /// See [`SpiffeId`](crate::mtls::SpiffeId) for a request guard that
+    /// requires a SPIFFE ID matching a pattern.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::mtls::Certificate;
+    ///
+    /// #[get("/auth")]
+    /// fn auth(cert: Certificate<'_>) {
+    ///     if let Some(id) = cert.spiffe_id() {
+    ///         println!("workload: {id}");
+    ///     }
+    /// }
+    /// ```
+    pub fn spiffe_id(&self) -> Option<&'a str> {
+        self.subject_alt_names().find_map(|name| match name {
+            SubjectAltName::Uri(uri) if uri.starts_with("spiffe://") => Some(uri),
+            _ => None,
+        })
+    }
+
+    /// Returns the key usage extension of the X.509 certificate, if it is
+    /// present and valid.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::mtls::Certificate;
+    ///
+    /// #[get("/auth")]
+    /// fn auth(cert: Certificate<'_>) {
+    ///     if cert.key_usage().map_or(false, |usage| usage.digital_signature()) {
+    ///         println!("key may be used for digital signatures");
+    ///     }
+    /// }
+    /// ```
+    pub fn key_usage(&self) -> Option<&x509::KeyUsage> {
+        self.x509.key_usage().ok().flatten().map(|ext| ext.value)
+    }
+
+    /// Returns the extended key usage extension of the X.509 certificate, if it
+    /// is present and valid.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::mtls::Certificate;
+    ///
+    /// #[get("/auth")]
+    /// fn auth(cert: Certificate<'_>) {
+    ///     if cert.extended_key_usage().map_or(false, |usage| usage.client_auth) {
+    ///         println!("certificate is meant for client authentication");
+    ///     }
+    /// }
+    /// ```
+    pub fn extended_key_usage(&self) -> Option<&x509::ExtendedKeyUsage<'_>> {
+        self.x509.extended_key_usage().ok().flatten().map(|ext| ext.value)
+    }
+
+    /// Returns the time before which the X.509 certificate is not valid.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::mtls::Certificate;
+    ///
+    /// #[get("/auth")]
+    /// fn auth(cert: Certificate<'_>) {
+    ///     println!("valid since {}", cert.not_before());
+    /// }
+    /// ```
+    pub fn not_before(&self) -> time::OffsetDateTime {
+        self.inner().validity.not_before.to_datetime()
+    }
+
+    /// Returns the time after which the X.509 certificate is not valid.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::mtls::Certificate;
+    /// use rocket::time::{Duration, OffsetDateTime};
+    ///
+    /// #[get("/auth")]
+    /// fn auth(cert: Certificate<'_>) {
+    ///     if cert.not_after() - OffsetDateTime::now_utc() < Duration::days(7) {
+    ///         println!("certificate expires within a week");
+    ///     }
+    /// }
+    /// ```
+    pub fn not_after(&self) -> time::OffsetDateTime {
+        self.inner().validity.not_after.to_datetime()
+    }
+
+    /// Returns the SHA-256 fingerprint of the X.509 certificate: the SHA-256
+    /// digest of its DER encoding.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # use rocket::get;
+    /// use rocket::mtls::Certificate;
+    ///
+    /// const SHA256_FINGERPRINT: [u8; 32] = [
+    ///     0xCE, 0xC2, 0x4E, 0x01, 0x00, 0xFF, 0xF7, 0x78,
+    ///     0xCB, 0xA4, 0xAA, 0xCB, 0xD2, 0x49, 0xDD, 0x09,
+    ///     0x02, 0xEF, 0x0E, 0x9B, 0xDA, 0x89, 0x2A, 0xE4,
+    ///     0x0D, 0xF4, 0x09, 0x83, 0x97, 0xC1, 0x97, 0x0D,
+    /// ];
+    ///
+    /// #[get("/auth")]
+    /// fn auth(cert: Certificate<'_>) {
+    ///     if cert.sha256_fingerprint() == SHA256_FINGERPRINT {
+    ///         println!("certificate fingerprint matched");
+    ///     }
+    /// }
+    /// ```
+    pub fn sha256_fingerprint(&self) -> [u8; 32] {
+        use sha2::Digest;
+
+        sha2::Sha256::digest(self.as_bytes()).into()
+    }
+
     /// Checks if the certificate has the serial number `number`.
     ///
     /// If `number` is not a valid unsigned integer in base 10, returns `None`.
@@ -306,16 +473,10 @@ impl<'a> Certificate<'a> {
     /// # use rocket::get;
     /// use rocket::mtls::Certificate;
     ///
-    /// const SHA256_FINGERPRINT: &str =
-    ///     "CE C2 4E 01 00 FF F7 78 CB A4 AA CB D2 49 DD 09 \
-    ///      02 EF 0E 9B DA 89 2A E4 0D F4 09 83 97 C1 97 0D";
-    ///
     /// #[get("/auth")]
     /// fn auth(cert: Certificate<'_>) {
-    ///     # fn sha256_fingerprint(bytes: &[u8]) -> String { todo!() }
-    ///     if sha256_fingerprint(cert.as_bytes()) == SHA256_FINGERPRINT {
-    ///         println!("certificate fingerprint matched");
-    ///     }
+    ///     let der: &[u8] = cert.as_bytes();
+    ///     println!("certificate is {} bytes", der.len());
     /// }
     /// ```
     pub fn as_bytes(&self) -> &'a [u8] {
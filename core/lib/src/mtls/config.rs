@@ -1,7 +1,9 @@
 use std::io;
+use std::time::SystemTime;
 
 use figment::value::magic::{RelativePathBuf, Either};
 use serde::{Serialize, Deserialize};
+use rustls::pki_types::CertificateRevocationListDer;
 
 use crate::tls::{Result, Error};
 
@@ -34,12 +36,62 @@ use crate::tls::{Result, Error};
 ///     either case, if a certificate _is_ presented, it must be valid or the
 ///     connection is terminated.
 ///
+/// Several optional parameters further restrict which client certificates are
+/// accepted:
+///
+///   * `crls`
+///
+///     An array of paths to PEM files with, or raw bytes for, DER-encoded
+///     certificate revocation lists. Certificates revoked by any list are
+///     rejected. Lists configured as paths are reloaded when modified.
+///
+///     When any list is configured, the revocation status of _every_
+///     certificate in the presented chain, not just the end-entity
+///     certificate, is checked. A certificate whose status is unknown because
+///     no configured list was issued by its issuer is rejected. As such, a list
+///     must be provided for every certificate authority in accepted chains.
+///
+///   * `crl_refresh`
+///
+///     How often, in seconds, to check CRL files for modifications. Defaults to
+///     `60`. A value of `0` disables reloading. Modified lists are reloaded in
+///     the background; handshakes use the previously loaded lists until the
+///     reload completes.
+///
+///   * `allowed_sans`
+///
+///     An array of subject alternative names (DNS names, URIs such as SPIFFE
+///     IDs, emails, or IP addresses) of which the client certificate must
+///     contain at least one. A name ending in `*` matches any name with the
+///     preceding prefix. DNS names are compared ignoring ASCII case; all other
+///     names are compared exactly. When empty, the default, any name is
+///     allowed.
+///
+///   * `allowed_issuers`
+///
+///     An array of issuer distinguished names, such as `"C=US, O=Rocket CA,
+///     CN=Rocket Root CA"`, or issuer common names, such as `"Rocket Root
+///     CA"`, one of which must match the issuer of the client certificate.
+///     When empty, the default, any issuer is allowed.
+///
+///   * `max_depth`
+///
+///     The maximum number of certificates, including the client's own, in the
+///     chain presented by the client. Unlimited by default.
+///
+/// Clients whose certificates fail verification are sent a descriptive TLS
+/// alert, such as `certificate_revoked` or `access_denied`, and the reason is
+/// logged.
+///
 /// In a `Rocket.toml`, configuration might look like:
 ///
 /// ```toml
 /// [default.tls.mutual]
 /// ca_certs = "/ssl/ca_cert.pem"
 /// mandatory = true                # when absent, defaults to false
+/// crls = ["/ssl/revoked.crl.pem"]
+/// allowed_sans = ["spiffe://example.org/ns/prod/*"]
+/// max_depth = 3
 /// ```
 ///
 /// Programmatically, configuration might look like:
@@ -74,6 +126,25 @@ pub struct MtlsConfig {
     #[serde(default)]
     #[serde(deserialize_with = "figment::util::bool_from_str_or_int")]
     pub mandatory: bool,
+    /// Paths to PEM files with, or raw bytes for, DER-encoded certificate
+    /// revocation lists.
+    #[serde(default)]
+    pub(crate) crls: Vec<Either<RelativePathBuf, Vec<u8>>>,
+    /// How often, in seconds, to check CRL files for modifications. `0`
+    /// disables reloading.
+    #[serde(default = "MtlsConfig::default_crl_refresh")]
+    pub crl_refresh: u64,
+    /// Subject alternative names, one of which the client certificate must
+    /// contain. Empty allows any.
+    #[serde(default)]
+    pub allowed_sans: Vec<String>,
+    /// Issuer names, one of which must have issued the client certificate.
+    /// Empty allows any.
+    #[serde(default)]
+    pub allowed_issuers: Vec<String>,
+    /// The maximum length of the client's certificate chain.
+    #[serde(default)]
+    pub max_depth: Option<usize>,
 }
 
 impl MtlsConfig {
@@ -93,10 +164,7 @@ impl MtlsConfig {
     /// let tls_config = MtlsConfig::from_path("/ssl/ca_certs.pem");
     /// ```
     pub fn from_path<C: AsRef<std::path::Path>>(ca_certs: C) -> Self {
-        MtlsConfig {
-            ca_certs: Either::Left(ca_certs.as_ref().to_path_buf().into()),
-            mandatory: Default::default()
-        }
+        Self::new(Either::Left(ca_certs.as_ref().to_path_buf().into()))
     }
 
     /// Constructs a `MtlsConfig` from a byte buffer to a certificate authority
@@ -115,12 +183,25 @@ impl MtlsConfig {
     /// let mtls_config = MtlsConfig::from_bytes(ca_certs_buf);
     /// ```
     pub fn from_bytes(ca_certs: &[u8]) -> Self {
+        Self::new(Either::Right(ca_certs.to_vec()))
+    }
+
+    fn new(ca_certs: Either<RelativePathBuf, Vec<u8>>) -> Self {
         MtlsConfig {
-            ca_certs: Either::Right(ca_certs.to_vec()),
-            mandatory: Default::default()
+            ca_certs,
+            mandatory: Default::default(),
+            crls: vec![],
+            crl_refresh: Self::default_crl_refresh(),
+            allowed_sans: vec![],
+            allowed_issuers: vec![],
+            max_depth: None,
         }
     }
 
+    fn default_crl_refresh() -> u64 {
+        60
+    }
+
     /// Sets whether client authentication is required. Disabled by default.
     ///
     /// When `true`, client authentication will be required. TLS connections
@@ -142,6 +223,91 @@ impl MtlsConfig {
         self
     }
 
+    /// Adds a path to a PEM file with a DER-encoded certificate revocation
+    /// list. Certificates revoked by the list are rejected. The file is
+    /// reloaded when modified.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::mtls::MtlsConfig;
+    ///
+    /// let mtls_config = MtlsConfig::from_path("/ssl/ca_cert.pem")
+    ///     .with_crl("/ssl/revoked.crl.pem");
+    /// ```
+    pub fn with_crl<C: AsRef<std::path::Path>>(mut self, crl: C) -> Self {
+        self.crls.push(Either::Left(crl.as_ref().to_path_buf().into()));
+        self
+    }
+
+    /// Adds raw bytes to a DER-encoded certificate revocation list.
+    /// Certificates revoked by the list are rejected.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::mtls::MtlsConfig;
+    ///
+    /// # let ca_certs_buf = &[];
+    /// # let crl_buf = &[];
+    /// let mtls_config = MtlsConfig::from_bytes(ca_certs_buf).with_crl_bytes(crl_buf);
+    /// ```
+    pub fn with_crl_bytes(mut self, crl: &[u8]) -> Self {
+        self.crls.push(Either::Right(crl.to_vec()));
+        self
+    }
+
+    /// Requires client certificates to contain the subject alternative name
+    /// `name`, or, if any names were previously allowed, any one of them. A
+    /// `name` ending in `*` matches any name with the preceding prefix.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::mtls::MtlsConfig;
+    ///
+    /// let mtls_config = MtlsConfig::from_path("/ssl/ca_cert.pem")
+    ///     .with_allowed_san("spiffe://example.org/ns/prod/*")
+    ///     .with_allowed_san("admin.example.org");
+    /// ```
+    pub fn with_allowed_san<S: Into<String>>(mut self, name: S) -> Self {
+        self.allowed_sans.push(name.into());
+        self
+    }
+
+    /// Requires client certificates to be issued by `issuer`, or, if any
+    /// issuers were previously allowed, any one of them. `issuer` is either a
+    /// distinguished name or a common name.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::mtls::MtlsConfig;
+    ///
+    /// let mtls_config = MtlsConfig::from_path("/ssl/ca_cert.pem")
+    ///     .with_allowed_issuer("Rocket Root CA");
+    /// ```
+    pub fn with_allowed_issuer<S: Into<String>>(mut self, issuer: S) -> Self {
+        self.allowed_issuers.push(issuer.into());
+        self
+    }
+
+    /// Limits the client's certificate chain to `depth` certificates,
+    /// including the client's own.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::mtls::MtlsConfig;
+    ///
+    /// let mtls_config = MtlsConfig::from_path("/ssl/ca_cert.pem").with_max_depth(2);
+    /// assert_eq!(mtls_config.max_depth, Some(2));
+    /// ```
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
     /// Returns the value of the `ca_certs` parameter.
     ///
     /// # Example
@@ -174,6 +340,30 @@ impl MtlsConfig {
 
         Ok(roots)
     }
+
+    /// Load and decode all configured certificate revocation lists.
+    pub(crate) fn load_crls(&self) -> Result<Vec<CertificateRevocationListDer<'static>>> {
+        let mut crls = vec![];
+        for crl in &self.crls {
+            let mut reader = crate::tls::config::to_reader(crl)?;
+            for crl in rustls_pemfile::crls(&mut reader) {
+                crls.push(crl.map_err(Error::Crl)?);
+            }
+        }
+
+        Ok(crls)
+    }
+
+    /// Returns the last modification time of each CRL configured as a path.
+    pub(crate) fn crl_modification_times(&self) -> Vec<Option<SystemTime>> {
+        self.crls.iter()
+            .filter_map(|crl| match crl {
+                Either::Left(path) => Some(path),
+                Either::Right(_) => None,
+            })
+            .map(|path| std::fs::metadata(path.relative()).and_then(|m| m.modified()).ok())
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -217,6 +407,28 @@ mod tests {
 
             let mtls: MtlsConfig = figment().extract()?;
             assert_eq!(mtls.ca_certs().unwrap_left(), jail.directory().join("relative/ca.pem"));
+            assert!(mtls.crls.is_empty() && mtls.allowed_sans.is_empty());
+            assert_eq!(mtls.crl_refresh, 60);
+            assert_eq!(mtls.max_depth, None);
+
+            jail.create_file("MTLS.toml", r#"
+                ca_certs = "/ssl/ca.pem"
+                crls = ["/ssl/revoked.pem"]
+                crl_refresh = 0
+                allowed_sans = ["spiffe://example.org/*"]
+                allowed_issuers = ["Rocket Root CA"]
+                max_depth = 2
+            "#)?;
+
+            let mtls: MtlsConfig = figment().extract()?;
+            let mut expected = MtlsConfig::from_path("/ssl/ca.pem")
+                .with_crl("/ssl/revoked.pem")
+                .with_allowed_san("spiffe://example.org/*")
+                .with_allowed_issuer("Rocket Root CA")
+                .with_max_depth(2);
+
+            expected.crl_refresh = 0;
+            assert_eq!(mtls, expected);
 
             Ok(())
         });
@@ -39,6 +39,10 @@ pub enum Error {
     Incomplete(Option<NonZeroUsize>),
     /// The certificate contained `.0` bytes of trailing data.
     Trailing(usize),
+    /// The certificate contained no SPIFFE ID.
+    NoSpiffeId,
+    /// The certificate's SPIFFE ID `.0` didn't match the required pattern.
+    SpiffeIdMismatch(String),
 }
 
 impl fmt::Display for Error {
@@ -50,6 +54,8 @@ impl fmt::Display for Error {
             Error::Empty => write!(f, "empty certificate chain"),
             Error::NoSubject => write!(f, "empty subject without subjectAlt"),
             Error::NonCriticalSubjectAlt => write!(f, "empty subject without critical subjectAlt"),
+            Error::NoSpiffeId => write!(f, "certificate has no SPIFFE ID"),
+            Error::SpiffeIdMismatch(id) => write!(f, "SPIFFE ID {} is not allowed", id),
         }
     }
 }
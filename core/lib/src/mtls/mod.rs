@@ -34,11 +34,18 @@ mod certificate;
 mod error;
 mod name;
 mod config;
+mod verifier;
+mod san;
+mod spiffe;
 
 pub use error::Error;
 pub use name::Name;
 pub use config::MtlsConfig;
 pub use certificate::{Certificate, CertificateDer};
+pub use san::SubjectAltName;
+pub use spiffe::{SpiffeId, SpiffePattern, AnySpiffeId};
+
+pub(crate) use verifier::ClientVerifier;
 
 /// A type alias for `Result` with the error type set to [`Error`].
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -0,0 +1,140 @@
+use std::fmt;
+use std::net::IpAddr;
+
+use crate::mtls::x509;
+
+/// A subject alternative name (SAN) found in a
+/// [`Certificate`](crate::mtls::Certificate).
+///
+/// Only the most common kinds of names are represented. Use
+/// [`Certificate::extensions()`](crate::mtls::Certificate::extensions()) to
+/// access others.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::mtls::{Certificate, SubjectAltName};
+///
+/// #[get("/auth")]
+/// fn auth(cert: Certificate<'_>) {
+///     for name in cert.subject_alt_names() {
+///         match name {
+///             SubjectAltName::Dns(host) => println!("host: {host}"),
+///             SubjectAltName::Uri(uri) => println!("uri: {uri}"),
+///             SubjectAltName::Email(email) => println!("email: {email}"),
+///             SubjectAltName::Ip(ip) => println!("ip: {ip}"),
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubjectAltName<'a> {
+    /// A DNS name, such as `example.com`.
+    Dns(&'a str),
+    /// A URI, such as the SPIFFE ID `spiffe://example.org/ns/prod/sa/web`.
+    Uri(&'a str),
+    /// An RFC 822 email address, such as `admin@example.com`.
+    Email(&'a str),
+    /// An IPv4 or IPv6 address.
+    Ip(IpAddr),
+}
+
+impl<'a> SubjectAltName<'a> {
+    /// Returns the string value of `self` if it is a DNS name, URI, or email.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::mtls::SubjectAltName;
+    ///
+    /// assert_eq!(SubjectAltName::Dns("rocket.rs").as_str(), Some("rocket.rs"));
+    /// assert_eq!(SubjectAltName::Ip([127, 0, 0, 1].into()).as_str(), None);
+    /// ```
+    pub fn as_str(&self) -> Option<&'a str> {
+        match *self {
+            SubjectAltName::Dns(s) | SubjectAltName::Uri(s) | SubjectAltName::Email(s) => Some(s),
+            SubjectAltName::Ip(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for SubjectAltName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubjectAltName::Dns(s) | SubjectAltName::Uri(s) | SubjectAltName::Email(s) => {
+                s.fmt(f)
+            }
+            SubjectAltName::Ip(ip) => ip.fmt(f),
+        }
+    }
+}
+
+/// Returns an iterator over the supported subject alternative names in `x509`.
+pub(crate) fn subject_alt_names<'a: 'b, 'b>(
+    x509: &'b x509::X509Certificate<'a>
+) -> impl Iterator<Item = SubjectAltName<'a>> + 'b {
+    let names = match x509.subject_alternative_name() {
+        Ok(Some(ext)) => &ext.value.general_names[..],
+        _ => &[],
+    };
+
+    names.iter().filter_map(|name| match *name {
+        x509::GeneralName::DNSName(s) => Some(SubjectAltName::Dns(s)),
+        x509::GeneralName::URI(s) => Some(SubjectAltName::Uri(s)),
+        x509::GeneralName::RFC822Name(s) => Some(SubjectAltName::Email(s)),
+        x509::GeneralName::IPAddress(bytes) => match bytes.len() {
+            4 => <[u8; 4]>::try_from(bytes).ok().map(|b| SubjectAltName::Ip(b.into())),
+            16 => <[u8; 16]>::try_from(bytes).ok().map(|b| SubjectAltName::Ip(b.into())),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+/// Matches `name` against `pattern`. A pattern ending in `*` matches any name
+/// with the preceding prefix. Otherwise, the match is exact. DNS names, whose
+/// labels are case-insensitive, are compared in lowercase. All other names are
+/// compared as-is.
+pub(crate) fn matches(pattern: &str, name: &SubjectAltName<'_>) -> bool {
+    match *name {
+        SubjectAltName::Dns(name) => {
+            matches_str(&pattern.to_ascii_lowercase(), &name.to_ascii_lowercase())
+        }
+        SubjectAltName::Uri(name) | SubjectAltName::Email(name) => matches_str(pattern, name),
+        SubjectAltName::Ip(ip) => matches_str(pattern, &ip.to_string()),
+    }
+}
+
+fn matches_str(pattern: &str, name: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => pattern == name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        use SubjectAltName::*;
+
+        assert!(matches("localhost", &Dns("localhost")));
+        assert!(matches("LocalHost", &Dns("localhost")));
+        assert!(matches("localhost", &Dns("LOCALHOST")));
+        assert!(matches("a.Example.*", &Dns("a.EXAMPLE.com")));
+        assert!(!matches("localhost", &Dns("localhost.com")));
+        let prod = "spiffe://example.org/ns/prod/*";
+        assert!(matches(prod, &Uri("spiffe://example.org/ns/prod/sa/web")));
+        assert!(!matches(prod, &Uri("spiffe://example.org/ns/dev/sa/web")));
+        assert!(!matches(prod, &Uri("spiffe://example.org/ns/PROD/sa/web")));
+        assert!(!matches("spiffe://Example.org/sa/web", &Uri("spiffe://example.org/sa/web")));
+        assert!(matches("admin@example.com", &Email("admin@example.com")));
+        assert!(!matches("Admin@example.com", &Email("admin@example.com")));
+        assert!(matches("127.0.0.1", &Ip([127, 0, 0, 1].into())));
+        assert!(matches("*", &Uri("anything")));
+        assert!(!matches("abcdef*", &Dns("abc")));
+    }
+}
@@ -0,0 +1,145 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::mtls::{san, Certificate, Error, SubjectAltName};
+use crate::outcome::try_outcome;
+use crate::request::{Request, FromRequest, Outcome};
+use crate::http::Status;
+
+/// A pattern that a [`SpiffeId`] must match.
+///
+/// A pattern ending in `*` matches any SPIFFE ID with the preceding prefix.
+/// Otherwise, the SPIFFE ID must match the pattern exactly. Matching is
+/// case-sensitive.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::mtls::SpiffePattern;
+///
+/// /// Any workload in the `prod` namespace of `example.org`.
+/// struct Prod;
+///
+/// impl SpiffePattern for Prod {
+///     const PATTERN: &'static str = "spiffe://example.org/ns/prod/*";
+/// }
+/// ```
+pub trait SpiffePattern: Send + Sync + 'static {
+    /// The pattern the SPIFFE ID must match.
+    const PATTERN: &'static str;
+}
+
+/// A [`SpiffePattern`] that matches any SPIFFE ID.
+#[derive(Debug)]
+pub struct AnySpiffeId;
+
+impl SpiffePattern for AnySpiffeId {
+    const PATTERN: &'static str = "spiffe://*";
+}
+
+/// A request guard for a client certificate's SPIFFE ID matching the pattern
+/// `P`.
+///
+/// A [SPIFFE] ID is a URI of the form `spiffe://trust-domain/path` carried as a
+/// URI subject alternative name in a client certificate. It identifies a
+/// workload, enabling service-to-service authorization.
+///
+/// # Request Guard
+///
+/// The request guard forwards or fails exactly as
+/// [`Certificate`](crate::mtls::Certificate) does. Additionally, it fails with a
+/// status of 403 Forbidden and:
+///
+///   * [`Error::NoSpiffeId`] if the certificate contains no SPIFFE ID.
+///   * [`Error::SpiffeIdMismatch`] if the SPIFFE ID doesn't match `P`.
+///
+/// [SPIFFE]: https://spiffe.io/docs/latest/spiffe-about/spiffe-concepts/
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::mtls::{SpiffeId, SpiffePattern};
+///
+/// struct Billing;
+///
+/// impl SpiffePattern for Billing {
+///     const PATTERN: &'static str = "spiffe://example.org/ns/prod/sa/billing";
+/// }
+///
+/// #[post("/charge")]
+/// fn charge(caller: SpiffeId<'_, Billing>) {
+///     // This handler only runs when the billing service is the client.
+/// }
+///
+/// #[get("/whoami")]
+/// fn whoami(caller: SpiffeId<'_>) -> String {
+///     // This handler runs for any client with a SPIFFE ID.
+///     format!("{} in {}", caller.path(), caller.trust_domain())
+/// }
+/// ```
+pub struct SpiffeId<'r, P: SpiffePattern = AnySpiffeId> {
+    id: &'r str,
+    certificate: Certificate<'r>,
+    _pattern: PhantomData<fn() -> P>,
+}
+
+impl<'r, P: SpiffePattern> SpiffeId<'r, P> {
+    /// Returns the full SPIFFE ID, for example
+    /// `spiffe://example.org/ns/prod/sa/web`.
+    pub fn as_str(&self) -> &'r str {
+        self.id
+    }
+
+    /// Returns the trust domain of the SPIFFE ID, for example `example.org`.
+    pub fn trust_domain(&self) -> &'r str {
+        let rest = &self.id["spiffe://".len()..];
+        rest.split_once('/').map_or(rest, |(domain, _)| domain)
+    }
+
+    /// Returns the path of the SPIFFE ID, for example `/ns/prod/sa/web`, which
+    /// is empty if the SPIFFE ID has no path.
+    pub fn path(&self) -> &'r str {
+        let rest = &self.id["spiffe://".len()..];
+        rest.find('/').map_or("", |i| &rest[i..])
+    }
+
+    /// Returns the client certificate containing the SPIFFE ID.
+    pub fn certificate(&self) -> &Certificate<'r> {
+        &self.certificate
+    }
+}
+
+#[crate::async_trait]
+impl<'r, P: SpiffePattern> FromRequest<'r> for SpiffeId<'r, P> {
+    type Error = Error;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let certificate = try_outcome!(req.guard::<Certificate<'r>>().await);
+        let Some(id) = certificate.spiffe_id() else {
+            return Outcome::Error((Status::Forbidden, Error::NoSpiffeId));
+        };
+
+        if !san::matches(P::PATTERN, &SubjectAltName::Uri(id)) {
+            let error = Error::SpiffeIdMismatch(id.to_string());
+            return Outcome::Error((Status::Forbidden, error));
+        }
+
+        Outcome::Success(SpiffeId { id, certificate, _pattern: PhantomData })
+    }
+}
+
+impl<P: SpiffePattern> fmt::Debug for SpiffeId<'_, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpiffeId")
+            .field("id", &self.id)
+            .field("pattern", &P::PATTERN)
+            .finish()
+    }
+}
+
+impl<P: SpiffePattern> fmt::Display for SpiffeId<'_, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.id.fmt(f)
+    }
+}
@@ -0,0 +1,530 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use parking_lot::{Mutex, RwLock};
+use rustls::{CertificateError, DigitallySignedStruct, DistinguishedName};
+use rustls::{Error as TlsError, OtherError, RootCertStore, SignatureScheme};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, UnixTime};
+use rustls::server::WebPkiClientVerifier;
+use rustls::client::danger::HandshakeSignatureValid;
+use rustls::server::danger::{ClientCertVerified, ClientCertVerifier};
+
+use crate::mtls::{x509, san, MtlsConfig};
+use crate::tls::Result;
+
+/// A client certificate verifier that enforces the revocation lists and the
+/// additional constraints in an [`MtlsConfig`] atop webpki verification.
+///
+/// Revocation lists configured as paths are reloaded when they are modified.
+/// Modification times are checked at most once every `crl_refresh` seconds.
+pub(crate) struct ClientVerifier {
+    config: MtlsConfig,
+    hints: Vec<DistinguishedName>,
+    crls: Arc<CrlReloader>,
+}
+
+/// Rebuilds the webpki verifier off of the handshake path when CRL files are
+/// modified, swapping it in once it has been built.
+struct CrlReloader {
+    config: MtlsConfig,
+    provider: Arc<CryptoProvider>,
+    roots: Arc<RootCertStore>,
+    inner: RwLock<Arc<dyn ClientCertVerifier>>,
+    state: Mutex<CrlState>,
+}
+
+/// Modification times of the CRL files as of the last check.
+struct CrlState {
+    checked: Instant,
+    modified: Vec<Option<SystemTime>>,
+    reloading: bool,
+}
+
+/// The reason a client certificate was rejected by a configured constraint.
+#[derive(Debug)]
+pub(crate) enum Rejection {
+    /// The chain of `.0` certificates exceeds the maximum depth of `.1`.
+    Depth(usize, usize),
+    /// No subject alternative name matched an allowed name.
+    San(Vec<String>),
+    /// The issuer is not allowed.
+    Issuer(String),
+    /// The certificate couldn't be parsed to check constraints.
+    Parse(String),
+}
+
+impl ClientVerifier {
+    pub(crate) fn new(config: &MtlsConfig, provider: Arc<CryptoProvider>) -> Result<Self> {
+        let roots = Arc::new(config.load_ca_certs()?);
+        let hints = roots.subjects();
+        let inner = CrlReloader::build(config, &roots, &provider)?;
+        let crls = Arc::new(CrlReloader {
+            config: config.clone(),
+            state: Mutex::new(CrlState {
+                checked: Instant::now(),
+                modified: config.crl_modification_times(),
+                reloading: false,
+            }),
+            inner: RwLock::new(inner),
+            provider,
+            roots,
+        });
+
+        Ok(ClientVerifier { config: config.clone(), hints, crls })
+    }
+
+    /// Returns the current webpki verifier.
+    fn inner(&self) -> Arc<dyn ClientCertVerifier> {
+        self.crls.inner.read().clone()
+    }
+
+    fn check_constraints(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+    ) -> Result<(), Rejection> {
+        let depth = intermediates.len() + 1;
+        if let Some(max) = self.config.max_depth {
+            if depth > max {
+                return Err(Rejection::Depth(depth, max));
+            }
+        }
+
+        if self.config.allowed_sans.is_empty() && self.config.allowed_issuers.is_empty() {
+            return Ok(());
+        }
+
+        let (_, cert) = x509::parse_x509_certificate(end_entity)
+            .map_err(|e| Rejection::Parse(e.to_string()))?;
+
+        if !self.config.allowed_issuers.is_empty() {
+            let issuer = cert.issuer();
+            let dn = issuer.to_string();
+            let common_name = issuer.iter_common_name().next().and_then(|cn| cn.as_str().ok());
+            let allowed = self.config.allowed_issuers.iter()
+                .any(|allowed| *allowed == dn || Some(allowed.as_str()) == common_name);
+
+            if !allowed {
+                return Err(Rejection::Issuer(dn));
+            }
+        }
+
+        if !self.config.allowed_sans.is_empty() {
+            let allowed = san::subject_alt_names(&cert).any(|name| {
+                self.config.allowed_sans.iter().any(|pattern| san::matches(pattern, &name))
+            });
+
+            if !allowed {
+                let sans = san::subject_alt_names(&cert).map(|name| name.to_string()).collect();
+                return Err(Rejection::San(sans));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl CrlReloader {
+    fn build(
+        config: &MtlsConfig,
+        roots: &Arc<RootCertStore>,
+        provider: &Arc<CryptoProvider>,
+    ) -> Result<Arc<dyn ClientCertVerifier>> {
+        let verifier = WebPkiClientVerifier::builder_with_provider(roots.clone(), provider.clone())
+            .with_crls(config.load_crls()?);
+
+        Ok(match config.mandatory {
+            true => verifier.build()?,
+            false => verifier.allow_unauthenticated().build()?,
+        })
+    }
+
+    /// Schedules a check for modified CRL files, without blocking, if one is
+    /// due and none is in progress.
+    fn reload_if_needed(self: &Arc<Self>) {
+        if self.config.crl_refresh == 0 {
+            return;
+        }
+
+        {
+            let mut state = self.state.lock();
+            let refresh = Duration::from_secs(self.config.crl_refresh);
+            if state.reloading || state.checked.elapsed() < refresh {
+                return;
+            }
+
+            state.reloading = true;
+        }
+
+        let reloader = self.clone();
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => drop(handle.spawn_blocking(move || reloader.reload())),
+            Err(_) => drop(std::thread::spawn(move || reloader.reload())),
+        }
+    }
+
+    /// Rebuilds the verifier if any CRL file was modified since the last
+    /// check. If rebuilding fails, the previous verifier remains in use.
+    fn reload(&self) {
+        let modified = self.config.crl_modification_times();
+        let changed = modified != self.state.lock().modified;
+        if changed {
+            match Self::build(&self.config, &self.roots, &self.provider) {
+                Ok(verifier) => {
+                    info!("reloaded modified certificate revocation lists");
+                    *self.inner.write() = verifier;
+                    self.state.lock().modified = modified;
+                }
+                Err(e) => warn!(reason = %e, "failed to reload certificate revocation lists"),
+            }
+        }
+
+        let mut state = self.state.lock();
+        state.checked = Instant::now();
+        state.reloading = false;
+    }
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rejection::Depth(n, max) => {
+                write!(f, "chain of {n} certificates exceeds maximum depth of {max}")
+            }
+            Rejection::San(sans) if sans.is_empty() => {
+                write!(f, "certificate has no subject alternative names")
+            }
+            Rejection::San(sans) => {
+                write!(f, "no allowed subject alternative name in [{}]", sans.join(", "))
+            }
+            Rejection::Issuer(issuer) => write!(f, "issuer `{issuer}` is not allowed"),
+            Rejection::Parse(e) => write!(f, "failed to parse certificate: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Rejection { }
+
+impl From<Rejection> for TlsError {
+    fn from(rejection: Rejection) -> Self {
+        // `ApplicationVerificationFailure` results in an `access_denied`
+        // alert, `Other` in a `bad_certificate` alert.
+        match rejection {
+            Rejection::San(_) | Rejection::Issuer(_) => {
+                TlsError::InvalidCertificate(CertificateError::ApplicationVerificationFailure)
+            }
+            rejection => {
+                let error = OtherError(Arc::new(rejection));
+                TlsError::InvalidCertificate(CertificateError::Other(error))
+            }
+        }
+    }
+}
+
+impl ClientCertVerifier for ClientVerifier {
+    fn offer_client_auth(&self) -> bool {
+        true
+    }
+
+    fn client_auth_mandatory(&self) -> bool {
+        self.config.mandatory
+    }
+
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        &self.hints
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        now: UnixTime,
+    ) -> Result<ClientCertVerified, TlsError> {
+        self.crls.reload_if_needed();
+        let verified = self.inner().verify_client_cert(end_entity, intermediates, now)
+            .map_err(|e| {
+                warn!(reason = %e, "client certificate verification failed");
+                e
+            })?;
+
+        self.check_constraints(end_entity, intermediates)
+            .map_err(|rejection| {
+                warn!(reason = %rejection, "client certificate rejected by mtls constraints");
+                rejection
+            })?;
+
+        Ok(verified)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, TlsError> {
+        self.inner().verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, TlsError> {
+        self.inner().verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner().supported_verify_schemes()
+    }
+}
+
+impl fmt::Debug for ClientVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientVerifier")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rcgen::{BasicConstraints, CertificateParams, CertificateRevocationListParams, DnType};
+    use rcgen::{IsCa, KeyIdMethod, KeyPair, KeyUsagePurpose, RevokedCertParams, SanType};
+    use rcgen::SerialNumber;
+    use rustls::{ClientConfig, ClientConnection, ServerConfig, ServerConnection};
+    use rustls::pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer};
+
+    /// Returns a CA certificate with the common name `name`, signed by
+    /// `issuer` or self-signed, and its key.
+    fn ca(name: &str, issuer: Option<&(rcgen::Certificate, KeyPair)>)
+        -> (rcgen::Certificate, KeyPair)
+    {
+        let key = KeyPair::generate().unwrap();
+        let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.distinguished_name.push(DnType::CommonName, name);
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        params.key_usages = vec![KeyUsagePurpose::KeyCertSign, KeyUsagePurpose::CrlSign];
+        let cert = match issuer {
+            Some((issuer, issuer_key)) => params.signed_by(&key, issuer, issuer_key).unwrap(),
+            None => params.self_signed(&key).unwrap(),
+        };
+
+        (cert, key)
+    }
+
+    /// Returns a client certificate for `sans` with serial number `serial`
+    /// signed by `issuer`, and its key.
+    fn client(sans: &[&str], serial: u64, issuer: &(rcgen::Certificate, KeyPair))
+        -> (CertificateDer<'static>, KeyPair)
+    {
+        let key = KeyPair::generate().unwrap();
+        let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.subject_alt_names = sans.iter()
+            .map(|san| match san.contains("://") {
+                true => SanType::URI((*san).try_into().unwrap()),
+                false => SanType::DnsName((*san).try_into().unwrap()),
+            })
+            .collect();
+
+        params.serial_number = Some(SerialNumber::from(serial));
+        let cert = params.signed_by(&key, &issuer.0, &issuer.1).unwrap();
+        (cert.der().clone(), key)
+    }
+
+    /// Returns a PEM-encoded CRL numbered `number`, issued by `ca`, revoking
+    /// the certificates with serial numbers in `revoked`.
+    fn crl(ca: &(rcgen::Certificate, KeyPair), number: u64, revoked: &[u64]) -> String {
+        CertificateRevocationListParams {
+            this_update: rcgen::date_time_ymd(2024, 1, 1),
+            next_update: rcgen::date_time_ymd(2100, 1, 1),
+            crl_number: SerialNumber::from(number),
+            issuing_distribution_point: None,
+            revoked_certs: revoked.iter()
+                .map(|&serial| RevokedCertParams {
+                    serial_number: SerialNumber::from(serial),
+                    revocation_time: rcgen::date_time_ymd(2024, 1, 1),
+                    reason_code: None,
+                    invalidity_date: None,
+                })
+                .collect(),
+            key_identifier_method: KeyIdMethod::Sha256,
+        }.signed_by(&ca.0, &ca.1).unwrap().pem().unwrap()
+    }
+
+    fn private_key(key: &KeyPair) -> PrivateKeyDer<'static> {
+        PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key.serialize_der()))
+    }
+
+    /// Performs a TLS handshake, in memory, between a server verifying client
+    /// certificates as per `config` and a client presenting `chain`.
+    fn handshake(
+        config: &MtlsConfig,
+        chain: Vec<CertificateDer<'static>>,
+        key: &KeyPair,
+    ) -> Result<(), TlsError> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let verifier = ClientVerifier::new(config, provider.clone()).unwrap();
+
+        let server_key = KeyPair::generate().unwrap();
+        let server_params = CertificateParams::new(vec!["localhost".to_string()]).unwrap();
+        let server_cert = server_params.self_signed(&server_key).unwrap();
+        let server_config = ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions().unwrap()
+            .with_client_cert_verifier(Arc::new(verifier))
+            .with_single_cert(vec![server_cert.der().clone()], private_key(&server_key))
+            .unwrap();
+
+        let mut roots = RootCertStore::empty();
+        roots.add(server_cert.der().clone()).unwrap();
+        let client_config = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions().unwrap()
+            .with_root_certificates(roots)
+            .with_client_auth_cert(chain, private_key(key))
+            .unwrap();
+
+        let mut server = ServerConnection::new(Arc::new(server_config)).unwrap();
+        let name = "localhost".try_into().unwrap();
+        let mut client = ClientConnection::new(Arc::new(client_config), name).unwrap();
+        while client.is_handshaking() || server.is_handshaking() {
+            let mut bytes = vec![];
+            while client.wants_write() {
+                client.write_tls(&mut bytes).unwrap();
+            }
+
+            server.read_tls(&mut &bytes[..]).unwrap();
+            server.process_new_packets()?;
+
+            let mut bytes = vec![];
+            while server.wants_write() {
+                server.write_tls(&mut bytes).unwrap();
+            }
+
+            client.read_tls(&mut &bytes[..]).unwrap();
+            client.process_new_packets()?;
+        }
+
+        Ok(())
+    }
+
+    fn is_rejection(result: Result<(), TlsError>) -> bool {
+        matches!(result, Err(TlsError::InvalidCertificate(
+            CertificateError::ApplicationVerificationFailure
+        )))
+    }
+
+    #[test]
+    fn test_rejection_alerts() {
+        let san = TlsError::from(Rejection::San(vec![]));
+        assert!(matches!(san, TlsError::InvalidCertificate(
+            CertificateError::ApplicationVerificationFailure
+        )));
+
+        let depth = TlsError::from(Rejection::Depth(3, 2));
+        assert!(matches!(depth, TlsError::InvalidCertificate(CertificateError::Other(_))));
+    }
+
+    #[test]
+    fn test_revoked_certificate_rejected() {
+        let ca = ca("Test CA", None);
+        let config = MtlsConfig::from_bytes(ca.0.pem().as_bytes())
+            .with_crl_bytes(crl(&ca, 1, &[2]).as_bytes());
+
+        let (cert, key) = client(&["client.example.com"], 1, &ca);
+        assert!(handshake(&config, vec![cert], &key).is_ok());
+
+        let (cert, key) = client(&["client.example.com"], 2, &ca);
+        let result = handshake(&config, vec![cert], &key);
+        assert!(matches!(result, Err(TlsError::InvalidCertificate(CertificateError::Revoked))));
+    }
+
+    #[test]
+    fn test_san_constraint() {
+        let ca = ca("Test CA", None);
+        let (cert, key) = client(&["client.example.com", "spiffe://example.org/web"], 1, &ca);
+        let config = MtlsConfig::from_bytes(ca.0.pem().as_bytes());
+
+        let allowed = config.clone().with_allowed_san("CLIENT.example.com");
+        assert!(handshake(&allowed, vec![cert.clone()], &key).is_ok());
+
+        let allowed = config.clone().with_allowed_san("spiffe://example.org/*");
+        assert!(handshake(&allowed, vec![cert.clone()], &key).is_ok());
+
+        let denied = config.clone().with_allowed_san("server.example.com");
+        assert!(is_rejection(handshake(&denied, vec![cert.clone()], &key)));
+
+        let denied = config.with_allowed_san("spiffe://Example.org/web");
+        assert!(is_rejection(handshake(&denied, vec![cert], &key)));
+    }
+
+    #[test]
+    fn test_issuer_constraint() {
+        let ca = ca("Test CA", None);
+        let (cert, key) = client(&["client.example.com"], 1, &ca);
+        let config = MtlsConfig::from_bytes(ca.0.pem().as_bytes());
+
+        let allowed = config.clone().with_allowed_issuer("Test CA");
+        assert!(handshake(&allowed, vec![cert.clone()], &key).is_ok());
+
+        let allowed = config.clone().with_allowed_issuer("CN=Test CA");
+        assert!(handshake(&allowed, vec![cert.clone()], &key).is_ok());
+
+        let denied = config.with_allowed_issuer("Other CA");
+        assert!(is_rejection(handshake(&denied, vec![cert], &key)));
+    }
+
+    #[test]
+    fn test_depth_constraint() {
+        let root = ca("Root CA", None);
+        let intermediate = ca("Intermediate CA", Some(&root));
+        let (cert, key) = client(&["client.example.com"], 1, &intermediate);
+        let chain = vec![cert, intermediate.0.der().clone()];
+        let config = MtlsConfig::from_bytes(root.0.pem().as_bytes());
+
+        assert!(handshake(&config.clone().with_max_depth(2), chain.clone(), &key).is_ok());
+
+        let result = handshake(&config.with_max_depth(1), chain, &key);
+        assert!(matches!(result, Err(TlsError::InvalidCertificate(CertificateError::Other(_)))));
+    }
+
+    #[test]
+    fn test_crls_reload_off_handshake() {
+        let ca = ca("Test CA", None);
+        let dir = tempfile::tempdir().unwrap();
+        let crl_path = dir.path().join("revoked.crl.pem");
+        std::fs::write(&crl_path, crl(&ca, 1, &[])).unwrap();
+
+        let mut config = MtlsConfig::from_bytes(ca.0.pem().as_bytes()).with_crl(&crl_path);
+        config.crl_refresh = 1;
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let verifier = ClientVerifier::new(&config, provider).unwrap();
+        let original = verifier.inner();
+
+        // Not yet due: nothing is scheduled.
+        verifier.crls.reload_if_needed();
+        assert!(!verifier.crls.state.lock().reloading);
+
+        // Due, but unmodified: the verifier is kept.
+        verifier.crls.state.lock().checked -= Duration::from_secs(2);
+        verifier.crls.reload_if_needed();
+        while verifier.crls.state.lock().reloading {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        assert!(Arc::ptr_eq(&original, &verifier.inner()));
+
+        // Due and modified: the verifier is rebuilt in the background.
+        std::fs::write(&crl_path, crl(&ca, 2, &[])).unwrap();
+        verifier.crls.state.lock().modified = vec![None];
+        verifier.crls.state.lock().checked -= Duration::from_secs(2);
+        verifier.crls.reload_if_needed();
+        while verifier.crls.state.lock().reloading {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        assert!(!Arc::ptr_eq(&original, &verifier.inner()));
+    }
+}
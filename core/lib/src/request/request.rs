@@ -19,8 +19,9 @@ use crate::data::Limits;
 
 use crate::http::ProxyProto;
 use crate::http::{Method, Header, HeaderMap, ContentType, Accept, MediaType, CookieJar, Cookie};
+use crate::http::Status;
 use crate::http::uri::{fmt::Path, Origin, Segments, Host, Authority};
-use crate::listener::{Certificates, Endpoint};
+use crate::listener::{Certificates, Endpoint, InterimSender};
 
 /// The type of an incoming web request.
 ///
@@ -34,11 +35,22 @@ pub struct Request<'r> {
     uri: Origin<'r>,
     headers: HeaderMap<'r>,
     pub(crate) version: Option<HttpVersion>,
+    pub(crate) protocol: Option<String>,
     pub(crate) errors: Vec<RequestError>,
     pub(crate) connection: ConnectionMeta,
     pub(crate) state: RequestState<'r>,
 }
 
+/// The `:protocol` of an HTTP/2 or HTTP/3 extended `CONNECT` request that was
+/// rewritten as an upgrade request. Set as an extension on the request parts.
+#[derive(Debug, Clone)]
+pub(crate) struct ExtendedConnect(pub String);
+
+/// Early hints sent via [`Request::send_early_hints()`] that couldn't be sent
+/// immediately and are instead added to the response.
+#[derive(Default)]
+struct DeferredEarlyHints(parking_lot::Mutex<Vec<HeaderMap<'static>>>);
+
 /// Information derived from an incoming connection, if any.
 #[derive(Clone, Default)]
 pub(crate) struct ConnectionMeta {
@@ -47,6 +59,9 @@ pub(crate) struct ConnectionMeta {
     pub peer_certs: Option<Arc<Certificates<'static>>>,
     #[cfg_attr(feature = "tls", allow(dead_code))]
     pub server_name: Option<String>,
+    #[cfg(feature = "tls")]
+    pub tls_info: Option<Arc<crate::tls::TlsInfo>>,
+    pub interim: Option<InterimSender>,
 }
 
 impl ConnectionMeta {
@@ -58,7 +73,33 @@ impl ConnectionMeta {
             peer_endpoint: endpoint.ok(),
             peer_certs: certs.map(|c| c.into_owned()).map(Arc::new),
             server_name: server_name.map(|s| s.to_string()),
+            #[cfg(feature = "tls")]
+            tls_info: None,
+            interim: None,
+        }
+    }
+
+    pub fn with_interim(mut self, interim: InterimSender) -> Self {
+        self.interim = Some(interim);
+        self
+    }
+
+    #[cfg(feature = "tls")]
+    pub fn with_tls_info(mut self, info: Option<crate::tls::TlsInfo>) -> Self {
+        self.tls_info = info.map(Arc::new);
+        self
+    }
+
+    /// Whether the connection negotiated HTTP/2 via TLS ALPN. Connections
+    /// without TLS may speak HTTP/2 too, with prior knowledge, but that isn't
+    /// known until the connection is read.
+    pub fn negotiated_http2(&self) -> bool {
+        #[cfg(feature = "tls")]
+        if let Some(info) = &self.tls_info {
+            return info.alpn_protocol() == Some(b"h2");
         }
+
+        false
     }
 }
 
@@ -101,6 +142,7 @@ impl<'r> Request<'r> {
             method: AtomicMethod::new(method),
             headers: HeaderMap::new(),
             version,
+            protocol: None,
             errors: Vec::new(),
             connection: ConnectionMeta::default(),
             state: RequestState {
@@ -131,6 +173,28 @@ impl<'r> Request<'r> {
         self.version
     }
 
+    /// Returns the protocol, as given by the `:protocol` pseudo-header, if
+    /// this is an HTTP/2 or HTTP/3 extended `CONNECT` request.
+    ///
+    /// Rocket presents extended `CONNECT` requests for WebSockets ([RFC 8441],
+    /// [RFC 9220]) as `GET` requests with `Connection: upgrade` and `Upgrade:
+    /// websocket` headers so that they're routed and upgraded like HTTP/1.1
+    /// upgrade requests. This method distinguishes such requests.
+    ///
+    /// [RFC 8441]: https://datatracker.ietf.org/doc/html/rfc8441
+    /// [RFC 9220]: https://datatracker.ietf.org/doc/html/rfc9220
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let c = rocket::local::blocking::Client::debug_with(vec![]).unwrap();
+    /// # let req = c.get("/");
+    /// assert_eq!(req.connect_protocol(), None);
+    /// ```
+    pub fn connect_protocol(&self) -> Option<&str> {
+        self.protocol.as_deref()
+    }
+
     /// Retrieve the method from `self`.
     ///
     /// # Example
@@ -311,6 +375,27 @@ impl<'r> Request<'r> {
         self.connection.server_name.as_deref()
     }
 
+    /// Returns the parameters negotiated in the TLS handshake of the
+    /// connection `self` arrived on, if it arrived over TLS.
+    ///
+    /// This is also available via the [`&TlsInfo`](crate::tls::TlsInfo)
+    /// request guard.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let c = rocket::local::blocking::Client::debug_with(vec![]).unwrap();
+    /// # let req = c.get("/");
+    /// # let request = req.inner();
+    /// // Local requests never arrive over TLS.
+    /// assert!(request.tls_info().is_none());
+    /// ```
+    #[cfg(feature = "tls")]
+    #[inline(always)]
+    pub fn tls_info(&self) -> Option<&crate::tls::TlsInfo> {
+        self.connection.tls_info.as_deref()
+    }
+
     /// Sets the host of `self` to `host`.
     ///
     /// # Example
@@ -841,6 +926,53 @@ impl<'r> Request<'r> {
         T::from_request(self)
     }
 
+    /// Sends `hints`, typically `Link` headers, to the client as a `103 Early
+    /// Hints` informational response right away, while the request is still
+    /// being handled. Empty `hints` are ignored.
+    ///
+    /// Hints added to a response, via [`Response::add_early_hints()`] or the
+    /// [`EarlyHints`] responder, can only be sent once a handler has completed.
+    /// This method instead allows request fairings and guards to send hints
+    /// before the handler runs. Over HTTP/1.1, the hints are written to the
+    /// connection immediately. Otherwise, they're added to the response ahead
+    /// of any of its own hints and sent as described in [Early
+    /// Hints](crate::Response#early-hints).
+    ///
+    /// [`Response::add_early_hints()`]: crate::Response::add_early_hints()
+    /// [`EarlyHints`]: crate::response::EarlyHints
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::http::{Header, HeaderMap};
+    ///
+    /// # let c = rocket::local::blocking::Client::debug_with(vec![]).unwrap();
+    /// # let request = c.get("/");
+    /// let mut hints = HeaderMap::new();
+    /// hints.add(Header::new("Link", "</style.css>; rel=preload; as=style"));
+    /// request.send_early_hints(hints);
+    /// ```
+    pub fn send_early_hints(&self, hints: HeaderMap<'static>) {
+        if hints.is_empty() {
+            return;
+        }
+
+        match &self.connection.interim {
+            Some(interim) if self.version() == Some(HttpVersion::Http11) => {
+                interim.send(Status::EarlyHints, &hints);
+            }
+            _ => self.local_cache(DeferredEarlyHints::default).0.lock().push(hints),
+        }
+    }
+
+    /// Removes and returns the early hints sent via `send_early_hints()` that
+    /// are yet to be sent.
+    pub(crate) fn take_early_hints(&self) -> Vec<HeaderMap<'static>> {
+        self.state.cache.try_get::<DeferredEarlyHints>()
+            .map(|hints| std::mem::take(&mut *hints.0.lock()))
+            .unwrap_or_default()
+    }
+
     /// Retrieves the cached value for type `T` from the request-local cached
     /// state of `self`. If no such value has previously been cached for this
     /// request, `f` is called to produce the value which is subsequently
@@ -1176,6 +1308,7 @@ impl<'r> Request<'r> {
             _ => None,
         });
         request.errors = errors;
+        request.protocol = hyper.extensions.get::<ExtendedConnect>().map(|p| p.0.clone());
 
         // Set the passed in connection metadata.
         request.connection = connection;
@@ -0,0 +1,130 @@
+use std::borrow::Cow;
+
+use crate::request::Request;
+use crate::response::{self, Responder};
+use crate::http::{Header, HeaderMap};
+
+/// Sends `103 Early Hints` informational responses ahead of a response.
+///
+/// Wraps a responder `R` and adds hints, typically `Link` headers that allow a
+/// client to begin preloading resources, to its response. The hints added via
+/// [`EarlyHints::link()`], [`EarlyHints::preload()`], and
+/// [`EarlyHints::header()`] are sent in a single informational response.
+/// Additional informational responses can be added via
+/// [`EarlyHints::hints()`].
+///
+/// See [Early Hints](crate::Response#early-hints) for details on how hints are
+/// sent to the client.
+///
+/// # Example
+///
+/// ```rust
+/// # use rocket::get;
+/// use rocket::response::EarlyHints;
+/// use rocket::response::content::RawHtml;
+///
+/// #[get("/")]
+/// fn index() -> EarlyHints<RawHtml<&'static str>> {
+///     EarlyHints::new(RawHtml("<link rel=stylesheet href=/style.css>"))
+///         .preload("/style.css", "style")
+///         .preload("/app.js", "script")
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct EarlyHints<R> {
+    hints: Vec<HeaderMap<'static>>,
+    responder: R,
+}
+
+impl<R> EarlyHints<R> {
+    /// Wraps `responder` without any hints.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::EarlyHints;
+    ///
+    /// let response = EarlyHints::new("Hello, world!");
+    /// ```
+    pub fn new(responder: R) -> Self {
+        EarlyHints { hints: vec![HeaderMap::new()], responder }
+    }
+
+    /// Adds a `Link` header with the raw `value` to the hints.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::EarlyHints;
+    ///
+    /// let response = EarlyHints::new("Hello, world!")
+    ///     .link("</fonts/inter.woff2>; rel=preload; as=font; crossorigin");
+    /// ```
+    pub fn link<V: Into<Cow<'static, str>>>(self, value: V) -> Self {
+        self.header(Header::new("Link", value))
+    }
+
+    /// Adds a `Link` header to the hints requesting that the resource at `uri`
+    /// of kind `kind`, the value of the `as` attribute, be preloaded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::EarlyHints;
+    ///
+    /// let response = EarlyHints::new("Hello, world!")
+    ///     .preload("/style.css", "style");
+    /// ```
+    pub fn preload(self, uri: &str, kind: &str) -> Self {
+        self.link(format!("<{uri}>; rel=preload; as={kind}"))
+    }
+
+    /// Adds `header` to the hints.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::EarlyHints;
+    /// use rocket::http::Header;
+    ///
+    /// let response = EarlyHints::new("Hello, world!")
+    ///     .header(Header::new("Link", "<https://cdn.example.com>; rel=preconnect"));
+    /// ```
+    pub fn header<H: Into<Header<'static>>>(mut self, header: H) -> Self {
+        self.hints[0].add(header);
+        self
+    }
+
+    /// Adds a separate informational response with the headers in `hints`,
+    /// to be sent after the previously added ones.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::EarlyHints;
+    /// use rocket::http::{Header, HeaderMap};
+    ///
+    /// let mut hints = HeaderMap::new();
+    /// hints.add(Header::new("Link", "</app.js>; rel=preload; as=script"));
+    ///
+    /// let response = EarlyHints::new("Hello, world!")
+    ///     .preload("/style.css", "style")
+    ///     .hints(hints);
+    /// ```
+    pub fn hints(mut self, hints: HeaderMap<'static>) -> Self {
+        self.hints.push(hints);
+        self
+    }
+}
+
+/// Adds the hints to the response generated by the wrapped responder.
+impl<'r, 'o: 'r, R: Responder<'r, 'o>> Responder<'r, 'o> for EarlyHints<R> {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'o> {
+        let mut response = self.responder.respond_to(req)?;
+        for hints in self.hints {
+            response.add_early_hints(hints);
+        }
+
+        Ok(response)
+    }
+}
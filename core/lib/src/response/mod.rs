@@ -18,6 +18,7 @@ mod redirect;
 mod response;
 mod debug;
 mod body;
+mod early_hints;
 
 pub(crate) mod flash;
 
@@ -34,6 +35,7 @@ pub use self::responder::Responder;
 pub use self::redirect::Redirect;
 pub use self::flash::Flash;
 pub use self::debug::Debug;
+pub use self::early_hints::EarlyHints;
 
 /// Type alias for the `Result` of a [`Responder::respond_to()`] call.
 pub type Result<'r> = std::result::Result<Response<'r>, crate::http::Status>;
@@ -303,6 +303,32 @@ impl<'r> Builder<'r> {
         self
     }
 
+    /// Adds an informational `103 Early Hints` response with the headers in
+    /// `hints` to be sent ahead of the response.
+    ///
+    /// See [`Response::add_early_hints()`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::Response;
+    /// use rocket::http::{Header, HeaderMap};
+    ///
+    /// let mut hints = HeaderMap::new();
+    /// hints.add(Header::new("Link", "</style.css>; rel=preload; as=style"));
+    ///
+    /// let response = Response::build()
+    ///     .early_hints(hints)
+    ///     .finalize();
+    ///
+    /// assert_eq!(response.early_hints().count(), 1);
+    /// ```
+    #[inline(always)]
+    pub fn early_hints(&mut self, hints: HeaderMap<'r>) -> &mut Builder<'r> {
+        self.response.add_early_hints(hints);
+        self
+    }
+
     /// Sets the max chunk size of a body, if any, to `size`.
     ///
     /// See [`Response::set_max_chunk_size()`] for notes.
@@ -479,12 +505,33 @@ impl<'r> Builder<'r> {
 /// If a connection _is not_ upgraded due to an error, even though there was a
 /// matching, registered protocol, the `IoHandler` is not invoked, and the
 /// original response is sent to the client without alteration.
+///
+/// ## Early Hints
+///
+/// A response may optionally carry one or more sets of headers, typically
+/// `Link` headers, to send to the client as informational `103 Early Hints`
+/// responses ahead of the response itself via [`Response::add_early_hints()`],
+/// the corresponding builder method [`Builder::early_hints()`], or the
+/// [`EarlyHints`](crate::response::EarlyHints) responder. Clients can use these
+/// hints to begin preloading resources. As these hints are part of the
+/// response, they're sent once the handler completes. To send hints while the
+/// request is still being handled, from a request fairing or guard, use
+/// [`Request::send_early_hints()`](crate::Request::send_early_hints()). How
+/// hints are sent depends on the HTTP version of the request:
+///
+///   * **HTTP/1.1:** Each set of hints is sent as a `103 Early Hints`
+///     response immediately before the final response.
+///   * **HTTP/2 and HTTP/3:** The hints are added to the headers of the final
+///     response as informational responses are not yet supported.
+///   * **HTTP/1.0 and earlier:** The hints are discarded as these versions
+///     do not support informational responses.
 #[derive(Default)]
 pub struct Response<'r> {
     status: Option<Status>,
     headers: HeaderMap<'r>,
     body: Body<'r>,
     upgrade: HashMap<Uncased<'r>, Box<dyn IoHandler + 'r>>,
+    early_hints: Vec<HeaderMap<'r>>,
 }
 
 impl<'r> Response<'r> {
@@ -988,6 +1035,56 @@ impl<'r> Response<'r> {
         self.upgrade.insert(protocol.into(), Box::new(handler));
     }
 
+    /// Adds an informational `103 Early Hints` response with the headers in
+    /// `hints` to be sent ahead of `self`. Each call adds a separate
+    /// informational response. Empty `hints` are ignored.
+    ///
+    /// See [Early Hints](Response#early-hints) for details on how hints are
+    /// sent to the client.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::Response;
+    /// use rocket::http::{Header, HeaderMap};
+    ///
+    /// let mut response = Response::new();
+    /// assert_eq!(response.early_hints().count(), 0);
+    ///
+    /// let mut hints = HeaderMap::new();
+    /// hints.add(Header::new("Link", "</style.css>; rel=preload; as=style"));
+    /// response.add_early_hints(hints);
+    ///
+    /// let hints = response.early_hints().next().unwrap();
+    /// assert_eq!(hints.get_one("Link"), Some("</style.css>; rel=preload; as=style"));
+    /// ```
+    pub fn add_early_hints(&mut self, hints: HeaderMap<'r>) {
+        if !hints.is_empty() {
+            self.early_hints.push(hints);
+        }
+    }
+
+    /// Returns an iterator over the sets of headers to be sent as `103 Early
+    /// Hints` informational responses ahead of `self`, in the order they were
+    /// added.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::Response;
+    ///
+    /// let response = Response::new();
+    /// assert!(response.early_hints().next().is_none());
+    /// ```
+    pub fn early_hints(&self) -> impl Iterator<Item = &HeaderMap<'r>> {
+        self.early_hints.iter()
+    }
+
+    /// Removes and returns all early hints in `self`.
+    pub(crate) fn take_early_hints(&mut self) -> Vec<HeaderMap<'r>> {
+        std::mem::take(&mut self.early_hints)
+    }
+
     /// Sets the body's maximum chunk size to `size` bytes.
     ///
     /// The default max chunk size is [`Body::DEFAULT_MAX_CHUNK`]. The max chunk
@@ -1061,6 +1158,8 @@ impl<'r> Response<'r> {
         for (name, values) in other.headers.into_iter_raw() {
             self.headers.replace_all(name.into_cow(), values);
         }
+
+        self.early_hints.extend(other.early_hints);
     }
 
     /// Sets `self`'s status and body to that of `other` if they are not already
@@ -1106,6 +1205,8 @@ impl<'r> Response<'r> {
         for (name, mut values) in other.headers.into_iter_raw() {
             self.headers.add_all(name.into_cow(), &mut values);
         }
+
+        self.early_hints.extend(other.early_hints);
     }
 }
 
@@ -196,12 +196,13 @@ mod sse;
 mod raw_sse;
 
 pub(crate) use self::raw_sse::*;
+pub(crate) use self::sse::EventParser;
 
 pub use self::one::One;
 pub use self::text::TextStream;
 pub use self::bytes::ByteStream;
 pub use self::reader::ReaderStream;
-pub use self::sse::{Event, EventStream};
+pub use self::sse::{Event, EventStream, LastEventId, ReplayBuffer};
 
 crate::export! {
     /// Retrofitted support for [`Stream`]s with `yield`, `for await` syntax.
@@ -1,13 +1,19 @@
 use std::borrow::Cow;
+use std::collections::{HashSet, VecDeque};
+use std::convert::Infallible;
+use std::fmt;
+use std::ops::Deref;
+
+use parking_lot::Mutex;
 
 use tokio::io::AsyncRead;
 use tokio::time::{interval, Duration};
 use futures::{stream::{self, Stream}, future::Either};
 use tokio_stream::{StreamExt, wrappers::IntervalStream};
 
-use crate::request::Request;
+use crate::request::{Request, FromRequest, Outcome};
 use crate::response::{self, Response, Responder, stream::{ReaderStream, RawLinedEvent}};
-use crate::http::ContentType;
+use crate::http::{ContentType, Status};
 
 /// A Server-Sent `Event` (SSE) in a Server-Sent [`struct@EventStream`].
 ///
@@ -488,6 +494,7 @@ impl Event {
 pub struct EventStream<S> {
     stream: S,
     heartbeat: Option<Duration>,
+    replay: Vec<Event>,
 }
 
 impl<S: Stream<Item = Event>> EventStream<S> {
@@ -528,6 +535,39 @@ impl<S: Stream<Item = Event>> EventStream<S> {
         self
     }
 
+    /// Sends `events` before any events from the internal stream.
+    ///
+    /// This is typically used with a [`ReplayBuffer`] to send events missed
+    /// by a reconnecting client, identified via [`LastEventId`], before any
+    /// live events. See [`ReplayBuffer`] for a complete example.
+    ///
+    /// An event from the internal stream with the same ID as a replayed event
+    /// is skipped, once per replayed event, as the client has already received
+    /// it. This avoids sending an event twice when it is both retained and
+    /// sent live between subscribing to live events and retrieving those to
+    /// replay.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::get;
+    /// use rocket::response::stream::{Event, EventStream};
+    ///
+    /// #[get("/events")]
+    /// fn events() -> EventStream![] {
+    ///     let stream = EventStream! {
+    ///         yield Event::data("live");
+    ///     };
+    ///
+    ///     // Sends "first", then "second", then "live".
+    ///     stream.replay([Event::data("first"), Event::data("second")])
+    /// }
+    /// ```
+    pub fn replay<I: IntoIterator<Item = Event>>(mut self, events: I) -> Self {
+        self.replay.extend(events);
+        self
+    }
+
     fn heartbeat_stream(&self) -> impl Stream<Item = RawLinedEvent> {
         self.heartbeat
             .map(|beat| IntervalStream::new(interval(beat)))
@@ -539,7 +579,13 @@ impl<S: Stream<Item = Event>> EventStream<S> {
         use futures::StreamExt;
 
         let heartbeats = self.heartbeat_stream();
-        let events = StreamExt::map(self.stream, |e| e.into_stream()).flatten();
+        let mut replayed: HashSet<_> = self.replay.iter().filter_map(|e| e.id.clone()).collect();
+        let live = tokio_stream::StreamExt::filter(self.stream, move |event| {
+            !event.id.as_ref().is_some_and(|id| replayed.remove(id))
+        });
+
+        let events = StreamExt::chain(stream::iter(self.replay), live);
+        let events = StreamExt::map(events, |e| e.into_stream()).flatten();
         crate::util::join(events, heartbeats)
     }
 
@@ -564,7 +610,7 @@ impl<S: Stream<Item = Event>> From<S> for EventStream<S> {
     /// let stream = EventStream::from(raw);
     /// ```
     fn from(stream: S) -> Self {
-        EventStream { stream, heartbeat: Some(Duration::from_secs(30)), }
+        EventStream { stream, heartbeat: Some(Duration::from_secs(30)), replay: vec![] }
     }
 }
 
@@ -579,6 +625,300 @@ impl<'r, S: Stream<Item = Event> + Send + 'r> Responder<'r, 'r> for EventStream<
     }
 }
 
+/// The ID of the last event received by a reconnecting Server-Sent Events
+/// client, as sent in the `Last-Event-ID` header.
+///
+/// As a request guard, `LastEventId` succeeds if the request contains a
+/// `Last-Event-ID` header and forwards with a status of `400` otherwise. Since
+/// a client's first connection won't contain the header, `LastEventId` is
+/// typically used as an `Option<LastEventId>`. See [`ReplayBuffer`] for how to
+/// resume a stream from the ID.
+///
+/// # Example
+///
+/// ```rust
+/// # use rocket::get;
+/// use rocket::response::stream::{Event, EventStream, LastEventId};
+///
+/// #[get("/events")]
+/// fn events(last: Option<LastEventId<'_>>) -> EventStream![Event + '_] {
+///     EventStream! {
+///         if let Some(id) = last {
+///             yield Event::data(format!("welcome back; last saw {id}"));
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LastEventId<'r>(&'r str);
+
+impl<'r> LastEventId<'r> {
+    /// Returns the ID as a string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::stream::LastEventId;
+    ///
+    /// fn check(id: LastEventId<'_>) {
+    ///     let id: &str = id.as_str();
+    /// }
+    /// ```
+    pub fn as_str(&self) -> &'r str {
+        self.0
+    }
+}
+
+impl Deref for LastEventId<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.0
+    }
+}
+
+impl fmt::Display for LastEventId<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+#[crate::async_trait]
+impl<'r> FromRequest<'r> for LastEventId<'r> {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Infallible> {
+        match request.headers().get_one("Last-Event-ID") {
+            Some(id) => Outcome::Success(LastEventId(id)),
+            None => Outcome::Forward(Status::BadRequest)
+        }
+    }
+}
+
+/// A bounded buffer of recent Server-Sent [`Event`]s for replay to
+/// reconnecting clients.
+///
+/// A `ReplayBuffer` retains the last `capacity` events [pushed](Self::push())
+/// to it, each with an ID. When a client reconnects with a [`LastEventId`],
+/// [`ReplayBuffer::since()`] returns the retained events that followed it,
+/// which can be sent ahead of live events via [`EventStream::replay()`].
+///
+/// `ReplayBuffer` is thread-safe and is typically [managed] by the
+/// application.
+///
+/// [managed]: crate::Rocket::manage()
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::State;
+/// use rocket::response::stream::{Event, EventStream, LastEventId, ReplayBuffer};
+/// use rocket::tokio::sync::broadcast::{channel, Sender};
+///
+/// #[post("/message", data = "<message>")]
+/// fn post(message: String, buffer: &State<ReplayBuffer>, live: &State<Sender<Event>>) {
+///     // Assigns the event an ID, retains it, then sends it to live clients.
+///     let event = buffer.push(Event::data(message));
+///     let _ = live.send(event);
+/// }
+///
+/// #[get("/events")]
+/// fn events(
+///     last: Option<LastEventId<'_>>,
+///     buffer: &State<ReplayBuffer>,
+///     live: &State<Sender<Event>>,
+/// ) -> EventStream![] {
+///     // Subscribe first so that no event is missed between the two calls.
+///     // Events received both ways are only sent once by `replay()`.
+///     let mut live = live.subscribe();
+///     let missed = buffer.since(last.as_deref());
+///     let stream = EventStream! {
+///         while let Ok(event) = live.recv().await {
+///             yield event;
+///         }
+///     };
+///
+///     stream.replay(missed)
+/// }
+///
+/// #[launch]
+/// fn rocket() -> _ {
+///     rocket::build()
+///         .manage(ReplayBuffer::new(128))
+///         .manage(channel::<Event>(1024).0)
+///         .mount("/", routes![post, events])
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ReplayBuffer {
+    capacity: usize,
+    state: Mutex<ReplayState>,
+}
+
+#[derive(Debug)]
+struct ReplayState {
+    events: VecDeque<Event>,
+    next_id: u64,
+}
+
+impl ReplayBuffer {
+    /// Creates a new `ReplayBuffer` that retains at most `capacity` events.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::stream::ReplayBuffer;
+    ///
+    /// let buffer = ReplayBuffer::new(128);
+    /// ```
+    pub fn new(capacity: usize) -> Self {
+        let state = ReplayState { events: VecDeque::with_capacity(capacity), next_id: 0 };
+        ReplayBuffer { capacity, state: Mutex::new(state) }
+    }
+
+    /// Appends `event` to the buffer, evicting the oldest event if the buffer
+    /// is full, and returns the event as retained.
+    ///
+    /// If `event` doesn't have an ID, it is assigned the next in a sequence of
+    /// integer IDs starting at `0`. Events with IDs should be given unique IDs
+    /// so they can be located by [`ReplayBuffer::since()`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::stream::{Event, ReplayBuffer};
+    ///
+    /// let buffer = ReplayBuffer::new(128);
+    /// assert_eq!(buffer.push(Event::data("a")), Event::data("a").id("0"));
+    /// assert_eq!(buffer.push(Event::data("b")), Event::data("b").id("1"));
+    /// assert_eq!(buffer.push(Event::data("c").id("x")), Event::data("c").id("x"));
+    /// ```
+    pub fn push(&self, mut event: Event) -> Event {
+        let mut state = self.state.lock();
+        if event.id.is_none() {
+            event.id = Some(state.next_id.to_string().into());
+            state.next_id += 1;
+        }
+
+        if self.capacity == 0 {
+            return event;
+        }
+
+        if state.events.len() == self.capacity {
+            state.events.pop_front();
+        }
+
+        state.events.push_back(event.clone());
+        event
+    }
+
+    /// Returns the retained events that followed the event with ID `last`.
+    ///
+    /// If `last` is `None`, as for a client's first connection, no events are
+    /// returned. If no retained event has the ID `last`, for instance because
+    /// it has been evicted, all retained events are returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::stream::{Event, ReplayBuffer};
+    ///
+    /// let buffer = ReplayBuffer::new(2);
+    /// buffer.push(Event::data("a"));
+    /// buffer.push(Event::data("b"));
+    /// buffer.push(Event::data("c"));
+    ///
+    /// assert!(buffer.since(None).is_empty());
+    /// assert_eq!(buffer.since(Some("1")), [Event::data("c").id("2")]);
+    /// assert!(buffer.since(Some("2")).is_empty());
+    ///
+    /// // Event "0" was evicted: all retained events are returned.
+    /// assert_eq!(buffer.since(Some("0")).len(), 2);
+    /// ```
+    pub fn since(&self, last: Option<&str>) -> Vec<Event> {
+        let Some(last) = last else {
+            return vec![];
+        };
+
+        let state = self.state.lock();
+        let start = state.events.iter()
+            .rposition(|e| e.id.as_deref() == Some(last))
+            .map_or(0, |i| i + 1);
+
+        state.events.range(start..).cloned().collect()
+    }
+}
+
+/// Incremental parser of `text/event-stream` data into [`Event`]s.
+///
+/// Lines may be terminated by `\n` or `\r\n`. Empty comment lines, such as
+/// those sent as heartbeats, are ignored. Unknown fields are ignored, as are
+/// `retry` fields that aren't integers.
+#[derive(Debug, Default)]
+pub(crate) struct EventParser {
+    buffer: Vec<u8>,
+    event: Option<Event>,
+}
+
+impl EventParser {
+    /// Appends `bytes` to the data to be parsed.
+    pub(crate) fn feed(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Returns the next complete event in the fed data, if there is one.
+    pub(crate) fn next(&mut self) -> Option<Event> {
+        while let Some(i) = memchr::memchr(b'\n', &self.buffer) {
+            let line: Vec<u8> = self.buffer.drain(..=i).collect();
+            let line = line.strip_suffix(b"\n").unwrap_or(&line);
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            if line.is_empty() {
+                match self.event.take() {
+                    Some(event) => return Some(event),
+                    None => continue,
+                }
+            }
+
+            self.parse_line(&String::from_utf8_lossy(line));
+        }
+
+        None
+    }
+
+    fn parse_line(&mut self, line: &str) {
+        fn append(field: &mut Option<Cow<'static, str>>, value: &str) {
+            match field {
+                Some(field) => {
+                    let field = field.to_mut();
+                    field.push('\n');
+                    field.push_str(value);
+                }
+                None => *field = Some(value.to_owned().into()),
+            }
+        }
+
+        let (name, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        let retry = value.parse().map(Duration::from_millis);
+        match name {
+            "" if value.is_empty() => return,
+            "retry" if retry.is_err() => return,
+            "" | "data" | "event" | "id" | "retry" => {},
+            _ => return,
+        }
+
+        let event = self.event.get_or_insert_with(Event::new);
+        match name {
+            "" => append(&mut event.comment, value),
+            "data" => append(&mut event.data, value),
+            "event" => event.event = Some(value.to_owned().into()),
+            "id" => event.id = Some(value.to_owned().into()),
+            _ => event.retry = retry.ok(),
+        }
+    }
+}
+
 crate::export! {
     /// Type and stream expression macro for [`struct@EventStream`].
     ///
@@ -808,4 +1148,71 @@ mod sse_tests {
         assert!(string.contains("data:a\n\n"), "string = {:?}", string);
         assert!(string.contains("data:b\n\n"), "string = {:?}", string);
     }
+
+    #[test]
+    fn test_event_parser() {
+        use super::EventParser;
+
+        fn parse(string: &str) -> Vec<Event> {
+            let mut parser = EventParser::default();
+            parser.feed(string.as_bytes());
+            std::iter::from_fn(|| parser.next()).collect()
+        }
+
+        let events = vec![
+            Event::data("foo"),
+            Event::data("a\nb\n").id("moo").event("bar"),
+            Event::data("foo").id("moo").with_comment("cows, ey?"),
+            Event::comment("incoming\ndata..."),
+            Event::retry(Duration::from_secs(45)),
+            Event::empty().with_retry(Duration::from_millis(10)),
+            Event::data(r#"{"a": [1, 2]}"#).event("json"),
+        ];
+
+        let string = EventStream::from(futures::stream::iter(events.clone())).into_string();
+        assert_eq!(parse(&string), events);
+
+        // Parsing is incremental: events are only emitted once complete.
+        let mut parser = EventParser::default();
+        parser.feed(b"id: 1\r\ndata: hel");
+        assert_eq!(parser.next(), None);
+        parser.feed(b"lo\r\n");
+        assert_eq!(parser.next(), None);
+        parser.feed(b"\r\n:\n\ndata\n\n");
+        assert_eq!(parser.next(), Some(Event::data("hello").id("1")));
+        assert_eq!(parser.next(), Some(Event::empty()));
+        assert_eq!(parser.next(), None);
+
+        // Heartbeats, unknown fields, and invalid retries are ignored.
+        assert_eq!(parse(":\n\nfoo:bar\nretry:x\n\n:\ndata:x\n\n"), [Event::data("x")]);
+    }
+
+    #[test]
+    fn test_replay() {
+        use futures::stream::iter;
+        use super::ReplayBuffer;
+
+        let buffer = ReplayBuffer::new(3);
+        for data in ["a", "b", "c", "d"] {
+            buffer.push(Event::data(data));
+        }
+
+        let missed = buffer.since(Some("1"));
+        assert_eq!(missed, [Event::data("c").id("2"), Event::data("d").id("3")]);
+
+        let stream = EventStream::from(iter(vec![Event::data("live")])).replay(missed.clone());
+        assert_eq!(stream.heartbeat(None).into_string(),
+            "id:2\ndata:c\n\nid:3\ndata:d\n\ndata:live\n\n");
+
+        // Live events that were also replayed are only sent once.
+        let d = Event::data("d").id("3");
+        let live = vec![d.clone(), Event::data("e").id("4"), d];
+        let stream = EventStream::from(iter(live)).replay(missed);
+        assert_eq!(stream.heartbeat(None).into_string(),
+            "id:2\ndata:c\n\nid:3\ndata:d\n\nid:4\ndata:e\n\nid:3\ndata:d\n\n");
+
+        let buffer = ReplayBuffer::new(0);
+        assert_eq!(buffer.push(Event::data("a")), Event::data("a").id("0"));
+        assert!(buffer.since(Some("0")).is_empty());
+    }
 }
@@ -555,6 +555,16 @@ impl Rocket<Build> {
             }
         }
 
+        // Check that SNI certificates match their configured server names.
+        #[cfg(feature = "tls")]
+        match self.figment.extract_inner::<crate::tls::TlsConfig>("tls") {
+            Ok(tls) if tls.sni().next().is_some() => {
+                tls.server_config().await.map_err(ErrorKind::Tls)?;
+            }
+            Err(e) if !e.missing() => return Err(ErrorKind::Config(e).into()),
+            _ => {}
+        }
+
         // Initialize the router; check for collisions.
         let mut router = Router::new();
         self.routes.clone().into_iter().for_each(|r| router.routes.push(r));
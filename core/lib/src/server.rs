@@ -8,11 +8,13 @@ use hyper_util::rt::{TokioExecutor, TokioIo, TokioTimer};
 use hyper_util::server::conn::auto::Builder;
 use futures::{Future, TryFutureExt};
 use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_util::either::Either;
 
 use crate::{Ignite, Orbit, Request, Rocket};
 use crate::request::ConnectionMeta;
 use crate::erased::{ErasedRequest, ErasedResponse, ErasedIoHandler};
 use crate::listener::{Listener, Connection, BouncedExt, CancellableExt};
+use crate::listener::{InterimExt, InterimSender};
 use crate::error::log_server_error;
 use crate::data::{IoStream, RawStream};
 use crate::util::{spawn_inspect, FutureExt, ReaderStream};
@@ -27,20 +29,25 @@ impl Rocket<Orbit> {
         uri = %parts.uri,
         autohandled
     ))]
-    async fn service<T: for<'a> Into<RawStream<'a>>>(
+    async fn service<T, U>(
         self: Arc<Self>,
         parts: http::request::Parts,
         stream: T,
-        upgrade: Option<hyper::upgrade::OnUpgrade>,
+        upgrade: Option<U>,
         connection: ConnectionMeta,
-    ) -> Result<hyper::Response<ReaderStream<ErasedResponse>>, http::Error> {
+    ) -> Result<hyper::Response<ReaderStream<ErasedResponse>>, http::Error>
+        where T: for<'a> Into<RawStream<'a>>,
+              U: Future<Output = io::Result<IoStream>> + Send + 'static
+    {
         connection.trace_debug();
+        let version = parts.version;
+        let interim = connection.interim.clone().filter(|_| version == http::Version::HTTP_11);
         let request = ErasedRequest::new(self, parts, |rocket, parts| {
             Request::from_hyp(rocket, parts, connection).unwrap_or_else(|e| e)
         });
 
         span_debug!("request headers" => request.inner().headers().iter().trace_all_debug());
-        let mut response = request.into_response(
+        let response = request.into_response(
             stream,
             |rocket, request, data| Box::pin(rocket.preprocess(request, data)),
             |token, rocket, request, data| Box::pin(async move {
@@ -50,17 +57,24 @@ impl Rocket<Orbit> {
 
                 rocket.dispatch(token, request, data).await
             })
-        ).await;
+        );
+
+        // Write informational responses as they're sent during dispatch.
+        let mut response = match &interim {
+            Some(interim) => interim.drive(response).await,
+            None => response.await,
+        };
 
         // TODO: Should upgrades be handled in dispatch?
         response.inner().trace_info();
         span_debug!("response headers" => response.inner().headers().iter().trace_all_debug());
         let io_handler = response.make_io_handler(Rocket::extract_io_handler);
         if let (Some((proto, handler)), Some(upgrade)) = (io_handler, upgrade) {
-            let upgrade = upgrade.map_ok(IoStream::from).map_err(io::Error::other);
             tokio::task::spawn(io_handler_task(proto, upgrade, handler));
         }
 
+        response.with_inner_mut(|response| send_early_hints(response, version, interim));
+
         let mut builder = hyper::Response::builder();
         builder = builder.status(response.inner().status().code);
         for header in response.inner().headers().iter() {
@@ -72,7 +86,7 @@ impl Rocket<Orbit> {
     }
 
     pub(crate) fn alt_svc(&self) -> Option<&'static str> {
-        cfg!(feature = "http3-preview").then(|| {
+        cfg!(feature = "http3").then(|| {
             static ALT_SVC: state::InitCell<Option<String>> = state::InitCell::new();
 
             ALT_SVC.get_or_init(|| {
@@ -83,6 +97,32 @@ impl Rocket<Orbit> {
     }
 }
 
+/// Sends the early hints in `response` as `103 Early Hints` responses via
+/// `interim` if there is one, i.e., if the connection is HTTP/1.1. For HTTP/2
+/// and HTTP/3, where informational responses aren't supported, the hints are
+/// added to `response`'s headers. Otherwise, the hints are discarded.
+fn send_early_hints(
+    response: &mut crate::Response<'_>,
+    version: http::Version,
+    interim: Option<InterimSender>,
+) {
+    let hints = response.take_early_hints();
+    match (version, interim) {
+        (_, Some(interim)) => {
+            for hint in &hints {
+                interim.send(Status::EarlyHints, hint);
+            }
+        }
+        (http::Version::HTTP_2 | http::Version::HTTP_3, _) => {
+            for header in hints.into_iter().flatten() {
+                response.adjoin_header(header);
+            }
+        }
+        _ if !hints.is_empty() => debug!(?version, "discarding early hints"),
+        _ => {},
+    }
+}
+
 #[tracing::instrument("upgrade", skip_all, fields(protocol = proto))]
 async fn io_handler_task<S>(proto: String, stream: S, mut handler: ErasedIoHandler)
     where S: Future<Output = io::Result<IoStream>>
@@ -112,11 +152,29 @@ impl Rocket<Ignite> {
     {
         let endpoint = listener.endpoint()?;
 
-        #[cfg(feature = "http3-preview")]
-        if let (Some(addr), Some(tls)) = (endpoint.tcp(), endpoint.tls_config()) {
+        // HTTP/3 doesn't request client certificates: don't let it bypass
+        // mandatory client authentication.
+        #[cfg(all(feature = "http3", feature = "mtls"))]
+        let tls = endpoint.tls_config().filter(|tls| {
+            let mandatory = tls.mutual().map_or(false, |m| m.mandatory);
+            if mandatory {
+                warn!("HTTP/3 does not support mTLS and mTLS is mandatory.\n\
+                    Falling back to HTTP/1 + HTTP/2 server.");
+            }
+
+            !mandatory
+        });
+
+        #[cfg(all(feature = "http3", not(feature = "mtls")))]
+        let tls = endpoint.tls_config();
+
+        #[cfg(feature = "http3")]
+        if let (Some(addr), Some(tls)) = (endpoint.tcp(), tls) {
             use crate::error::ErrorKind;
+            use crate::listener::quic::{QuicConfig, QuicListener};
 
-            let h3listener = crate::listener::quic::QuicListener::bind(addr, tls.clone())
+            let config = QuicConfig::from_figment(self.figment()).map_err(ErrorKind::Config)?;
+            let h3listener = QuicListener::bind(addr, tls.clone(), config)
                 .map_err(|e| ErrorKind::Bind(Some(endpoint.clone()), Box::new(e)))
                 .await?;
 
@@ -131,7 +189,8 @@ impl Rocket<Ignite> {
             return Ok(rocket);
         }
 
-        if cfg!(feature = "http3-preview") {
+        #[cfg(feature = "http3")]
+        if endpoint.tcp().is_none() || endpoint.tls_config().is_none() {
             warn!("HTTP/3 cannot start without a valid TCP + TLS configuration.\n\
                 Falling back to HTTP/1 + HTTP/2 server.");
         }
@@ -158,7 +217,7 @@ impl Rocket<Orbit> {
             .header_read_timeout(Duration::from_secs(15));
 
         #[cfg(feature = "http2")] {
-            builder.http2().timer(TokioTimer::new());
+            builder.http2().timer(TokioTimer::new()).enable_connect_protocol();
             if keep_alive > Duration::ZERO {
                 builder.http2()
                     .timer(TokioTimer::new())
@@ -177,13 +236,31 @@ impl Rocket<Orbit> {
                     conn.certificates(),
                     conn.server_name()
                 );
+
+                #[cfg(feature = "tls")]
+                let meta = meta.with_tls_info(conn.tls_info());
+                // Only HTTP/1 connections carry `Interim` informational responses.
+                let conn = conn.cancellable(rocket.shutdown.clone());
+                let (conn, meta) = match meta.negotiated_http2() {
+                    true => (Either::Right(conn), meta),
+                    false => {
+                        let (conn, interim) = conn.interim();
+                        (Either::Left(conn), meta.with_interim(interim))
+                    }
+                };
+
                 let service = service_fn(|mut req| {
-                    let upgrade = hyper::upgrade::on(&mut req);
-                    let (parts, incoming) = req.into_parts();
+                    let upgrade = hyper::upgrade::on(&mut req)
+                        .map_ok(IoStream::from)
+                        .map_err(io::Error::other);
+
+                    let (mut parts, incoming) = req.into_parts();
+                    let extended = rewrite_extended_connect(&mut parts);
                     rocket.clone().service(parts, incoming, Some(upgrade), meta.clone())
+                        .map_ok(move |r| if extended { accept_extended_connect(r) } else { r })
                 });
 
-                let io = TokioIo::new(conn.cancellable(rocket.shutdown.clone()));
+                let io = TokioIo::new(conn);
                 let mut server = pin!(server.serve_connection_with_upgrades(io, service));
                 match server.as_mut().race(rocket.shutdown()).await.left() {
                     Some(result) => result,
@@ -198,7 +275,7 @@ impl Rocket<Orbit> {
         Ok(())
     }
 
-    #[cfg(feature = "http3-preview")]
+    #[cfg(feature = "http3")]
     async fn serve3(self: Arc<Self>, listener: crate::listener::quic::QuicListener) -> Result<()> {
         let rocket = self.clone();
         let listener = Arc::new(listener);
@@ -206,23 +283,19 @@ impl Rocket<Orbit> {
             let (listener, rocket) = (listener.clone(), rocket.clone());
             spawn_inspect(|e: &io::Error| log_server_error(e), async move {
                 let mut stream = listener.connect(accept).race_io(rocket.shutdown()).await?;
-                while let Some(mut conn) = stream.accept().race_io(rocket.shutdown()).await? {
-                    let rocket = rocket.clone();
-                    spawn_inspect(|e: &io::Error| log_server_error(e), async move {
-                        let meta = ConnectionMeta::new(conn.endpoint(), None, None);
-                        let rx = conn.rx.cancellable(rocket.shutdown.clone());
-                        let response = rocket.clone()
-                            .service(conn.parts, rx, None, meta)
-                            .map_err(io::Error::other)
-                            .race_io(rocket.shutdown.mercy.clone())
-                            .await?;
-
-                        let grace = rocket.shutdown.grace.clone();
-                        match conn.tx.send_response(response).race(grace).await.left() {
-                            Some(result) => result,
-                            None => Ok(conn.tx.cancel()),
-                        }
-                    });
+                while let Some(result) = stream.accept().race(rocket.shutdown()).await.left() {
+                    let Some(conn) = result? else { return Ok(()) };
+                    let request = rocket.clone().serve3_request(conn);
+                    spawn_inspect(|e: &io::Error| log_server_error(e), request);
+                }
+
+                // Send a `GOAWAY`, refusing new requests, then drive the
+                // connection until in-flight requests complete.
+                let mercy = rocket.shutdown.mercy.clone();
+                stream.shutdown().race_io(mercy.clone()).await?;
+                while let Some(conn) = stream.accept().race_io(mercy.clone()).await? {
+                    let request = rocket.clone().serve3_request(conn);
+                    spawn_inspect(|e: &io::Error| log_server_error(e), request);
                 }
 
                 Ok(())
@@ -231,4 +304,99 @@ impl Rocket<Orbit> {
 
         Ok(())
     }
+
+    #[cfg(feature = "http3")]
+    async fn serve3_request(
+        self: Arc<Self>,
+        mut conn: crate::listener::quic::H3Connection,
+    ) -> io::Result<()> {
+        let meta = ConnectionMeta::new(conn.endpoint(), None, None);
+        if conn.rewrite_extended_connect() {
+            return self.serve3_upgrade(conn, meta).await;
+        }
+
+        let rx = conn.rx.cancellable(self.shutdown.clone());
+        let upgrade = None::<futures::future::Pending<io::Result<IoStream>>>;
+        let response = self.clone()
+            .service(conn.parts, rx, upgrade, meta)
+            .map_err(io::Error::other)
+            .race_io(self.shutdown.mercy.clone())
+            .await?;
+
+        let grace = self.shutdown.grace.clone();
+        match conn.tx.send_response(response).race(grace).await.left() {
+            Some(result) => result,
+            None => Ok(conn.tx.cancel()),
+        }
+    }
+
+    /// Serves an extended `CONNECT` request, already rewritten as an upgrade
+    /// request. If the response accepts the upgrade, the request stream is
+    /// tunneled to the I/O handler via an in-memory duplex stream.
+    #[cfg(feature = "http3")]
+    async fn serve3_upgrade(
+        self: Arc<Self>,
+        conn: crate::listener::quic::H3Connection,
+        meta: ConnectionMeta,
+    ) -> io::Result<()> {
+        use crate::listener::quic;
+
+        let (io_tx, io_rx) = tokio::sync::oneshot::channel();
+        let upgrade = io_rx.map_err(io::Error::other);
+        let (mut tx, rx) = (conn.tx, conn.rx);
+        let response = self.clone()
+            .service(conn.parts, (), Some(upgrade), meta)
+            .map_err(io::Error::other)
+            .race_io(self.shutdown.mercy.clone())
+            .await?;
+
+        if response.status() != http::StatusCode::SWITCHING_PROTOCOLS {
+            let grace = self.shutdown.grace.clone();
+            return match tx.send_response(response).race(grace).await.left() {
+                Some(result) => result,
+                None => Ok(tx.cancel()),
+            };
+        }
+
+        tx.send_upgrade(response).await?;
+        let (local, remote) = tokio::io::duplex(64 * 1024);
+        let _ = io_tx.send(IoStream::from(local));
+        quic::tunnel(tx, rx, remote).race_io(self.shutdown.mercy.clone()).await
+    }
+}
+
+/// If this is an HTTP/2 extended `CONNECT` request for a WebSocket (RFC 8441),
+/// rewrites it as an HTTP/1.1-style upgrade request so that it's handled by the
+/// regular upgrade machinery. Returns `true` if it was rewritten.
+fn rewrite_extended_connect(parts: &mut http::request::Parts) -> bool {
+    use http::header::{self, HeaderValue};
+    use crate::request::ExtendedConnect;
+
+    #[cfg(feature = "http2")]
+    let protocol = parts.extensions.get::<hyper::ext::Protocol>().map(|p| p.as_str());
+
+    #[cfg(not(feature = "http2"))]
+    let protocol: Option<&str> = None;
+
+    if parts.method != http::Method::CONNECT || protocol != Some("websocket") {
+        return false;
+    }
+
+    parts.method = http::Method::GET;
+    parts.headers.insert(header::CONNECTION, HeaderValue::from_static("upgrade"));
+    parts.headers.insert(header::UPGRADE, HeaderValue::from_static("websocket"));
+    parts.extensions.insert(ExtendedConnect("websocket".into()));
+    true
+}
+
+/// Accepts a rewritten extended `CONNECT` request: an upgrade is signaled with
+/// a `200 OK` response, without connection-specific headers, in HTTP/2.
+fn accept_extended_connect<B>(mut response: hyper::Response<B>) -> hyper::Response<B> {
+    if response.status() == hyper::StatusCode::SWITCHING_PROTOCOLS {
+        *response.status_mut() = hyper::StatusCode::OK;
+        response.headers_mut().remove(http::header::CONNECTION);
+        response.headers_mut().remove(http::header::UPGRADE);
+    }
+
+    response
 }
@@ -0,0 +1,1061 @@
+//! Automatic certificate provisioning via ACME (e.g, Let's Encrypt).
+//!
+//! The [`Acme`] fairing obtains a certificate for the configured domains from
+//! an ACME certificate authority, serves it for all TLS connections, and
+//! renews it before it expires. It is enabled via the `acme` crate feature:
+//!
+//! ```toml
+//! [dependencies]
+//! rocket = { version = "0.6.0-dev", features = ["acme"] }
+//! ```
+//!
+//! Attach the fairing to use it:
+//!
+//! ```rust,no_run
+//! # #[macro_use] extern crate rocket;
+//! use rocket::tls::acme::Acme;
+//!
+//! #[launch]
+//! fn rocket() -> _ {
+//!     rocket::build().attach(Acme)
+//! }
+//! ```
+//!
+//! # Configuration
+//!
+//! The fairing is configured via the `acme` configuration key, which
+//! deserializes into an [`AcmeConfig`]. In `Rocket.toml`, configuration might
+//! look like:
+//!
+//! ```toml
+//! [default.acme]
+//! domains = ["example.com", "www.example.com"]
+//! contact = ["mailto:admin@example.com"]
+//! terms_agreed = true
+//! cache = "acme-cache"
+//! ```
+//!
+//! If no `tls` configuration is provided, the fairing configures TLS with a
+//! self-signed placeholder certificate which is presented only until the first
+//! certificate is obtained. If a `tls` configuration _is_ provided, its
+//! certificate is used as the placeholder, and its cipher suite and mutual TLS
+//! settings are applied to the provisioned certificate.
+//!
+//! # Challenges
+//!
+//! The certificate authority verifies control of each domain with the
+//! `tls-alpn-01` challenge, which is answered during the TLS handshake. The
+//! certificate authority connects on port 443, so Rocket must be reachable on
+//! port 443 for every domain. Other challenge types are not supported: `http-01`
+//! requires a plaintext listener on port 80, which Rocket does not provide, and
+//! `dns-01` requires access to the domain's DNS records.
+//!
+//! # Caching
+//!
+//! The account key and the provisioned certificate and key are stored in the
+//! `cache` directory and reused across restarts. The directory contains private
+//! keys and should be protected accordingly.
+//!
+//! # Testing
+//!
+//! Setting `directory` to the directory URL of a local ACME server, such as
+//! [Pebble], exercises the entire issuance flow without contacting a public
+//! certificate authority. If the test server's certificate is signed by a
+//! private root, point the `SSL_CERT_FILE` environment variable at that root.
+//!
+//! [Pebble]: https://github.com/letsencrypt/pebble
+
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::collections::HashMap;
+
+use figment::value::magic::RelativePathBuf;
+use serde::{Deserialize, Serialize};
+use parking_lot::RwLock;
+use instant_acme::{Account, AccountCredentials, HttpClient, NewAccount, NewOrder, Identifier};
+use instant_acme::{AuthorizationStatus, ChallengeType, OrderStatus};
+use rcgen::{CertificateParams, CustomExtension, DistinguishedName, KeyPair};
+use rustls::server::ResolvesServerCert;
+use rustls::sign::CertifiedKey;
+
+use crate::{Build, Orbit, Rocket};
+use crate::fairing::{self, Fairing, Info, Kind};
+use crate::figment::value::magic::Either;
+use crate::shutdown::Shutdown;
+use crate::util::FutureExt;
+use crate::tls::{Resolver, TlsConfig, ClientHello, ServerConfig, Error, Result};
+
+/// The ALPN protocol identifier used by the `tls-alpn-01` challenge.
+const ACME_TLS_ALPN: &[u8] = b"acme-tls/1";
+
+/// A fairing that provisions and renews TLS certificates via ACME.
+///
+/// See the [module level docs](crate::tls::acme) for details.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Acme;
+
+/// ACME configuration, read from the `acme` configuration key.
+///
+/// | key            | required  | default                          | type              |
+/// |----------------|-----------|----------------------------------|-------------------|
+/// | `domains`      | **_yes_** |                                  | array of strings  |
+/// | `terms_agreed` | **_yes_** | `false`                          | boolean           |
+/// | `contact`      | no        | `[]`                             | array of URLs     |
+/// | `directory`    | no        | [`AcmeConfig::LETS_ENCRYPT`]     | URL string        |
+/// | `cache`        | no        | `"acme"`                         | path              |
+/// | `renew_days`   | no        | `30`                             | integer           |
+///
+/// `terms_agreed` must be set to `true` to indicate agreement to the
+/// certificate authority's terms of service; provisioning is refused
+/// otherwise. A certificate is renewed once it expires in fewer than
+/// `renew_days` days.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::tls::acme::AcmeConfig;
+/// use rocket::figment::Figment;
+///
+/// let figment = Figment::new()
+///     .merge(("domains", ["example.com"]))
+///     .merge(("terms_agreed", true));
+///
+/// let config: AcmeConfig = figment.extract().unwrap();
+/// assert_eq!(config.domains, ["example.com"]);
+/// assert_eq!(config.directory, AcmeConfig::LETS_ENCRYPT);
+/// assert_eq!(config.renew_days, 30);
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct AcmeConfig {
+    /// Domains to include in the certificate.
+    pub domains: Vec<String>,
+    /// Whether the certificate authority's terms of service are agreed to.
+    #[serde(default)]
+    pub terms_agreed: bool,
+    /// Contact URLs for the ACME account, e.g, `mailto:admin@example.com`.
+    #[serde(default)]
+    pub contact: Vec<String>,
+    /// URL of the ACME directory of the certificate authority.
+    #[serde(default = "AcmeConfig::default_directory")]
+    pub directory: String,
+    /// Directory in which to cache the account key and certificates.
+    #[serde(default = "AcmeConfig::default_cache")]
+    pub cache: RelativePathBuf,
+    /// Renew certificates expiring in fewer than this many days.
+    #[serde(default = "AcmeConfig::default_renew_days")]
+    pub renew_days: u32,
+}
+
+/// Returns an HTTP client for requests to the ACME directory.
+type HttpClientFn = Box<dyn Fn() -> Box<dyn HttpClient> + Send + Sync>;
+
+/// Shared state between the resolver and the renewal task.
+struct AcmeState {
+    config: AcmeConfig,
+    tls: TlsConfig,
+    /// The server configuration for the current certificate, if any.
+    current: RwLock<Option<(Arc<ServerConfig>, SystemTime)>>,
+    /// Server configurations answering `tls-alpn-01` challenges, by domain.
+    alpn_challenges: RwLock<HashMap<String, Arc<ServerConfig>>>,
+    /// The HTTP client for the ACME directory. `None` uses the default client.
+    http: Option<HttpClientFn>,
+}
+
+/// The `Resolver` presenting the provisioned or challenge certificate.
+#[derive(Clone)]
+struct AcmeResolver(Arc<AcmeState>);
+
+impl AcmeConfig {
+    /// The production Let's Encrypt directory URL.
+    pub const LETS_ENCRYPT: &'static str = "https://acme-v02.api.letsencrypt.org/directory";
+
+    /// The staging Let's Encrypt directory URL, for testing.
+    pub const LETS_ENCRYPT_STAGING: &'static str =
+        "https://acme-staging-v02.api.letsencrypt.org/directory";
+
+    fn default_directory() -> String {
+        Self::LETS_ENCRYPT.into()
+    }
+
+    fn default_cache() -> RelativePathBuf {
+        PathBuf::from("acme").into()
+    }
+
+    fn default_renew_days() -> u32 {
+        30
+    }
+
+    fn validate(&self) -> Result<()> {
+        let error = |msg: String| Err(Error::Config(figment::Error::from(msg)));
+        if !self.terms_agreed {
+            return error("`acme.terms_agreed` must be `true` to request certificates".into());
+        }
+
+        if self.domains.is_empty() {
+            return error("`acme.domains` must contain at least one domain".into());
+        }
+
+        if let Some(domain) = self.domains.iter().find(|d| d.contains('*')) {
+            return error(format!("wildcard domain `{domain}` requires unsupported dns-01"));
+        }
+
+        Ok(())
+    }
+
+    fn cache_path(&self, name: &str) -> PathBuf {
+        self.cache.relative().join(name)
+    }
+
+    fn cert_path(&self) -> PathBuf {
+        self.cache_path(&format!("{}.crt.pem", self.domains[0]))
+    }
+
+    fn key_path(&self) -> PathBuf {
+        self.cache_path(&format!("{}.key.pem", self.domains[0]))
+    }
+
+    fn renew_window(&self) -> Duration {
+        Duration::from_secs(u64::from(self.renew_days) * 24 * 60 * 60)
+    }
+}
+
+impl AcmeState {
+    /// Returns a server configuration presenting `certs` and `key`, both PEM
+    /// encoded, with the settings in `self.tls`.
+    async fn server_config(&self, certs: String, key: String) -> Result<Arc<ServerConfig>> {
+        let tls = TlsConfig {
+            certs: Either::Right(certs.into_bytes()),
+            key: Either::Right(key.into_bytes()),
+            sni: Default::default(),
+            resolver: None,
+            ..self.tls.clone()
+        };
+
+        Ok(Arc::new(tls.server_config().await?))
+    }
+
+    /// Loads a previously provisioned certificate from the cache, if any.
+    async fn load_cached(&self) -> Result<()> {
+        let (cert_path, key_path) = (self.config.cert_path(), self.config.key_path());
+        if !cert_path.exists() || !key_path.exists() {
+            return Ok(());
+        }
+
+        let certs = tokio::fs::read_to_string(cert_path).await?;
+        let key = tokio::fs::read_to_string(key_path).await?;
+        let expiry = expiry(&certs)?;
+        let config = self.server_config(certs, key).await?;
+        *self.current.write() = Some((config, expiry));
+        Ok(())
+    }
+
+    /// Returns the time at which the current certificate should be renewed.
+    fn renew_at(&self) -> SystemTime {
+        match &*self.current.read() {
+            Some((_, expiry)) => *expiry - self.config.renew_window(),
+            None => SystemTime::now(),
+        }
+    }
+
+    async fn account(&self) -> Result<Account> {
+        let path = self.config.cache_path("account.json");
+        if let Ok(json) = tokio::fs::read(&path).await {
+            let credentials: AccountCredentials = serde_json::from_slice(&json)
+                .map_err(|e| Error::Acme(e.into()))?;
+
+            let account = match &self.http {
+                Some(http) => Account::from_credentials_and_http(credentials, http()).await,
+                None => Account::from_credentials(credentials).await,
+            };
+
+            return account.map_err(|e| Error::Acme(e.into()));
+        }
+
+        let contact: Vec<&str> = self.config.contact.iter().map(|s| s.as_str()).collect();
+        let new_account = NewAccount {
+            contact: &contact,
+            terms_of_service_agreed: self.config.terms_agreed,
+            only_return_existing: false,
+        };
+
+        let directory = &self.config.directory;
+        let (account, credentials) = match &self.http {
+            Some(http) => Account::create_with_http(&new_account, directory, None, http()).await,
+            None => Account::create(&new_account, directory, None).await,
+        }.map_err(|e| Error::Acme(e.into()))?;
+
+        let json = serde_json::to_vec_pretty(&credentials).map_err(|e| Error::Acme(e.into()))?;
+        tokio::fs::create_dir_all(self.config.cache.relative()).await?;
+        tokio::fs::write(&path, json).await?;
+        Ok(account)
+    }
+
+    /// Provisions a new certificate, caches it, and begins presenting it.
+    async fn provision(&self) -> Result<()> {
+        let acme_err = |e: instant_acme::Error| Error::Acme(e.into());
+        let account = self.account().await?;
+        let identifiers: Vec<_> = self.config.domains.iter()
+            .map(|domain| Identifier::Dns(domain.clone()))
+            .collect();
+
+        let mut order = account.new_order(&NewOrder { identifiers: &identifiers })
+            .await
+            .map_err(acme_err)?;
+
+        for authz in order.authorizations().await.map_err(acme_err)? {
+            if !matches!(authz.status, AuthorizationStatus::Pending) {
+                continue;
+            }
+
+            let Identifier::Dns(domain) = &authz.identifier;
+            let challenge = authz.challenges.iter()
+                .find(|c| c.r#type == ChallengeType::TlsAlpn01)
+                .ok_or_else(|| {
+                    let msg = format!("no tls-alpn-01 challenge offered for {domain}");
+                    Error::Acme(msg.into())
+                })?;
+
+            let digest = order.key_authorization(challenge).digest();
+            let config = alpn_challenge_config(&self.tls, domain, digest.as_ref())?;
+            self.alpn_challenges.write().insert(domain.to_ascii_lowercase(), config);
+
+            order.set_challenge_ready(&challenge.url).await.map_err(acme_err)?;
+        }
+
+        let result = self.finalize(&mut order).await;
+        self.alpn_challenges.write().clear();
+        let (certs, key) = result?;
+
+        tokio::fs::create_dir_all(self.config.cache.relative()).await?;
+        tokio::fs::write(self.config.cert_path(), &certs).await?;
+        tokio::fs::write(self.config.key_path(), &key).await?;
+
+        let expiry = expiry(&certs)?;
+        let config = self.server_config(certs, key).await?;
+        *self.current.write() = Some((config, expiry));
+        Ok(())
+    }
+
+    /// Waits for `order` to become ready, then finalizes it, returning the PEM
+    /// encoded certificate chain and private key.
+    async fn finalize(&self, order: &mut instant_acme::Order) -> Result<(String, String)> {
+        let acme_err = |e: instant_acme::Error| Error::Acme(e.into());
+        let mut delay = Duration::from_millis(250);
+        loop {
+            let state = order.refresh().await.map_err(acme_err)?;
+            match state.status {
+                OrderStatus::Ready | OrderStatus::Valid => break,
+                OrderStatus::Invalid => {
+                    let msg = format!("order invalid: {:?}", state.error);
+                    return Err(Error::Acme(msg.into()));
+                }
+                _ if delay > Duration::from_secs(60) => {
+                    return Err(Error::Acme("timed out waiting for order".into()));
+                }
+                _ => {
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+            }
+        }
+
+        let key_pair = KeyPair::generate().map_err(|e| Error::Acme(e.into()))?;
+        let mut params = CertificateParams::new(self.config.domains.clone())
+            .map_err(|e| Error::Acme(e.into()))?;
+
+        params.distinguished_name = DistinguishedName::new();
+        let csr = params.serialize_request(&key_pair).map_err(|e| Error::Acme(e.into()))?;
+        order.finalize(csr.der()).await.map_err(acme_err)?;
+
+        let mut delay = Duration::from_millis(250);
+        loop {
+            match order.certificate().await.map_err(acme_err)? {
+                Some(certs) => return Ok((certs, key_pair.serialize_pem())),
+                None if delay > Duration::from_secs(60) => {
+                    return Err(Error::Acme("timed out waiting for certificate".into()));
+                }
+                None => {
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+            }
+        }
+    }
+
+    /// Provisions certificates as they near expiry until `shutdown`.
+    async fn renew(self: Arc<Self>, shutdown: Shutdown) {
+        let mut retry = Duration::from_secs(60);
+        loop {
+            let wait = self.renew_at()
+                .duration_since(SystemTime::now())
+                .unwrap_or(Duration::ZERO)
+                .min(Duration::from_secs(24 * 60 * 60));
+
+            if !wait.is_zero() {
+                if tokio::time::sleep(wait).race(shutdown.clone()).await.is_right() {
+                    return;
+                }
+
+                continue;
+            }
+
+            let domains = self.config.domains.join(", ");
+            info!(%domains, directory = %self.config.directory, "requesting ACME certificate");
+            let result = match self.provision().race(shutdown.clone()).await {
+                either::Either::Left(result) => result,
+                either::Either::Right(_) => return,
+            };
+
+            match result {
+                Ok(()) => {
+                    info!(%domains, "ACME certificate provisioned");
+                    retry = Duration::from_secs(60);
+                }
+                Err(e) => {
+                    error!(%domains, reason = %e, retry = ?retry, "ACME provisioning failed");
+                    if tokio::time::sleep(retry).race(shutdown.clone()).await.is_right() {
+                        return;
+                    }
+
+                    retry = (retry * 2).min(Duration::from_secs(6 * 60 * 60));
+                }
+            }
+        }
+    }
+}
+
+/// Returns the expiry of the first certificate in the PEM-encoded `certs`.
+fn expiry(certs: &str) -> Result<SystemTime> {
+    let chain = crate::tls::sni::load_certs(&mut certs.as_bytes())?;
+    let der = chain.first().ok_or_else(|| Error::Acme("empty certificate chain".into()))?;
+    let (_, cert) = x509_parser::parse_x509_certificate(der)
+        .map_err(|e| Error::Acme(e.to_string().into()))?;
+
+    let not_after = cert.validity().not_after.timestamp();
+    Ok(UNIX_EPOCH + Duration::from_secs(not_after.max(0) as u64))
+}
+
+/// Returns a self-signed certificate for `domains` as PEM-encoded certificate
+/// and key.
+fn self_signed(domains: &[String]) -> Result<(String, String)> {
+    let key_pair = KeyPair::generate().map_err(|e| Error::Acme(e.into()))?;
+    let cert = CertificateParams::new(domains.to_vec())
+        .and_then(|params| params.self_signed(&key_pair))
+        .map_err(|e| Error::Acme(e.into()))?;
+
+    Ok((cert.pem(), key_pair.serialize_pem()))
+}
+
+/// Returns a server configuration answering a `tls-alpn-01` challenge for
+/// `domain` with the key authorization `digest`, per RFC 8737.
+fn alpn_challenge_config(
+    tls: &TlsConfig,
+    domain: &str,
+    digest: &[u8],
+) -> Result<Arc<ServerConfig>> {
+    let key_pair = KeyPair::generate().map_err(|e| Error::Acme(e.into()))?;
+    let mut params = CertificateParams::new(vec![domain.to_string()])
+        .map_err(|e| Error::Acme(e.into()))?;
+
+    params.custom_extensions = vec![CustomExtension::new_acme_identifier(digest)];
+    let cert = params.self_signed(&key_pair).map_err(|e| Error::Acme(e.into()))?;
+    let key = rustls::pki_types::PrivateKeyDer::try_from(key_pair.serialize_der())
+        .map_err(|e| Error::Acme(e.into()))?;
+
+    // `with_single_cert()` checks that the key matches the certificate by
+    // parsing it with webpki, which rejects the critical `acmeIdentifier`
+    // extension. Install the certificate via a resolver, which doesn't.
+    let provider = Arc::new(tls.default_crypto_provider());
+    let key = provider.key_provider.load_private_key(key)?;
+    let resolver = ChallengeCert(Arc::new(CertifiedKey::new(vec![cert.der().clone()], key)));
+    let mut config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_cert_resolver(Arc::new(resolver));
+
+    config.alpn_protocols = vec![ACME_TLS_ALPN.to_vec()];
+    Ok(Arc::new(config))
+}
+
+/// Resolves every handshake to a `tls-alpn-01` challenge certificate.
+#[derive(Debug)]
+struct ChallengeCert(Arc<CertifiedKey>);
+
+impl ResolvesServerCert for ChallengeCert {
+    fn resolve(&self, _: rustls::server::ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.0.clone())
+    }
+}
+
+#[crate::async_trait]
+impl Resolver for AcmeResolver {
+    async fn resolve(&self, hello: ClientHello<'_>) -> Option<Arc<ServerConfig>> {
+        let is_challenge = hello.alpn().is_some_and(|mut p| p.any(|p| p == ACME_TLS_ALPN));
+        if is_challenge {
+            let domain = hello.server_name()?.to_ascii_lowercase();
+            return self.0.alpn_challenges.read().get(&domain).cloned();
+        }
+
+        self.0.current.read().as_ref().map(|(config, _)| config.clone())
+    }
+}
+
+impl Acme {
+    async fn init(rocket: Rocket<Build>) -> Result<Rocket<Build>, (Rocket<Build>, Error)> {
+        let config: AcmeConfig = match rocket.figment().extract_inner("acme") {
+            Ok(config) => config,
+            Err(e) => return Err((rocket, e.into())),
+        };
+
+        if let Err(e) = config.validate() {
+            return Err((rocket, e));
+        }
+
+        // Configure TLS with a placeholder certificate if it isn't configured.
+        let rocket = match rocket.figment().find_value("tls") {
+            Ok(_) => rocket,
+            Err(_) => match self_signed(&config.domains) {
+                Ok((certs, key)) => {
+                    let tls = TlsConfig::from_bytes(certs.as_bytes(), key.as_bytes());
+                    let figment = rocket.figment().clone().merge(("tls", tls));
+                    rocket.reconfigure(figment)
+                }
+                Err(e) => return Err((rocket, e)),
+            }
+        };
+
+        let tls: TlsConfig = match rocket.figment().extract_inner("tls") {
+            Ok(tls) => tls,
+            Err(e) => return Err((rocket, e.into())),
+        };
+
+        let state = Arc::new(AcmeState {
+            config,
+            tls,
+            current: RwLock::new(None),
+            alpn_challenges: RwLock::new(HashMap::new()),
+            http: None,
+        });
+
+        if let Err(e) = state.load_cached().await {
+            warn!(reason = %e, "ignoring invalid cached ACME certificate");
+        }
+
+        Ok(rocket
+            .manage(Arc::new(AcmeResolver(state.clone())) as Arc<dyn Resolver>)
+            .manage(state))
+    }
+}
+
+#[crate::async_trait]
+impl Fairing for Acme {
+    fn info(&self) -> Info {
+        Info {
+            name: "ACME",
+            kind: Kind::Ignite | Kind::Liftoff | Kind::Singleton
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        match Acme::init(rocket).await {
+            Ok(rocket) => Ok(rocket),
+            Err((rocket, e)) => {
+                error!(reason = %e, "ACME fairing failed to initialize");
+                Err(rocket)
+            }
+        }
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<Orbit>) {
+        if let Some(state) = rocket.state::<Arc<AcmeState>>() {
+            tokio::spawn(state.clone().renew(rocket.shutdown()));
+        }
+    }
+}
+
+impl fmt::Debug for AcmeState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AcmeState")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::{OnceLock, Weak};
+
+    use base64::prelude::{Engine, BASE64_URL_SAFE_NO_PAD};
+    use bytes::Bytes;
+    use figment::{Figment, providers::{Format, Toml}};
+    use http::{Method, StatusCode};
+    use http_body_util::{BodyExt, Full};
+    use instant_acme::BytesResponse;
+    use parking_lot::Mutex;
+    use rcgen::{BasicConstraints, Certificate, CertificateSigningRequestParams, IsCa};
+    use rustls::{ClientConfig, DigitallySignedStruct, SignatureScheme};
+    use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified};
+    use rustls::client::danger::ServerCertVerifier;
+    use rustls::crypto::CryptoProvider;
+    use rustls::pki_types::{CertificateDer, ServerName, SubjectPublicKeyInfoDer, UnixTime};
+    use rustls::server::Acceptor;
+    use serde_json::{json, Value};
+    use sha2::{Digest, Sha256};
+    use tokio_rustls::{LazyConfigAcceptor, TlsConnector};
+
+    /// An in-memory stand-in for an ACME certificate authority, in the spirit
+    /// of Pebble. It validates `tls-alpn-01` challenges by completing a TLS
+    /// handshake against the fairing's resolver and signs the submitted CSR.
+    #[derive(Clone)]
+    struct StandIn(Arc<StandInState>);
+
+    struct StandInState {
+        ca: Certificate,
+        ca_key: KeyPair,
+        target: OnceLock<Weak<AcmeState>>,
+        session: Mutex<Session>,
+    }
+
+    #[derive(Default)]
+    struct Session {
+        accounts: usize,
+        thumbprint: String,
+        domains: Vec<String>,
+        validations: Vec<Option<bool>>,
+        certs: Option<String>,
+    }
+
+    impl StandIn {
+        const BASE: &'static str = "https://acme.test";
+        const DIRECTORY: &'static str = "https://acme.test/dir";
+
+        fn new() -> Self {
+            let ca_key = KeyPair::generate().unwrap();
+            let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+            params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            let ca = params.self_signed(&ca_key).unwrap();
+            StandIn(Arc::new(StandInState {
+                ca, ca_key,
+                target: OnceLock::new(),
+                session: Mutex::new(Session::default()),
+            }))
+        }
+
+        /// Validates challenges against the resolver for `state`.
+        fn target(&self, state: &Arc<AcmeState>) {
+            self.0.target.set(Arc::downgrade(state)).unwrap();
+        }
+
+        fn client(&self) -> HttpClientFn {
+            let stand_in = self.clone();
+            Box::new(move || Box::new(stand_in.clone()) as Box<dyn HttpClient>)
+        }
+
+        fn url(path: &str) -> String {
+            format!("{}{path}", Self::BASE)
+        }
+
+        fn order(&self) -> Value {
+            let session = self.0.session.lock();
+            let status = match &session.validations {
+                _ if session.certs.is_some() => "valid",
+                v if v.iter().any(|v| *v == Some(false)) => "invalid",
+                v if v.iter().all(|v| *v == Some(true)) => "ready",
+                _ => "pending",
+            };
+
+            let authorizations: Vec<_> = (0..session.domains.len())
+                .map(|i| Self::url(&format!("/authz/{i}")))
+                .collect();
+
+            json!({
+                "status": status,
+                "authorizations": authorizations,
+                "finalize": Self::url("/finalize"),
+                "certificate": session.certs.as_ref().map(|_| Self::url("/cert")),
+            })
+        }
+
+        fn challenge(&self, i: usize) -> Value {
+            let status = match self.0.session.lock().validations[i] {
+                Some(true) => "valid",
+                Some(false) => "invalid",
+                None => "pending",
+            };
+
+            json!({
+                "type": "tls-alpn-01",
+                "url": Self::url(&format!("/chall/{i}")),
+                "token": format!("token-{i}"),
+                "status": status,
+            })
+        }
+
+        fn authorization(&self, i: usize) -> Value {
+            let challenge = self.challenge(i);
+            let domain = self.0.session.lock().domains[i].clone();
+            json!({
+                "identifier": { "type": "dns", "value": domain },
+                "status": if challenge["status"] == "valid" { "valid" } else { "pending" },
+                "challenges": [challenge],
+            })
+        }
+
+        /// Performs `tls-alpn-01` validation of the `i`th domain.
+        async fn validate(&self, i: usize) -> bool {
+            let (domain, expected) = {
+                let session = self.0.session.lock();
+                let key_auth = format!("token-{i}.{}", session.thumbprint);
+                (session.domains[i].clone(), sha256(key_auth.as_bytes()))
+            };
+
+            let state = self.0.target.get().and_then(|state| state.upgrade()).unwrap();
+            let resolver = AcmeResolver(state);
+            match handshake(&resolver, &domain, Some(ACME_TLS_ALPN)).await {
+                Some((cert, alpn)) => {
+                    alpn.as_deref() == Some(ACME_TLS_ALPN)
+                        && acme_identifier(&cert).as_deref() == Some(&expected[..])
+                }
+                None => false,
+            }
+        }
+
+        async fn respond(self, req: http::Request<Full<Bytes>>) -> BytesResponse {
+            let (parts, body) = req.into_parts();
+            let body = body.collect().await.unwrap().to_bytes();
+            let (header, payload) = match parts.method {
+                Method::POST => {
+                    let jws: Value = serde_json::from_slice(&body).unwrap();
+                    (decode(&jws["protected"]), decode(&jws["payload"]))
+                }
+                _ => (Value::Null, Value::Null),
+            };
+
+            let path = parts.uri.path();
+            let index = |prefix: &str| path.strip_prefix(prefix).and_then(|i| i.parse().ok());
+            match path {
+                "/dir" => reply(StatusCode::OK, None, json!({
+                    "newNonce": Self::url("/nonce"),
+                    "newAccount": Self::url("/account"),
+                    "newOrder": Self::url("/order"),
+                })),
+                "/nonce" => reply(StatusCode::OK, None, Value::Null),
+                "/account" => {
+                    let jwk = &header["jwk"];
+                    let thumbprint = format!(
+                        r#"{{"crv":{},"kty":{},"x":{},"y":{}}}"#,
+                        jwk["crv"], jwk["kty"], jwk["x"], jwk["y"]
+                    );
+
+                    let thumbprint = BASE64_URL_SAFE_NO_PAD.encode(sha256(thumbprint.as_bytes()));
+                    let mut session = self.0.session.lock();
+                    session.accounts += 1;
+                    session.thumbprint = thumbprint;
+                    reply(StatusCode::CREATED, Some(Self::url("/account/1")), json!({}))
+                }
+                "/order" => {
+                    let domains: Vec<String> = payload["identifiers"].as_array().unwrap()
+                        .iter()
+                        .map(|id| id["value"].as_str().unwrap().to_string())
+                        .collect();
+
+                    let mut session = self.0.session.lock();
+                    session.validations = vec![None; domains.len()];
+                    session.domains = domains;
+                    session.certs = None;
+                    drop(session);
+
+                    reply(StatusCode::CREATED, Some(Self::url("/order/1")), self.order())
+                }
+                "/order/1" => reply(StatusCode::OK, None, self.order()),
+                "/finalize" => {
+                    let csr = BASE64_URL_SAFE_NO_PAD.decode(payload["csr"].as_str().unwrap());
+                    let csr = rustls::pki_types::CertificateSigningRequestDer::from(csr.unwrap());
+                    let cert = CertificateSigningRequestParams::from_der(&csr).unwrap()
+                        .signed_by(&self.0.ca, &self.0.ca_key)
+                        .unwrap();
+
+                    self.0.session.lock().certs = Some(cert.pem() + &self.0.ca.pem());
+                    reply(StatusCode::OK, None, self.order())
+                }
+                "/cert" => {
+                    let certs = self.0.session.lock().certs.clone().unwrap();
+                    BytesResponse {
+                        parts: http::Response::new(()).into_parts().0,
+                        body: Box::new(Bytes::from(certs)),
+                    }
+                }
+                _ => if let Some(i) = index("/authz/") {
+                    reply(StatusCode::OK, None, self.authorization(i))
+                } else if let Some(i) = index("/chall/") {
+                    let valid = self.validate(i).await;
+                    self.0.session.lock().validations[i] = Some(valid);
+                    reply(StatusCode::OK, None, self.challenge(i))
+                } else {
+                    reply(StatusCode::NOT_FOUND, None, json!({
+                        "type": "urn:ietf:params:acme:error:malformed",
+                        "detail": format!("unknown resource {path}"),
+                    }))
+                }
+            }
+        }
+    }
+
+    impl HttpClient for StandIn {
+        fn request(
+            &self,
+            req: http::Request<Full<Bytes>>,
+        ) -> Pin<Box<dyn Future<Output = Result<BytesResponse, instant_acme::Error>> + Send>> {
+            let stand_in = self.clone();
+            Box::pin(async move { Ok(stand_in.respond(req).await) })
+        }
+    }
+
+    fn reply(status: StatusCode, location: Option<String>, body: Value) -> BytesResponse {
+        let mut response = http::Response::builder()
+            .status(status)
+            .header("Replay-Nonce", "nonce");
+
+        if let Some(location) = location {
+            response = response.header("Location", location);
+        }
+
+        let body = match body {
+            Value::Null => Bytes::new(),
+            body => Bytes::from(serde_json::to_vec(&body).unwrap()),
+        };
+
+        BytesResponse {
+            parts: response.body(()).unwrap().into_parts().0,
+            body: Box::new(body),
+        }
+    }
+
+    fn decode(value: &Value) -> Value {
+        match value.as_str() {
+            Some(b64) if !b64.is_empty() => {
+                let json = BASE64_URL_SAFE_NO_PAD.decode(b64).unwrap();
+                serde_json::from_slice(&json).unwrap()
+            }
+            _ => Value::Null,
+        }
+    }
+
+    fn sha256(data: &[u8]) -> Vec<u8> {
+        Sha256::digest(data).to_vec()
+    }
+
+    /// Returns the digest in the `acmeIdentifier` extension of `cert`, if any.
+    fn acme_identifier(cert: &CertificateDer<'_>) -> Option<Vec<u8>> {
+        let (_, cert) = x509_parser::parse_x509_certificate(cert).ok()?;
+        let ext = cert.extensions().iter()
+            .find(|ext| ext.oid.to_id_string() == "1.3.6.1.5.5.7.1.31")
+            .filter(|ext| ext.critical)?;
+
+        // The extension value is a DER-encoded, 32-byte OCTET STRING.
+        ext.value.strip_prefix(&[0x04, 0x20]).map(|digest| digest.to_vec())
+    }
+
+    /// Accepts any server certificate, as the certificate authority does when
+    /// validating `tls-alpn-01` challenges. Handshake signatures are verified
+    /// with the certificate's raw public key: webpki, which rustls otherwise
+    /// uses, rejects the critical `acmeIdentifier` extension.
+    #[derive(Debug)]
+    struct AcceptAny(Arc<CryptoProvider>);
+
+    impl ServerCertVerifier for AcceptAny {
+        fn verify_server_cert(
+            &self,
+            _: &CertificateDer<'_>,
+            _: &[CertificateDer<'_>],
+            _: &ServerName<'_>,
+            _: &[u8],
+            _: UnixTime,
+        ) -> Result<ServerCertVerified, rustls::Error> {
+            Ok(ServerCertVerified::assertion())
+        }
+
+        fn verify_tls12_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, rustls::Error> {
+            let algorithms = &self.0.signature_verification_algorithms;
+            rustls::crypto::verify_tls12_signature(message, cert, dss, algorithms)
+        }
+
+        fn verify_tls13_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, rustls::Error> {
+            let (_, cert) = x509_parser::parse_x509_certificate(cert)
+                .map_err(|_| rustls::CertificateError::BadEncoding)?;
+
+            let spki = SubjectPublicKeyInfoDer::from(cert.public_key().raw);
+            let algorithms = &self.0.signature_verification_algorithms;
+            rustls::crypto::verify_tls13_signature_with_raw_key(message, &spki, dss, algorithms)
+        }
+
+        fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+            self.0.signature_verification_algorithms.supported_schemes()
+        }
+    }
+
+    /// Completes an in-memory TLS 1.3 handshake with `resolver` for `domain`,
+    /// offering `alpn`. Returns the presented certificate and negotiated ALPN.
+    async fn handshake(
+        resolver: &AcmeResolver,
+        domain: &str,
+        alpn: Option<&[u8]>,
+    ) -> Option<(CertificateDer<'static>, Option<Vec<u8>>)> {
+        let provider = Arc::new(resolver.0.tls.default_crypto_provider());
+        let mut config = ClientConfig::builder_with_provider(provider.clone())
+            .with_protocol_versions(&[&rustls::version::TLS13])
+            .ok()?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(AcceptAny(provider)))
+            .with_no_client_auth();
+
+        config.alpn_protocols = alpn.into_iter().map(|p| p.to_vec()).collect();
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let server = async move {
+            let handshake = LazyConfigAcceptor::new(Acceptor::default(), server_io).await.ok()?;
+            let config = resolver.resolve(handshake.client_hello()).await?;
+            handshake.into_stream(config).await.ok()
+        };
+
+        let name = ServerName::try_from(domain.to_string()).ok()?;
+        let client = TlsConnector::from(Arc::new(config)).connect(name, client_io);
+        let (server, client) = tokio::join!(server, client);
+        let (_server, client) = (server?, client.ok()?);
+        let session = client.get_ref().1;
+        let cert = session.peer_certificates()?.first()?.clone().into_owned();
+        Some((cert, session.alpn_protocol().map(|p| p.to_vec())))
+    }
+
+    fn state(config: &AcmeConfig, stand_in: &StandIn) -> Arc<AcmeState> {
+        let (certs, key) = self_signed(&config.domains).unwrap();
+        Arc::new(AcmeState {
+            config: config.clone(),
+            tls: TlsConfig::from_bytes(certs.as_bytes(), key.as_bytes()),
+            current: RwLock::new(None),
+            alpn_challenges: RwLock::new(HashMap::new()),
+            http: Some(stand_in.client()),
+        })
+    }
+
+    fn stand_in_config(cache: &std::path::Path) -> AcmeConfig {
+        Figment::new()
+            .merge(("domains", ["example.com", "www.example.com"]))
+            .merge(("terms_agreed", true))
+            .merge(("directory", StandIn::DIRECTORY))
+            .merge(("cache", cache))
+            .extract()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_acme_config() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file("Rocket.toml", r#"
+                [default.acme]
+                domains = ["example.com", "www.example.com"]
+                contact = ["mailto:admin@example.com"]
+                terms_agreed = true
+                directory = "https://localhost:14000/dir"
+                cache = "acme-cache"
+                renew_days = 10
+            "#)?;
+
+            let config: AcmeConfig = crate::Config::figment().extract_inner("acme")?;
+            assert_eq!(config.domains, ["example.com", "www.example.com"]);
+            assert_eq!(config.directory, "https://localhost:14000/dir");
+            assert_eq!(config.renew_window(), Duration::from_secs(10 * 24 * 60 * 60));
+            assert_eq!(config.cert_path(), jail.directory().join("acme-cache/example.com.crt.pem"));
+            assert!(config.validate().is_ok());
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_acme_config_validation() {
+        let config = |toml: &str| -> AcmeConfig {
+            Figment::from(Toml::string(toml)).extract().unwrap()
+        };
+
+        assert!(config("domains = ['a.com']").validate().is_err());
+        assert!(config("domains = [] \n terms_agreed = true").validate().is_err());
+        assert!(config("domains = ['*.a.com'] \n terms_agreed = true").validate().is_err());
+        assert!(config("domains = ['a.com'] \n terms_agreed = true").validate().is_ok());
+    }
+
+    #[test]
+    fn test_self_signed_expiry() {
+        let (certs, key) = self_signed(&["localhost".into()]).unwrap();
+        assert!(expiry(&certs).unwrap() > SystemTime::now());
+        assert!(TlsConfig::from_bytes(certs.as_bytes(), key.as_bytes()).validate().is_ok());
+    }
+
+    #[test]
+    fn test_tls_alpn_01_issuance() {
+        crate::async_test(async {
+            let cache = tempfile::tempdir().unwrap();
+            let config = stand_in_config(cache.path());
+            let stand_in = StandIn::new();
+            let state = state(&config, &stand_in);
+            stand_in.target(&state);
+
+            state.provision().await.unwrap();
+            assert!(state.alpn_challenges.read().is_empty());
+            assert!(config.cert_path().exists());
+            assert!(config.key_path().exists());
+            assert!(config.cache_path("account.json").exists());
+            assert!(state.renew_at() > SystemTime::now());
+
+            // Ordinary handshakes are presented the issued certificate.
+            let resolver = AcmeResolver(state.clone());
+            let (cert, alpn) = handshake(&resolver, "www.example.com", None).await.unwrap();
+            let (_, cert) = x509_parser::parse_x509_certificate(&cert).unwrap();
+            let (_, ca) = x509_parser::parse_x509_certificate(stand_in.0.ca.der()).unwrap();
+            assert_eq!(cert.issuer().to_string(), ca.subject().to_string());
+            assert!(alpn.is_none());
+
+            // With no pending challenges, challenge handshakes are refused.
+            assert!(handshake(&resolver, "example.com", Some(ACME_TLS_ALPN)).await.is_none());
+
+            // A restart reuses the cached certificate and account.
+            let restarted = self::state(&config, &stand_in);
+            restarted.load_cached().await.unwrap();
+            assert!(restarted.current.read().is_some());
+            restarted.account().await.unwrap();
+            assert_eq!(stand_in.0.session.lock().accounts, 1);
+        })
+    }
+
+    #[test]
+    fn test_tls_alpn_01_failed_validation() {
+        crate::async_test(async {
+            let cache = tempfile::tempdir().unwrap();
+            let config = stand_in_config(cache.path());
+            let stand_in = StandIn::new();
+            let state = state(&config, &stand_in);
+
+            // Validate against a resolver that isn't answering the challenges.
+            let imposter = self::state(&config, &stand_in);
+            stand_in.target(&imposter);
+
+            assert!(state.provision().await.is_err());
+            assert!(state.alpn_challenges.read().is_empty());
+            assert!(state.current.read().is_none());
+            assert!(!config.cert_path().exists());
+        })
+    }
+}
@@ -4,13 +4,14 @@ use std::sync::Arc;
 use futures::TryFutureExt;
 use figment::value::magic::{Either, RelativePathBuf};
 use serde::{Deserialize, Serialize};
-use indexmap::IndexSet;
+use indexmap::{IndexMap, IndexSet};
 use rustls::crypto::{ring, CryptoProvider};
 use rustls::pki_types::{CertificateDer, PrivateKeyDer};
 use rustls::server::{ServerSessionMemoryCache, ServerConfig, WebPkiClientVerifier};
 
 use crate::tls::resolver::DynResolver;
-use crate::tls::error::{Result, Error, KeyError};
+use crate::tls::sni::{self, SniCert, SniResolver};
+use crate::tls::error::{Result, KeyError};
 
 /// TLS configuration: certificate chain, key, and ciphersuites.
 ///
@@ -42,6 +43,11 @@ use crate::tls::error::{Result, Error, KeyError};
 /// [`mtls`](crate::mtls) module. See [`MtlsConfig`](crate::mtls::MtlsConfig)
 /// for configuration details.
 ///
+/// Finally, the `sni` parameter maps server names, or wildcard patterns like
+/// `*.example.com`, to alternate certificate chains and keys. Clients whose
+/// SNI matches an entry are presented with that certificate; all others are
+/// presented with the top-level `certs`. See [`SniCert`] for details.
+///
 /// In `Rocket.toml`, configuration might look like:
 ///
 /// ```toml
@@ -102,6 +108,9 @@ pub struct TlsConfig {
     #[cfg(feature = "mtls")]
     #[cfg_attr(nightly, doc(cfg(feature = "mtls")))]
     pub(crate) mutual: Option<crate::mtls::MtlsConfig>,
+    /// Certificate chains and keys selected by server name, if any.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub(crate) sni: IndexMap<String, SniCert>,
     #[serde(skip)]
     pub(crate) resolver: Option<DynResolver>,
 }
@@ -141,6 +150,7 @@ impl Default for TlsConfig {
             prefer_server_cipher_order: false,
             #[cfg(feature = "mtls")]
             mutual: None,
+            sni: IndexMap::new(),
             resolver: None,
         }
     }
@@ -308,6 +318,31 @@ impl TlsConfig {
         self
     }
 
+    /// Presents the certificate chain and key in `cert` to clients requesting
+    /// the server `name` via SNI. `name` may be a wildcard pattern like
+    /// `*.example.com`, which matches exactly one leftmost label. Exact names
+    /// take precedence over wildcards. If `name` was already configured, its
+    /// certificate is replaced.
+    ///
+    /// The certificate must be valid for `name`. This is checked when the
+    /// configuration is validated, at the latest during ignition.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::tls::{TlsConfig, SniCert};
+    ///
+    /// let tls_config = TlsConfig::from_paths("/ssl/certs.pem", "/ssl/key.pem")
+    ///     .with_sni("example.com", SniCert::from_paths("/ssl/a.pem", "/ssl/a_key.pem"))
+    ///     .with_sni("*.example.com", SniCert::from_paths("/ssl/b.pem", "/ssl/b_key.pem"));
+    ///
+    /// assert_eq!(tls_config.sni().count(), 2);
+    /// ```
+    pub fn with_sni<N: Into<String>>(mut self, name: N, cert: SniCert) -> Self {
+        self.sni.insert(name.into(), cert);
+        self
+    }
+
     /// Returns the value of the `certs` parameter.
     ///
     /// # Example
@@ -438,6 +473,32 @@ impl TlsConfig {
         self.mutual.as_ref()
     }
 
+    /// Returns an iterator over the configured SNI server names or patterns
+    /// and their certificates, in configuration order.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::tls::TlsConfig;
+    /// use rocket::figment::{Figment, providers::{Format, Toml}};
+    ///
+    /// let figment = Figment::from(Toml::string(r#"
+    ///     certs = "/ssl/certs.pem"
+    ///     key = "/ssl/key.pem"
+    ///
+    ///     [sni."example.com"]
+    ///     certs = "/ssl/example.pem"
+    ///     key = "/ssl/example_key.pem"
+    /// "#));
+    ///
+    /// let tls_config: TlsConfig = figment.extract().unwrap();
+    /// let (name, _) = tls_config.sni().next().unwrap();
+    /// assert_eq!(name, "example.com");
+    /// ```
+    pub fn sni(&self) -> impl Iterator<Item = (&str, &SniCert)> + '_ {
+        self.sni.iter().map(|(name, cert)| (name.as_str(), cert))
+    }
+
     /// Try to convert `self` into a [rustls] [`ServerConfig`].
     ///
     /// [`ServerConfig`]: rustls::server::ServerConfig
@@ -455,25 +516,26 @@ impl TlsConfig {
         let provider = Arc::new(self.default_crypto_provider());
 
         #[cfg(feature = "mtls")]
-        let verifier = match self.mutual {
-            Some(ref mtls) => {
-                let ca = Arc::new(mtls.load_ca_certs()?);
-                let verifier = WebPkiClientVerifier::builder_with_provider(ca, provider.clone());
-                match mtls.mandatory {
-                    true => verifier.build()?,
-                    false => verifier.allow_unauthenticated().build()?,
-                }
-            },
+        let verifier: Arc<dyn rustls::server::danger::ClientCertVerifier> = match self.mutual {
+            Some(ref mtls) => Arc::new(crate::mtls::ClientVerifier::new(mtls, provider.clone())?),
             None => WebPkiClientVerifier::no_client_auth(),
         };
 
         #[cfg(not(feature = "mtls"))]
         let verifier = WebPkiClientVerifier::no_client_auth();
 
-        let mut tls_config = ServerConfig::builder_with_provider(provider)
+        let builder = ServerConfig::builder_with_provider(provider.clone())
             .with_safe_default_protocol_versions()?
-            .with_client_cert_verifier(verifier)
-            .with_single_cert(self.load_certs()?, self.load_key()?)?;
+            .with_client_cert_verifier(verifier);
+
+        let mut tls_config = match self.sni.is_empty() {
+            true => builder.with_single_cert(self.load_certs()?, self.load_key()?)?,
+            false => {
+                let default = sni::certified_key(&self.certs, &self.key, &provider)?;
+                let resolver = SniResolver::new(default, &self.sni, &provider)?;
+                builder.with_cert_resolver(Arc::new(resolver))
+            }
+        };
 
         tls_config.ignore_client_order = self.prefer_server_cipher_order;
         tls_config.session_storage = ServerSessionMemoryCache::new(1024);
@@ -495,32 +557,13 @@ impl TlsConfig {
 /// Loads certificates from `reader`.
 impl TlsConfig {
     pub(crate) fn load_certs(&self) -> Result<Vec<CertificateDer<'static>>> {
-        rustls_pemfile::certs(&mut self.certs_reader()?)
-            .collect::<Result<_, _>>()
-            .map_err(Error::CertChain)
+        sni::load_certs(&mut self.certs_reader()?)
     }
 
     /// Load and decode the private key  from `reader`.
     pub(crate) fn load_key(&self) -> Result<PrivateKeyDer<'static>> {
-        use rustls_pemfile::Item::*;
-
-        let mut keys = rustls_pemfile::read_all(&mut self.key_reader()?)
-            .map(|result| result.map_err(KeyError::Io)
-                .and_then(|item| match item {
-                    Pkcs1Key(key) => Ok(key.into()),
-                    Pkcs8Key(key) => Ok(key.into()),
-                    Sec1Key(key) => Ok(key.into()),
-                    _ => Err(KeyError::BadItem(item))
-                })
-            )
-            .collect::<Result<Vec<PrivateKeyDer<'static>>, _>>()?;
-
-        if keys.len() != 1 {
-            return Err(KeyError::BadKeyCount(keys.len()).into());
-        }
-
         // Ensure we can use the key.
-        let key = keys.remove(0);
+        let key = sni::load_key(&mut self.key_reader()?)?;
         self.default_crypto_provider()
             .key_provider
             .load_private_key(key.clone_key())
@@ -595,6 +638,30 @@ impl CipherSuite {
         CipherSuite::TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256,
     ];
 
+    /// Maps a rustls cipher suite identifier to a `CipherSuite`, if it is one.
+    pub(crate) fn from_rustls(suite: rustls::CipherSuite) -> Option<Self> {
+        use rustls::CipherSuite as Rustls;
+
+        Some(match suite {
+            Rustls::TLS13_CHACHA20_POLY1305_SHA256 => CipherSuite::TLS_CHACHA20_POLY1305_SHA256,
+            Rustls::TLS13_AES_256_GCM_SHA384 => CipherSuite::TLS_AES_256_GCM_SHA384,
+            Rustls::TLS13_AES_128_GCM_SHA256 => CipherSuite::TLS_AES_128_GCM_SHA256,
+            Rustls::TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256 =>
+                CipherSuite::TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256,
+            Rustls::TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256 =>
+                CipherSuite::TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256,
+            Rustls::TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384 =>
+                CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384,
+            Rustls::TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256 =>
+                CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256,
+            Rustls::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384 =>
+                CipherSuite::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+            Rustls::TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256 =>
+                CipherSuite::TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256,
+            _ => return None,
+        })
+    }
+
     /// Used as the `serde` default for `ciphers`.
     fn default_set() -> IndexSet<Self> {
         Self::DEFAULT_SET.iter().copied().collect()
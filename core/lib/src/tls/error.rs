@@ -17,7 +17,11 @@ pub enum Error {
     CertChain(std::io::Error),
     PrivKey(KeyError),
     CertAuth(rustls::Error),
+    Crl(std::io::Error),
     Config(figment::Error),
+    Sni(String, rustls::Error),
+    #[cfg(feature = "acme")]
+    Acme(Box<dyn std::error::Error + Send + Sync>),
 }
 
 impl std::fmt::Display for Error {
@@ -31,8 +35,12 @@ impl std::fmt::Display for Error {
             CertChain(e) => write!(f, "failed to process certificate chain: {e}"),
             PrivKey(e) => write!(f, "failed to process private key: {e}"),
             CertAuth(e) => write!(f, "failed to process certificate authority: {e}"),
+            Crl(e) => write!(f, "failed to process certificate revocation list: {e}"),
             Bind(e) => write!(f, "failed to bind to network interface: {e}"),
             Config(e) => write!(f, "failed to read tls configuration: {e}"),
+            Sni(name, e) => write!(f, "invalid certificate for sni name `{name}`: {e}"),
+            #[cfg(feature = "acme")]
+            Acme(e) => write!(f, "acme provisioning failed: {e}"),
         }
     }
 }
@@ -70,8 +78,12 @@ impl std::error::Error for Error {
             Error::CertChain(e) => Some(e),
             Error::PrivKey(e) => Some(e),
             Error::CertAuth(e) => Some(e),
+            Error::Crl(e) => Some(e),
             Error::Bind(e) => Some(&**e),
             Error::Config(e) => Some(e),
+            Error::Sni(_, e) => Some(e),
+            #[cfg(feature = "acme")]
+            Error::Acme(e) => Some(&**e),
         }
     }
 }
@@ -0,0 +1,133 @@
+use std::fmt;
+
+use rustls::server::ServerConnection;
+
+use crate::tls::CipherSuite;
+use crate::request::{Request, FromRequest, Outcome};
+use crate::outcome::IntoOutcome;
+use crate::http::Status;
+
+/// Parameters negotiated in the TLS handshake of a client's connection.
+///
+/// # Request Guard
+///
+/// `&TlsInfo` is a request guard. It succeeds when the request arrived over a
+/// TLS connection and forwards with a status of 403 Forbidden otherwise.
+///
+/// # Example
+///
+/// Reject TLS 1.2 connections and log the negotiated cipher suite:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::http::Status;
+/// use rocket::tls::{TlsInfo, TlsVersion};
+///
+/// #[get("/account")]
+/// fn account(tls: &TlsInfo) -> Result<&'static str, Status> {
+///     info!(version = ?tls.version(), cipher = ?tls.cipher_suite(), "tls session");
+///     if tls.version() < Some(TlsVersion::V1_3) {
+///         return Err(Status::Forbidden);
+///     }
+///
+///     Ok("sensitive data")
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TlsInfo {
+    version: Option<TlsVersion>,
+    cipher_suite: Option<CipherSuite>,
+    alpn_protocol: Option<Vec<u8>>,
+    server_name: Option<String>,
+}
+
+/// A version of the TLS protocol.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum TlsVersion {
+    /// TLS 1.2.
+    V1_2,
+    /// TLS 1.3.
+    V1_3,
+}
+
+impl TlsInfo {
+    pub(crate) fn from_connection(conn: &ServerConnection) -> Self {
+        TlsInfo {
+            version: conn.protocol_version().and_then(TlsVersion::from_rustls),
+            cipher_suite: conn.negotiated_cipher_suite()
+                .and_then(|suite| CipherSuite::from_rustls(suite.suite())),
+            alpn_protocol: conn.alpn_protocol().map(|p| p.to_vec()),
+            server_name: conn.server_name().map(|s| s.to_string()),
+        }
+    }
+
+    /// Returns the negotiated TLS protocol version, if it is one of the
+    /// versions in [`TlsVersion`].
+    pub fn version(&self) -> Option<TlsVersion> {
+        self.version
+    }
+
+    /// Returns the negotiated cipher suite, if it is one of the suites in
+    /// [`CipherSuite`].
+    ///
+    /// The suite is always known unless a custom crypto provider negotiated a
+    /// suite that Rocket doesn't support natively.
+    pub fn cipher_suite(&self) -> Option<CipherSuite> {
+        self.cipher_suite
+    }
+
+    /// Returns the protocol negotiated via ALPN, if any, such as `b"h2"`.
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.alpn_protocol.as_deref()
+    }
+
+    /// Returns the server name the client requested via SNI, if any.
+    pub fn server_name(&self) -> Option<&str> {
+        self.server_name.as_deref()
+    }
+}
+
+impl TlsVersion {
+    fn from_rustls(version: rustls::ProtocolVersion) -> Option<Self> {
+        match version {
+            rustls::ProtocolVersion::TLSv1_2 => Some(TlsVersion::V1_2),
+            rustls::ProtocolVersion::TLSv1_3 => Some(TlsVersion::V1_3),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TlsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TlsVersion::V1_2 => "TLSv1.2".fmt(f),
+            TlsVersion::V1_3 => "TLSv1.3".fmt(f),
+        }
+    }
+}
+
+#[crate::async_trait]
+impl<'r> FromRequest<'r> for &'r TlsInfo {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        req.tls_info().or_forward(Status::Forbidden)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tls_version() {
+        use rustls::ProtocolVersion::*;
+
+        assert_eq!(TlsVersion::from_rustls(TLSv1_2), Some(TlsVersion::V1_2));
+        assert_eq!(TlsVersion::from_rustls(TLSv1_3), Some(TlsVersion::V1_3));
+        assert_eq!(TlsVersion::from_rustls(TLSv1_1), None);
+        assert!(TlsVersion::V1_2 < TlsVersion::V1_3);
+        assert_eq!(TlsVersion::V1_3.to_string(), "TLSv1.3");
+    }
+}
@@ -8,7 +8,7 @@ use rustls::server::{Acceptor, ServerConfig};
 
 use crate::{Ignite, Rocket};
 use crate::listener::{Bind, Certificates, Connection, Endpoint, Listener};
-use crate::tls::{TlsConfig, Result, Error};
+use crate::tls::{TlsConfig, TlsInfo, Result, Error};
 use super::resolver::DynResolver;
 
 #[doc(inline)]
@@ -105,4 +105,8 @@ impl<C: Connection> Connection for TlsStream<C> {
         #[cfg(not(feature = "tls"))]
         None
     }
+
+    fn tls_info(&self) -> Option<TlsInfo> {
+        Some(TlsInfo::from_connection(self.get_ref().1))
+    }
 }
@@ -1,9 +1,17 @@
 mod error;
 mod resolver;
 mod listener;
+mod sni;
+mod info;
 pub(crate) mod config;
 
+#[cfg(feature = "acme")]
+#[cfg_attr(nightly, doc(cfg(feature = "acme")))]
+pub mod acme;
+
 pub use error::{Error, Result};
 pub use config::{TlsConfig, CipherSuite};
+pub use sni::SniCert;
+pub use info::{TlsInfo, TlsVersion};
 pub use resolver::{Resolver, ClientHello, ServerConfig};
 pub use listener::{TlsListener, TlsStream};
@@ -0,0 +1,277 @@
+use std::io;
+use std::sync::Arc;
+use std::collections::HashMap;
+
+use figment::value::magic::{Either, RelativePathBuf};
+use serde::{Deserialize, Serialize};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::{ClientHello, ResolvesServerCert, ResolvesServerCertUsingSni};
+use rustls::sign::CertifiedKey;
+
+use crate::tls::config::to_reader;
+use crate::tls::error::{Result, Error, KeyError};
+
+/// A certificate chain and private key pair selected by SNI.
+///
+/// An `SniCert` is the value in the `sni` map of a
+/// [`TlsConfig`](crate::tls::TlsConfig). Its `certs` and `key` parameters are
+/// configured exactly as the eponymous parameters in `TlsConfig`: as a path or
+/// as raw bytes.
+///
+/// In `Rocket.toml`, configuration might look like:
+///
+/// ```toml
+/// [default.tls]
+/// certs = "private/default_cert.pem"
+/// key = "private/default_key.pem"
+///
+/// [default.tls.sni."example.com"]
+/// certs = "private/example_cert.pem"
+/// key = "private/example_key.pem"
+///
+/// [default.tls.sni."*.example.com"]
+/// certs = "private/wildcard_cert.pem"
+/// key = "private/wildcard_key.pem"
+/// ```
+///
+/// A client requesting `example.com` is presented with `example_cert.pem`, one
+/// requesting `api.example.com` with `wildcard_cert.pem`, and any other client,
+/// including one that doesn't send SNI, with `default_cert.pem`.
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct SniCert {
+    /// Path to a PEM file with, or raw bytes for, a DER-encoded X.509 TLS
+    /// certificate chain.
+    pub(crate) certs: Either<RelativePathBuf, Vec<u8>>,
+    /// Path to a PEM file with, or raw bytes for, DER-encoded private key in
+    /// either PKCS#8 or PKCS#1 format.
+    pub(crate) key: Either<RelativePathBuf, Vec<u8>>,
+}
+
+/// Resolves a certificate by exact server name, then by wildcard, then falls
+/// back to the default certificate.
+#[derive(Debug)]
+pub(crate) struct SniResolver {
+    names: HashMap<String, Arc<CertifiedKey>>,
+    wildcards: HashMap<String, Arc<CertifiedKey>>,
+    default: Arc<CertifiedKey>,
+}
+
+impl SniCert {
+    /// Constructs an `SniCert` from paths to a `certs` certificate chain and a
+    /// `key` private-key. This method does no validation.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::tls::SniCert;
+    ///
+    /// let cert = SniCert::from_paths("/ssl/example.pem", "/ssl/example_key.pem");
+    /// ```
+    pub fn from_paths<C, K>(certs: C, key: K) -> Self
+        where C: AsRef<std::path::Path>, K: AsRef<std::path::Path>,
+    {
+        SniCert {
+            certs: Either::Left(certs.as_ref().to_path_buf().into()),
+            key: Either::Left(key.as_ref().to_path_buf().into()),
+        }
+    }
+
+    /// Constructs an `SniCert` from byte buffers to a `certs` certificate chain
+    /// and a `key` private-key. This method does no validation.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::tls::SniCert;
+    ///
+    /// # let certs_buf = &[];
+    /// # let key_buf = &[];
+    /// let cert = SniCert::from_bytes(certs_buf, key_buf);
+    /// ```
+    pub fn from_bytes(certs: &[u8], key: &[u8]) -> Self {
+        SniCert {
+            certs: Either::Right(certs.to_vec()),
+            key: Either::Right(key.to_vec()),
+        }
+    }
+
+    /// Returns the value of the `certs` parameter.
+    pub fn certs(&self) -> either::Either<std::path::PathBuf, &[u8]> {
+        match &self.certs {
+            Either::Left(path) => either::Either::Left(path.relative()),
+            Either::Right(bytes) => either::Either::Right(bytes),
+        }
+    }
+
+    /// Returns the value of the `key` parameter.
+    pub fn key(&self) -> either::Either<std::path::PathBuf, &[u8]> {
+        match &self.key {
+            Either::Left(path) => either::Either::Left(path.relative()),
+            Either::Right(bytes) => either::Either::Right(bytes),
+        }
+    }
+}
+
+/// Loads a certificate chain and a single private key into a `CertifiedKey`.
+pub(crate) fn certified_key(
+    certs: &Either<RelativePathBuf, Vec<u8>>,
+    key: &Either<RelativePathBuf, Vec<u8>>,
+    provider: &CryptoProvider,
+) -> Result<CertifiedKey> {
+    let certs = load_certs(&mut to_reader(certs)?)?;
+    let key = load_key(&mut to_reader(key)?)?;
+    let key = provider.key_provider
+        .load_private_key(key)
+        .map_err(KeyError::Unsupported)?;
+
+    Ok(CertifiedKey::new(certs, key))
+}
+
+pub(crate) fn load_certs(reader: &mut dyn io::BufRead) -> Result<Vec<CertificateDer<'static>>> {
+    rustls_pemfile::certs(reader)
+        .collect::<Result<_, _>>()
+        .map_err(Error::CertChain)
+}
+
+pub(crate) fn load_key(reader: &mut dyn io::BufRead) -> Result<PrivateKeyDer<'static>> {
+    use rustls_pemfile::Item::*;
+
+    let mut keys = rustls_pemfile::read_all(reader)
+        .map(|result| result.map_err(KeyError::Io)
+            .and_then(|item| match item {
+                Pkcs1Key(key) => Ok(key.into()),
+                Pkcs8Key(key) => Ok(key.into()),
+                Sec1Key(key) => Ok(key.into()),
+                _ => Err(KeyError::BadItem(item))
+            })
+        )
+        .collect::<Result<Vec<PrivateKeyDer<'static>>, _>>()?;
+
+    if keys.len() != 1 {
+        return Err(KeyError::BadKeyCount(keys.len()).into());
+    }
+
+    Ok(keys.remove(0))
+}
+
+impl SniResolver {
+    /// The label substituted for `*` when checking that a certificate covers a
+    /// wildcard pattern.
+    const WILDCARD_PROBE: &'static str = "rocket-sni-probe";
+
+    /// Loads every configured certificate and checks that each is valid for
+    /// the server name or wildcard pattern it's configured for.
+    pub(crate) fn new<'a, I>(default: CertifiedKey, entries: I, provider: &CryptoProvider) -> Result<Self>
+        where I: IntoIterator<Item = (&'a String, &'a SniCert)>
+    {
+        let mut resolver = SniResolver {
+            names: HashMap::new(),
+            wildcards: HashMap::new(),
+            default: Arc::new(default),
+        };
+
+        for (name, cert) in entries {
+            let sni_error = |e: rustls::Error| Error::Sni(name.clone(), e);
+            let key = certified_key(&cert.certs, &cert.key, provider)?;
+            let pattern = name.to_ascii_lowercase();
+            let (probe, suffix) = match pattern.strip_prefix("*.") {
+                Some(suffix) => (format!("{}.{suffix}", Self::WILDCARD_PROBE), Some(suffix)),
+                None => (pattern.clone(), None),
+            };
+
+            if probe.contains('*') {
+                let msg = "wildcards are only allowed as the entire leftmost label";
+                return Err(sni_error(rustls::Error::General(msg.into())));
+            }
+
+            // `ResolvesServerCertUsingSni` checks that the certificate's
+            // subject alternative names cover the server name.
+            ResolvesServerCertUsingSni::new()
+                .add(&probe, key.clone())
+                .map_err(sni_error)?;
+
+            match suffix {
+                Some(suffix) => resolver.wildcards.insert(suffix.into(), Arc::new(key)),
+                None => resolver.names.insert(pattern, Arc::new(key)),
+            };
+        }
+
+        Ok(resolver)
+    }
+
+    fn lookup(&self, server_name: &str) -> Option<&Arc<CertifiedKey>> {
+        let name = server_name.to_ascii_lowercase();
+        self.names.get(&name).or_else(|| {
+            let (_, parent) = name.split_once('.')?;
+            self.wildcards.get(parent)
+        })
+    }
+}
+
+impl ResolvesServerCert for SniResolver {
+    fn resolve(&self, hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        hello.server_name()
+            .and_then(|name| self.lookup(name))
+            .or(Some(&self.default))
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! tls_example_private_pem {
+        ($k:expr) => {
+            concat!(env!("CARGO_MANIFEST_DIR"), "/../../examples/tls/private/", $k)
+        }
+    }
+
+    fn localhost_cert() -> SniCert {
+        SniCert::from_paths(
+            tls_example_private_pem!("rsa_sha256_cert.pem"),
+            tls_example_private_pem!("rsa_sha256_key.pem"),
+        )
+    }
+
+    fn resolver(names: &[&str]) -> Result<SniResolver> {
+        let provider = rustls::crypto::ring::default_provider();
+        let cert = localhost_cert();
+        let default = certified_key(&cert.certs, &cert.key, &provider)?;
+        let entries: Vec<_> = names.iter()
+            .map(|name| (name.to_string(), localhost_cert()))
+            .collect();
+
+        SniResolver::new(default, entries.iter().map(|(n, c)| (n, c)), &provider)
+    }
+
+    #[test]
+    fn sni_names_must_match_certificates() {
+        assert!(resolver(&["localhost"]).is_ok());
+        assert!(resolver(&["LocalHost"]).is_ok());
+
+        let error = resolver(&["localhost", "example.com"]).unwrap_err();
+        assert!(matches!(error, Error::Sni(name, _) if name == "example.com"));
+
+        let error = resolver(&["*.localhost"]).unwrap_err();
+        assert!(matches!(error, Error::Sni(name, _) if name == "*.localhost"));
+
+        let error = resolver(&["local*"]).unwrap_err();
+        assert!(matches!(error, Error::Sni(name, _) if name == "local*"));
+    }
+
+    #[test]
+    fn sni_lookup_prefers_exact_names() {
+        let mut resolver = resolver(&["localhost"]).unwrap();
+        let exact = resolver.names["localhost"].clone();
+        resolver.wildcards.insert("example.com".into(), exact.clone());
+
+        assert!(Arc::ptr_eq(resolver.lookup("localhost").unwrap(), &exact));
+        assert!(Arc::ptr_eq(resolver.lookup("LOCALHOST").unwrap(), &exact));
+        assert!(Arc::ptr_eq(resolver.lookup("a.example.com").unwrap(), &exact));
+        assert!(resolver.lookup("example.com").is_none());
+        assert!(resolver.lookup("a.b.example.com").is_none());
+        assert!(resolver.lookup("rocket.rs").is_none());
+    }
+}
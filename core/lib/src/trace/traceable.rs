@@ -354,6 +354,8 @@ impl Trace for ErrorKind {
             ),
             Liftoff(_, reason) => event!(level, "panic", %reason, "liftoff fairing failed"),
             Shutdown(_) => event!(level, "shutdown", "shutdown failed"),
+            #[cfg(feature = "tls")]
+            Tls(reason) => event!(level, "error::tls", %reason, "invalid tls configuration"),
         }
     }
 }
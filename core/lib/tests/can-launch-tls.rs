@@ -21,3 +21,10 @@ async fn can_launch_tls() {
     client.rocket().shutdown().notify();
     client.rocket().shutdown().await;
 }
+
+#[rocket::async_test]
+async fn malformed_tls_config_fails_ignite() {
+    let config = rocket::Config::figment().merge(("tls", "not a table"));
+    let error = rocket::custom(config).ignite().await.unwrap_err();
+    assert!(matches!(error.kind(), rocket::error::ErrorKind::Config(_)));
+}
@@ -0,0 +1,79 @@
+#[macro_use] extern crate rocket;
+
+use rocket::http::{Header, HeaderMap, Status};
+use rocket::local::blocking::Client;
+use rocket::request::{self, FromRequest, Request};
+use rocket::response::EarlyHints;
+
+/// A guard that sends hints before the handler runs.
+struct Preconnect;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Preconnect {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let mut hints = HeaderMap::new();
+        hints.add(Header::new("Link", "<https://cdn.example.com>; rel=preconnect"));
+        req.send_early_hints(hints);
+        req.send_early_hints(HeaderMap::new());
+        request::Outcome::Success(Preconnect)
+    }
+}
+
+#[get("/")]
+fn index() -> EarlyHints<&'static str> {
+    let mut fonts = HeaderMap::new();
+    fonts.add(Header::new("Link", "</font.woff2>; rel=preload; as=font"));
+
+    EarlyHints::new("Hello, world!")
+        .preload("/style.css", "style")
+        .preload("/app.js", "script")
+        .hints(fonts)
+}
+
+#[get("/guarded")]
+fn guarded(_guard: Preconnect) -> EarlyHints<&'static str> {
+    EarlyHints::new("Hello, world!").preload("/style.css", "style")
+}
+
+#[get("/none")]
+fn none() -> EarlyHints<&'static str> {
+    EarlyHints::new("Hello, world!")
+}
+
+#[test]
+fn early_hints_are_attached() {
+    let client = Client::debug_with(routes![index, none]).unwrap();
+    let response = client.get("/").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+
+    let hints: Vec<Vec<_>> = response.early_hints()
+        .map(|hints| hints.get("Link").collect())
+        .collect();
+
+    assert_eq!(hints, vec![
+        vec!["</style.css>; rel=preload; as=style", "</app.js>; rel=preload; as=script"],
+        vec!["</font.woff2>; rel=preload; as=font"],
+    ]);
+
+    assert!(response.headers().get_one("Link").is_none());
+    assert_eq!(response.into_string().unwrap(), "Hello, world!");
+
+    let response = client.get("/none").dispatch();
+    assert_eq!(response.early_hints().count(), 0);
+}
+
+#[test]
+fn early_hints_sent_by_request_precede_response_hints() {
+    let client = Client::debug_with(routes![guarded]).unwrap();
+    let response = client.get("/guarded").dispatch();
+    let hints: Vec<Vec<_>> = response.early_hints()
+        .map(|hints| hints.get("Link").collect())
+        .collect();
+
+    assert_eq!(hints, vec![
+        vec!["<https://cdn.example.com>; rel=preconnect"],
+        vec!["</style.css>; rel=preload; as=style"],
+    ]);
+}
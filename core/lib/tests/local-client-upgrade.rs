@@ -0,0 +1,74 @@
+#[macro_use] extern crate rocket;
+
+use rocket::{Request, tokio::io};
+use rocket::data::{IoHandler, IoStream};
+use rocket::http::{Header, Status};
+use rocket::local::asynchronous::Client;
+use rocket::response::{self, Responder, Response};
+use rocket::tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+struct Echo;
+
+#[rocket::async_trait]
+impl IoHandler for Echo {
+    async fn io(self: Box<Self>, io: IoStream) -> io::Result<()> {
+        let (mut reader, mut writer) = io::split(io);
+        io::copy(&mut reader, &mut writer).await?;
+        Ok(())
+    }
+}
+
+impl<'r> Responder<'r, 'static> for Echo {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+        Response::build().upgrade("echo", self).ok()
+    }
+}
+
+#[get("/echo")]
+fn echo() -> Echo {
+    Echo
+}
+
+#[async_test]
+async fn local_upgrade_runs_io_handler() {
+    let client = Client::debug_with(routes![echo]).await.unwrap();
+    let mut io = client.get("/echo")
+        .header(Header::new("Upgrade", "echo"))
+        .upgrade()
+        .await
+        .expect("upgraded");
+
+    let response = io.response();
+    assert_eq!(response.status(), Status::SwitchingProtocols);
+    assert_eq!(response.headers().get_one("Upgrade"), Some("echo"));
+    assert_eq!(response.headers().get_one("Connection"), Some("Upgrade"));
+
+    for message in ["hello", "there", "rocket"] {
+        io.write_all(message.as_bytes()).await.unwrap();
+        let mut buf = vec![0; message.len()];
+        io.read_exact(&mut buf).await.unwrap();
+        assert_eq!(buf, message.as_bytes());
+    }
+
+    io.shutdown().await.unwrap();
+    let mut rest = vec![];
+    io.read_to_end(&mut rest).await.unwrap();
+    assert!(rest.is_empty());
+    assert!(io.is_finished());
+}
+
+#[async_test]
+async fn local_upgrade_requires_matching_protocol() {
+    let client = Client::debug_with(routes![echo]).await.unwrap();
+
+    let response = client.get("/echo").upgrade().await.expect_err("no upgrade header");
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client.get("/echo")
+        .header(Header::new("Upgrade", "websocket"))
+        .upgrade()
+        .await
+        .expect_err("no matching protocol");
+
+    assert_eq!(response.status(), Status::Ok);
+}
@@ -0,0 +1,141 @@
+#![cfg(feature = "mtls")]
+
+#[macro_use] extern crate rocket;
+
+use rocket::http::Status;
+use rocket::local::blocking::Client;
+use rocket::mtls::{Certificate, SpiffeId, SpiffePattern, SubjectAltName};
+
+use rcgen::{CertificateParams, ExtendedKeyUsagePurpose, KeyPair, KeyUsagePurpose, SanType};
+use sha2::{Digest, Sha256};
+
+struct Prod;
+
+impl SpiffePattern for Prod {
+    const PATTERN: &'static str = "spiffe://example.org/ns/prod/*";
+}
+
+struct Dev;
+
+impl SpiffePattern for Dev {
+    const PATTERN: &'static str = "spiffe://example.org/ns/dev/*";
+}
+
+struct ShoutingProd;
+
+impl SpiffePattern for ShoutingProd {
+    const PATTERN: &'static str = "spiffe://EXAMPLE.ORG/ns/prod/*";
+}
+
+#[get("/cert")]
+fn cert(cert: Certificate<'_>) -> String {
+    let sans: Vec<_> = cert.subject_alt_names().map(|name| name.to_string()).collect();
+    let hosts: Vec<_> = cert.subject_alt_names()
+        .filter_map(|name| match name {
+            SubjectAltName::Dns(host) => Some(host),
+            _ => None,
+        })
+        .collect();
+
+    let signs = cert.key_usage().map_or(false, |usage| usage.digital_signature());
+    let client_auth = cert.extended_key_usage().map_or(false, |usage| usage.client_auth);
+    let fingerprint: String = cert.sha256_fingerprint().iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+
+    format!("{}|{}|{}|{}|{}|{}|{}|{}",
+        sans.join(","), hosts.join(","), cert.spiffe_id().unwrap_or("-"),
+        signs, client_auth, cert.not_before().year(), cert.not_after().year(), fingerprint)
+}
+
+#[get("/any")]
+fn any(id: SpiffeId<'_>) -> String {
+    format!("{} {} {}", id.as_str(), id.trust_domain(), id.path())
+}
+
+#[get("/prod")]
+fn prod(id: SpiffeId<'_, Prod>) -> String {
+    id.to_string()
+}
+
+#[get("/dev")]
+fn dev(id: SpiffeId<'_, Dev>) -> String {
+    id.to_string()
+}
+
+#[get("/shouting")]
+fn shouting(id: SpiffeId<'_, ShoutingProd>) -> String {
+    id.to_string()
+}
+
+fn client() -> Client {
+    Client::debug_with(routes![cert, any, prod, dev, shouting]).unwrap()
+}
+
+/// Returns a PEM-encoded client certificate, optionally with the SPIFFE ID
+/// `spiffe`, and its DER encoding.
+fn identity(spiffe: Option<&str>) -> (String, Vec<u8>) {
+    let key = KeyPair::generate().unwrap();
+    let mut params = CertificateParams::new(vec!["Web.Example.com".to_string()]).unwrap();
+    if let Some(spiffe) = spiffe {
+        params.subject_alt_names.push(SanType::URI(spiffe.try_into().unwrap()));
+    }
+
+    params.subject_alt_names.push(SanType::Rfc822Name("web@example.com".try_into().unwrap()));
+    params.subject_alt_names.push(SanType::IpAddress([10, 0, 0, 1].into()));
+    params.key_usages = vec![KeyUsagePurpose::DigitalSignature];
+    params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+    params.not_before = rcgen::date_time_ymd(2024, 1, 1);
+    params.not_after = rcgen::date_time_ymd(2099, 1, 1);
+
+    let cert = params.self_signed(&key).unwrap();
+    (cert.pem(), cert.der().to_vec())
+}
+
+#[test]
+fn certificate_accessors() {
+    let client = client();
+    let (pem, der) = identity(Some("spiffe://example.org/ns/prod/sa/web"));
+    let response = client.get("/cert").identity(pem.as_bytes()).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+
+    let fingerprint: String = Sha256::digest(&der).iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+
+    assert_eq!(response.into_string().unwrap(), format!("{}|{}|{}|true|true|2024|2099|{}",
+        "Web.Example.com,spiffe://example.org/ns/prod/sa/web,web@example.com,10.0.0.1",
+        "Web.Example.com", "spiffe://example.org/ns/prod/sa/web", fingerprint));
+
+    let response = client.get("/cert").dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+}
+
+#[test]
+fn spiffe_id_guard() {
+    let client = client();
+    let (pem, _) = identity(Some("spiffe://example.org/ns/prod/sa/web"));
+
+    let response = client.get("/any").identity(pem.as_bytes()).dispatch();
+    assert_eq!(response.into_string().unwrap(),
+        "spiffe://example.org/ns/prod/sa/web example.org /ns/prod/sa/web");
+
+    let response = client.get("/prod").identity(pem.as_bytes()).dispatch();
+    assert_eq!(response.into_string().unwrap(), "spiffe://example.org/ns/prod/sa/web");
+
+    let response = client.get("/dev").identity(pem.as_bytes()).dispatch();
+    assert_eq!(response.status(), Status::Forbidden);
+
+    // SPIFFE IDs are matched case-sensitively.
+    let response = client.get("/shouting").identity(pem.as_bytes()).dispatch();
+    assert_eq!(response.status(), Status::Forbidden);
+
+    // A certificate without a SPIFFE ID is forbidden.
+    let (pem, _) = identity(None);
+    let response = client.get("/any").identity(pem.as_bytes()).dispatch();
+    assert_eq!(response.status(), Status::Forbidden);
+
+    // No certificate at all forwards, as `Certificate` does.
+    let response = client.get("/any").dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+}
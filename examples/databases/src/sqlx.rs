@@ -1,5 +1,5 @@
-use rocket::{Rocket, Build, futures};
-use rocket::fairing::{self, AdHoc};
+use rocket::futures;
+use rocket::fairing::AdHoc;
 use rocket::response::status::Created;
 use rocket::serde::{Serialize, Deserialize, json::Json};
 
@@ -73,23 +73,9 @@ async fn destroy(mut db: Connection<Db>) -> Result<()> {
     Ok(())
 }
 
-async fn run_migrations(rocket: Rocket<Build>) -> fairing::Result {
-    match Db::fetch(&rocket) {
-        Some(db) => match sqlx::migrate!("db/sqlx/migrations").run(&**db).await {
-            Ok(_) => Ok(rocket),
-            Err(e) => {
-                error!("Failed to initialize SQLx database: {}", e);
-                Err(rocket)
-            }
-        }
-        None => Err(rocket),
-    }
-}
-
 pub fn stage() -> AdHoc {
     AdHoc::on_ignite("SQLx Stage", |rocket| async {
-        rocket.attach(Db::init())
-            .attach(AdHoc::try_on_ignite("SQLx Migrations", run_migrations))
+        rocket.attach(Db::init().migrations(sqlx::migrate!("db/sqlx/migrations")))
             .mount("/sqlx", routes![list, create, read, delete, destroy])
     })
 }
@@ -1,5 +1,7 @@
 #[macro_use] extern crate rocket;
 
+#[cfg(test)] mod tests;
+
 use rocket::fs::{self, FileServer};
 use rocket::futures::{SinkExt, StreamExt};
 
@@ -21,6 +23,9 @@ fn echo_channel(ws: ws::WebSocket) -> ws::Channel<'static> {
         ..Default::default()
     });
 
+    // compress messages if the client supports it
+    let ws = ws.deflate(ws::DeflateConfig::default());
+
     ws.channel(move |mut stream| Box::pin(async move {
         while let Some(message) = stream.next().await {
             let _ = stream.send(message?).await;
@@ -0,0 +1,31 @@
+use rocket::local::asynchronous::Client;
+use rocket::futures::{SinkExt, StreamExt};
+use rocket::http::Status;
+
+use ws::Message;
+use ws::local::LocalWebSocket;
+
+#[rocket::async_test]
+async fn test_echo() {
+    let client = Client::tracked(super::rocket()).await.unwrap();
+    for uri in ["/echo?stream", "/echo?channel", "/echo?raw"] {
+        let mut stream = client.get(uri).websocket().await.expect("handshake");
+        let response = stream.get_ref().response();
+        assert_eq!(response.status(), Status::SwitchingProtocols);
+
+        for message in [Message::text("hello"), Message::binary(vec![1, 2, 3])] {
+            stream.send(message.clone()).await.unwrap();
+            assert_eq!(stream.next().await.unwrap().unwrap(), message);
+        }
+
+        stream.close(None).await.unwrap();
+        assert!(matches!(stream.next().await, Some(Ok(Message::Close(_))) | None));
+    }
+}
+
+#[rocket::async_test]
+async fn test_not_websocket() {
+    let client = Client::tracked(super::rocket()).await.unwrap();
+    let response = client.get("/echo?stream").dispatch().await;
+    assert_eq!(response.status(), Status::NotFound);
+}
@@ -77,12 +77,15 @@ function test_contrib() {
   DB_POOLS_FEATURES=(
     deadpool_postgres
     deadpool_redis
+    deadpool_sqlite
     sqlx_mysql
     sqlx_postgres
     sqlx_sqlite
     mongodb
     diesel_mysql
     diesel_postgres
+    sqlx_migrate
+    diesel_migrate
   )
 
   SYNC_DB_POOLS_FEATURES=(
@@ -102,6 +105,8 @@ function test_contrib() {
 
   WS_FEATURES=(
     tungstenite
+    json
+    msgpack
   )
 
   for feature in "${DB_POOLS_FEATURES[@]}"; do
@@ -129,10 +134,11 @@ function test_core() {
   FEATURES=(
     tokio-macros
     http2
-    http3-preview
+    http3
     secrets
     tls
     mtls
+    acme
     json
     msgpack
     uuid
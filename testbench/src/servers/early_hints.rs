@@ -0,0 +1,41 @@
+use crate::prelude::*;
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+use rocket::response::EarlyHints;
+
+#[get("/")]
+fn index() -> EarlyHints<&'static str> {
+    EarlyHints::new("Hello, world!").preload("/style.css", "style")
+}
+
+fn raw_request(server: &Server, version: &str) -> Result<String> {
+    let mut stream = TcpStream::connect(server.socket_addr())?;
+    write!(stream, "GET / {version}\r\nHost: localhost\r\nConnection: close\r\n\r\n")?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    Ok(response)
+}
+
+fn test_early_hints() -> Result<()> {
+    let server = spawn! {
+        Rocket::default().mount("/", routes![index])
+    }?;
+
+    let response = raw_request(&server, "HTTP/1.1")?;
+    let (interim, last) = response.split_once("\r\n\r\n").unwrap();
+    assert_eq!(interim, "HTTP/1.1 103 Early Hints\r\nlink: </style.css>; rel=preload; as=style");
+    assert!(last.starts_with("HTTP/1.1 200 OK"), "{last}");
+    assert!(last.ends_with("Hello, world!"), "{last}");
+
+    let response = raw_request(&server, "HTTP/1.0")?;
+    assert!(!response.contains("103"), "{response}");
+    assert!(response.contains("200 OK"), "{response}");
+    assert!(response.ends_with("Hello, world!"), "{response}");
+
+    Ok(())
+}
+
+register!(test_early_hints);
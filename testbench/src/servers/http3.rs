@@ -0,0 +1,84 @@
+use crate::prelude::*;
+
+use std::io;
+use std::future::poll_fn;
+
+use bytes::Buf;
+use s2n_quic::client::{Client as QuicClient, Connect};
+use s2n_quic_h3::h3;
+
+use rocket::listener::Endpoint;
+
+#[get("/")]
+fn hello_world(endpoint: &Endpoint) -> String {
+    format!("Hello, {endpoint}!")
+}
+
+/// Sends a `GET` request for `path` to `server` over HTTP/3, returning the
+/// response status and body.
+fn h3_get(server: &Server, path: &str) -> Result<(http::StatusCode, String)> {
+    use s2n_quic::provider::tls::rustls::Client as TlsClient;
+
+    rocket::execute(async move {
+        let ca = rocket::fs::relative!("../examples/tls/private/ca_cert.pem");
+        let tls = TlsClient::builder()
+            .with_certificate(std::path::Path::new(ca))
+            .and_then(|b| b.with_application_protocols(["h3"].into_iter()))
+            .and_then(|b| b.build())
+            .map_err(io::Error::other)?;
+
+        let client = QuicClient::builder()
+            .with_tls(tls)
+            .map_err(io::Error::other)?
+            .with_io("0.0.0.0:0")
+            .map_err(io::Error::other)?
+            .start()
+            .map_err(io::Error::other)?;
+
+        let connect = Connect::new(server.socket_addr()).with_server_name("localhost");
+        let conn = client.connect(connect).await.map_err(io::Error::other)?;
+        let (mut driver, mut sender) = h3::client::new(s2n_quic_h3::Connection::new(conn))
+            .await
+            .map_err(io::Error::other)?;
+
+        rocket::tokio::spawn(async move { poll_fn(|cx| driver.poll_close(cx)).await });
+
+        let uri = format!("https://localhost:{}{path}", server.port);
+        let request = http::Request::get(uri).body(()).map_err(io::Error::other)?;
+        let mut stream = sender.send_request(request).await.map_err(io::Error::other)?;
+        stream.finish().await.map_err(io::Error::other)?;
+
+        let response = stream.recv_response().await.map_err(io::Error::other)?;
+        let mut body = vec![];
+        while let Some(mut chunk) = stream.recv_data().await.map_err(io::Error::other)? {
+            body.extend_from_slice(&chunk.copy_to_bytes(chunk.remaining()));
+        }
+
+        let body = String::from_utf8(body).map_err(io::Error::other)?;
+        Ok((response.status(), body))
+    })
+}
+
+fn test_http3_works() -> Result<()> {
+    let mut server = spawn! {
+        Rocket::tls_default().mount("/", routes![hello_world])
+    }?;
+
+    let (status, body) = h3_get(&server, "/")?;
+    assert_eq!(status, http::StatusCode::OK);
+    assert!(body.starts_with("Hello, https://127.0.0.1"), "{body}");
+
+    let client = Client::default();
+    let response = client.get(&server, "/")?.send()?;
+    let alt_svc = response.headers().get("alt-svc").unwrap().to_str().unwrap();
+    assert_eq!(alt_svc, format!("h3=\":{}\"", server.port));
+
+    server.terminate()?;
+    let stdout = server.read_stdout()?;
+    assert!(stdout.contains("QUIC"));
+    assert!(stdout.contains("Graceful shutdown completed"));
+
+    Ok(())
+}
+
+register!(test_http3_works);
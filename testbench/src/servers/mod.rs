@@ -8,3 +8,6 @@ pub mod sni_resolver;
 pub mod tracing;
 pub mod tls;
 pub mod no_content;
+pub mod http3;
+pub mod early_hints;
+pub mod tls_info;
@@ -0,0 +1,53 @@
+use crate::prelude::*;
+
+use rocket::tls::TlsInfo;
+
+static TLS_INFO_CONFIG: &str = r#"
+    [default.tls]
+    certs = "{ROCKET}/examples/tls/private/rsa_sha256_cert.pem"
+    key = "{ROCKET}/examples/tls/private/rsa_sha256_key.pem"
+    ciphers = ["TLS_CHACHA20_POLY1305_SHA256", "TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256"]
+"#;
+
+#[get("/")]
+fn info(tls: &TlsInfo) -> String {
+    let alpn = tls.alpn_protocol().map(String::from_utf8_lossy);
+    format!("{:?} {:?} {:?} {:?}", tls.version(), tls.cipher_suite(), alpn, tls.server_name())
+}
+
+fn test_tls_info() -> Result<()> {
+    let server = spawn! {
+        Rocket::default()
+            .reconfigure_with_toml(TLS_INFO_CONFIG)
+            .mount("/", routes![info])
+    }?;
+
+    let client: Client = Client::build()
+        .resolve("tls-info.dev", server.socket_addr())
+        .try_into()?;
+
+    let response = client.get(&server, "https://tls-info.dev")?.send()?.text()?;
+    assert_eq!(response,
+        r#"Some(V1_3) Some(TLS_CHACHA20_POLY1305_SHA256) Some("h2") Some("tls-info.dev")"#);
+
+    let client: Client = Client::build()
+        .resolve("tls-info.dev", server.socket_addr())
+        .max_tls_version(reqwest::tls::Version::TLS_1_2)
+        .http1_only()
+        .try_into()?;
+
+    let response = client.get(&server, "https://tls-info.dev")?.send()?.text()?;
+    assert_eq!(response, concat!(
+        r#"Some(V1_2) Some(TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256) "#,
+        r#"Some("http/1.1") Some("tls-info.dev")"#
+    ));
+
+    // Without SNI, as when connecting to an IP address, there's no server name.
+    let response = Client::default().get(&server, "/")?.send()?.text()?;
+    assert!(response.starts_with("Some(V1_3)"), "{response}");
+    assert!(response.ends_with(" None"), "{response}");
+
+    Ok(())
+}
+
+register!(test_tls_info);